@@ -0,0 +1,338 @@
+use clap::{Args, ValueEnum};
+use rayon::prelude::*;
+use serde::Serialize;
+use shared::credit_scoring::{
+    CreditScore, ModelRegistry, ReasonCode, ScoringConfig, ScoringEngine, ScoringModel,
+};
+use shared::openbank::TransactionsResponse;
+use shared::result::{Error, Result};
+use std::collections::BTreeMap;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::time::Instant;
+
+#[derive(Clone, Copy, ValueEnum)]
+enum Format {
+    Csv,
+    Jsonl,
+}
+
+#[derive(Args)]
+pub struct BatchArgs {
+    /// Directory of statements, one customer per `.json` file: a
+    /// `TransactionsResponse` body or a JSON array of transactions.
+    #[arg(long)]
+    input: PathBuf,
+    /// Scoring config; the default heuristic model when unset.
+    #[arg(long)]
+    config: Option<PathBuf>,
+    /// Score with this registered model instead of the config's:
+    /// `heuristic_weighted` or `recency_weighted`. Scorecard, GBDT and ONNX
+    /// models carry their artifact, so select them in `--config`.
+    #[arg(long)]
+    model: Option<String>,
+    /// Where to write one result per statement.
+    #[arg(long)]
+    out: PathBuf,
+    /// Taken from the `out` extension when unset, JSON lines unless it is
+    /// `.csv`.
+    #[arg(long, value_enum)]
+    format: Option<Format>,
+    /// Worker threads; one per core when unset.
+    #[arg(long)]
+    jobs: Option<usize>,
+}
+
+/// What one statement scored, or why it couldn't be.
+#[derive(Serialize)]
+struct BatchResult {
+    file: String,
+    transactions: usize,
+    score: Option<CreditScore>,
+    error: Option<Error>,
+}
+
+pub fn run(args: BatchArgs) -> Result<()> {
+    let mut config: ScoringConfig = match &args.config {
+        Some(path) => serde_json::from_str(&std::fs::read_to_string(path)?)?,
+        None => ScoringConfig::default(),
+    };
+    if let Some(id) = args.model {
+        config.model = ScoringModel::Registered { id };
+    }
+    let engine = ModelRegistry::default().resolve(&config.model)?;
+    let format =
+        args.format
+            .unwrap_or_else(|| match args.out.extension().and_then(|e| e.to_str()) {
+                Some(ext) if ext.eq_ignore_ascii_case("csv") => Format::Csv,
+                _ => Format::Jsonl,
+            });
+
+    let files = statements(&args.input)?;
+    let pool = rayon::ThreadPoolBuilder::new()
+        .num_threads(args.jobs.unwrap_or(0))
+        .build()
+        .map_err(|e| {
+            Error::tech("The worker threads could not be started.")
+                .add_meta_x("error", &e.to_string())
+        })?;
+    let started = Instant::now();
+    let results: Vec<BatchResult> = pool.install(|| {
+        files
+            .par_iter()
+            .map(|path| score_file(engine.as_ref(), &config, path))
+            .collect()
+    });
+    let elapsed = started.elapsed();
+
+    match format {
+        Format::Csv => write_csv(&args.out, &results)?,
+        Format::Jsonl => write_jsonl(&args.out, &results)?,
+    }
+    print_summary(&results, elapsed.as_secs_f64(), pool.current_num_threads());
+    Ok(())
+}
+
+/// The `.json` files directly under `dir`, by name.
+fn statements(dir: &Path) -> Result<Vec<PathBuf>> {
+    let mut files: Vec<PathBuf> = std::fs::read_dir(dir)?
+        .filter_map(|entry| entry.ok().map(|e| e.path()))
+        .filter(|p| p.is_file())
+        .filter(|p| {
+            p.extension()
+                .and_then(|e| e.to_str())
+                .is_some_and(|e| e.eq_ignore_ascii_case("json"))
+        })
+        .collect();
+    if files.is_empty() {
+        return Err(
+            Error::user("There are no .json statements in that directory.")
+                .add_meta_x("input", &dir.display().to_string()),
+        );
+    }
+    files.sort();
+    Ok(files)
+}
+
+fn score_file(engine: &dyn ScoringEngine, config: &ScoringConfig, path: &Path) -> BatchResult {
+    let file = path
+        .file_name()
+        .map(|n| n.to_string_lossy().into_owned())
+        .unwrap_or_default();
+    let transactions = std::fs::read_to_string(path)
+        .map_err(Error::from)
+        .and_then(|text| TransactionsResponse::parse_transactions(&text));
+    let (transactions, outcome) = match transactions {
+        Ok(txs) => (txs.len(), engine.assess(&txs, config)),
+        Err(e) => (0, Err(e)),
+    };
+    let (score, error) = match outcome {
+        Ok(score) => (Some(score), None),
+        Err(e) => (None, Some(e)),
+    };
+    BatchResult {
+        file,
+        transactions,
+        score,
+        error,
+    }
+}
+
+fn write_jsonl(path: &Path, results: &[BatchResult]) -> Result<()> {
+    let mut out = std::io::BufWriter::new(std::fs::File::create(path)?);
+    for result in results {
+        writeln!(out, "{}", serde_json::to_string(result)?)?;
+    }
+    out.flush()?;
+    Ok(())
+}
+
+/// One row per statement; factors and reason codes are `;`-separated.
+fn write_csv(path: &Path, results: &[BatchResult]) -> Result<()> {
+    let csv_error = |e: csv::Error| {
+        Error::tech("The results could not be written.").add_meta_x("error", &e.to_string())
+    };
+    let mut out = csv::Writer::from_path(path).map_err(csv_error)?;
+    out.write_record([
+        "file",
+        "transactions",
+        "total_score",
+        "risk_level",
+        "probability_of_default",
+        "confidence",
+        "thin_file",
+        "factors",
+        "reasons",
+        "error",
+    ])
+    .map_err(csv_error)?;
+    for result in results {
+        let mut row = vec![result.file.clone(), result.transactions.to_string()];
+        match &result.score {
+            Some(score) => row.extend([
+                score.total_score.to_string(),
+                score.risk_level.clone(),
+                score
+                    .probability_of_default
+                    .map(|pd| format!("{pd:.4}"))
+                    .unwrap_or_default(),
+                format!("{:.2}", score.confidence),
+                score.thin_file.to_string(),
+                score.factors.join("; "),
+                score
+                    .reasons
+                    .iter()
+                    .map(|r| reason_code(r.code))
+                    .collect::<Vec<_>>()
+                    .join("; "),
+            ]),
+            None => row.extend(std::iter::repeat_n(String::new(), 7)),
+        }
+        row.push(
+            result
+                .error
+                .as_ref()
+                .map(Error::to_string)
+                .unwrap_or_default(),
+        );
+        out.write_record(&row).map_err(csv_error)?;
+    }
+    out.flush()?;
+    Ok(())
+}
+
+/// As serialised, e.g. `FAILED_TRANSACTIONS`.
+fn reason_code(code: ReasonCode) -> String {
+    serde_json::to_value(code)
+        .ok()
+        .and_then(|v| v.as_str().map(str::to_string))
+        .unwrap_or_default()
+}
+
+fn print_summary(results: &[BatchResult], seconds: f64, threads: usize) {
+    let mut scores: Vec<u8> = results
+        .iter()
+        .filter_map(|r| r.score.as_ref().map(|s| s.total_score))
+        .collect();
+    scores.sort_unstable();
+    let failed = results.len() - scores.len();
+    let transactions: usize = results.iter().map(|r| r.transactions).sum();
+    println!(
+        "{} statements, {} scored, {} failed, {} transactions in {:.1}s on {} threads",
+        results.len(),
+        scores.len(),
+        failed,
+        transactions,
+        seconds,
+        threads
+    );
+    if let (Some(min), Some(max)) = (scores.first(), scores.last()) {
+        let mean = scores.iter().map(|s| *s as f64).sum::<f64>() / scores.len() as f64;
+        let mid = scores.len() / 2;
+        let median = if scores.len().is_multiple_of(2) {
+            (scores[mid - 1] as f64 + scores[mid] as f64) / 2.0
+        } else {
+            scores[mid] as f64
+        };
+        println!("score: mean {mean:.1}, median {median:.1}, min {min}, max {max}");
+    }
+
+    let mut bands: BTreeMap<&str, usize> = BTreeMap::new();
+    for score in results.iter().filter_map(|r| r.score.as_ref()) {
+        *bands.entry(score.risk_level.as_str()).or_default() += 1;
+    }
+    for (band, count) in bands {
+        println!("{band:<24} {count:>8}");
+    }
+    if failed > 0 {
+        println!("Unscored statements have their error in the results.");
+    }
+}
+
+#[cfg(test)]
+mod test_batch {
+    use super::*;
+
+    fn temp_dir(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("{name}-{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+        std::fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+
+    fn statement(days: u32) -> String {
+        let transactions: Vec<serde_json::Value> = (1..=days)
+            .map(|day| {
+                serde_json::json!({
+                    "id": day.to_string(),
+                    "amount": 10_000.0,
+                    "channel": "MOBILE",
+                    "authorization_token": "x",
+                    "transaction_type": "TRF",
+                    "debit_credit": if day % 2 == 0 { "DEBIT" } else { "CREDIT" },
+                    "narration": "Transfer",
+                    "reference": "ref1",
+                    "transaction_time": format!("2023-01-{day:02}"),
+                    "value_date": format!("2023-01-{day:02}"),
+                    "balance_after": 50_000.0,
+                    "status": "SUCCESSFUL",
+                })
+            })
+            .collect();
+        serde_json::to_string(&transactions).unwrap()
+    }
+
+    #[test]
+    fn testing_statements() {
+        let dir = temp_dir("batch-statements");
+        assert!(statements(&dir).is_err());
+
+        for name in ["b.json", "a.JSON", "notes.txt", "c.json.bak"] {
+            std::fs::write(dir.join(name), "[]").unwrap();
+        }
+        std::fs::create_dir(dir.join("nested.json")).unwrap();
+        let names: Vec<String> = statements(&dir)
+            .unwrap()
+            .iter()
+            .map(|p| p.file_name().unwrap().to_string_lossy().into_owned())
+            .collect();
+        assert_eq!(names, ["a.JSON", "b.json"]);
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn testing_write_csv() {
+        let dir = temp_dir("batch-csv");
+        std::fs::write(dir.join("good.json"), statement(20)).unwrap();
+        std::fs::write(dir.join("bad.json"), "not json").unwrap();
+        let config = ScoringConfig::default();
+        let engine = ModelRegistry::default().resolve(&config.model).unwrap();
+        let results: Vec<BatchResult> = statements(&dir)
+            .unwrap()
+            .iter()
+            .map(|path| score_file(engine.as_ref(), &config, path))
+            .collect();
+
+        let out = dir.join("results.csv");
+        write_csv(&out, &results).unwrap();
+        let mut reader = csv::Reader::from_path(&out).unwrap();
+        assert_eq!(reader.headers().unwrap().len(), 10);
+        let rows: Vec<csv::StringRecord> = reader.records().map(|r| r.unwrap()).collect();
+        assert_eq!(rows.len(), 2);
+
+        let failed = &rows[0];
+        assert_eq!(&failed[0], "bad.json");
+        assert_eq!(&failed[1], "0");
+        assert!((2..9).all(|i| failed[i].is_empty()));
+        assert!(!failed[9].is_empty());
+
+        let scored = &rows[1];
+        let score = results[1].score.as_ref().unwrap();
+        assert_eq!(&scored[0], "good.json");
+        assert_eq!(&scored[1], "20");
+        assert_eq!(&scored[2], score.total_score.to_string());
+        assert_eq!(&scored[3], score.risk_level);
+        assert_eq!(&scored[9], "");
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+}
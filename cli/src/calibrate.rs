@@ -0,0 +1,65 @@
+use clap::{Args, ValueEnum};
+use shared::credit_scoring::{Calibration, LabelledHistory, ModelRegistry, ScoringConfig};
+use shared::result::Result;
+use std::path::PathBuf;
+
+#[derive(Clone, Copy, ValueEnum)]
+enum Method {
+    Platt,
+    Isotonic,
+}
+
+#[derive(Args)]
+pub struct CalibrateArgs {
+    /// Labelled histories, a JSON array or JSON lines.
+    #[arg(long)]
+    data: PathBuf,
+    /// Scoring config whose model is calibrated; the default heuristic
+    /// model when unset.
+    #[arg(long)]
+    config: Option<PathBuf>,
+    #[arg(long, value_enum, default_value = "platt")]
+    method: Method,
+    /// Where to write the calibration as JSON, for `scales.calibration`.
+    #[arg(long)]
+    out: PathBuf,
+}
+
+pub fn run(args: CalibrateArgs) -> Result<()> {
+    let histories = LabelledHistory::parse_many(&std::fs::read_to_string(&args.data)?)?;
+    let config: ScoringConfig = match &args.config {
+        Some(path) => serde_json::from_str(&std::fs::read_to_string(path)?)?,
+        None => ScoringConfig::default(),
+    };
+    let engine = ModelRegistry::default().resolve(&config.model)?;
+
+    // Raw, uncalibrated output of the model.
+    let samples: Vec<(f64, bool)> = histories
+        .iter()
+        .map(|h| {
+            let features = engine.extract(&h.transactions, &config);
+            let score = engine.score(&features, &config);
+            (score.total_score as f64, h.defaulted)
+        })
+        .collect();
+    let calibration = match args.method {
+        Method::Platt => Calibration::fit_platt(&samples)?,
+        Method::Isotonic => Calibration::fit_isotonic(&samples)?,
+    };
+    std::fs::write(&args.out, serde_json::to_string_pretty(&calibration)?)?;
+
+    let brier = |pd: &dyn Fn(f64) -> f64| {
+        samples
+            .iter()
+            .map(|(s, d)| (pd(*s) - *d as u8 as f64).powi(2))
+            .sum::<f64>()
+            / samples.len().max(1) as f64
+    };
+    println!("{} histories", samples.len());
+    println!(
+        "Brier score: {:.4} raw (1 - score/100), {:.4} calibrated",
+        brier(&|s| 1.0 - s / 100.0),
+        brier(&|s| calibration.probability_of_default(s).unwrap_or_default()),
+    );
+    Ok(())
+}
@@ -0,0 +1,32 @@
+use clap::{Parser, Subcommand};
+use shared::result::Result;
+
+mod batch;
+mod calibrate;
+mod train;
+
+/// Offline tools for the finscore scoring models.
+#[derive(Parser)]
+#[command(name = "finscore-cli", version)]
+struct Cli {
+    #[command(subcommand)]
+    command: Command,
+}
+
+#[derive(Subcommand)]
+enum Command {
+    /// Fit a WoE scorecard from labelled customer histories.
+    Train(train::TrainArgs),
+    /// Fit a score-to-PD calibration on labelled customer histories.
+    Calibrate(calibrate::CalibrateArgs),
+    /// Score a directory of statements in parallel.
+    Batch(batch::BatchArgs),
+}
+
+fn main() -> Result<()> {
+    match Cli::parse().command {
+        Command::Train(args) => train::run(args),
+        Command::Calibrate(args) => calibrate::run(args),
+        Command::Batch(args) => batch::run(args),
+    }
+}
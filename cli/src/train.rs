@@ -0,0 +1,81 @@
+use clap::Args;
+use shared::credit_scoring::{
+    FeaturePipeline, LabelledHistory, PointsScaling, ScoringConfig, TrainingOptions,
+    TrainingReport, train,
+};
+use shared::result::Result;
+use std::path::PathBuf;
+
+#[derive(Args)]
+pub struct TrainArgs {
+    /// Labelled histories, a JSON array or JSON lines.
+    #[arg(long)]
+    data: PathBuf,
+    /// Where to write the scorecard; `.toml` or `.json`.
+    #[arg(long)]
+    out: PathBuf,
+    /// Where to write the training report as JSON.
+    #[arg(long)]
+    report: Option<PathBuf>,
+    /// Scoring config whose categories and thresholds feed the pipeline.
+    #[arg(long)]
+    config: Option<PathBuf>,
+    #[arg(long, default_value = "Trained scorecard")]
+    name: String,
+    /// Restrict training to these features.
+    #[arg(long = "feature")]
+    features: Vec<String>,
+    #[arg(long, default_value_t = 0.02)]
+    min_iv: f64,
+    #[arg(long, default_value_t = 12)]
+    max_features: usize,
+    #[arg(long, default_value_t = 600.0)]
+    base_score: f64,
+    #[arg(long, default_value_t = 50.0)]
+    base_odds: f64,
+    #[arg(long, default_value_t = 20.0)]
+    pdo: f64,
+}
+
+pub fn run(args: TrainArgs) -> Result<()> {
+    let histories = LabelledHistory::parse_many(&std::fs::read_to_string(&args.data)?)?;
+    let config: ScoringConfig = match &args.config {
+        Some(path) => serde_json::from_str(&std::fs::read_to_string(path)?)?,
+        None => ScoringConfig::default(),
+    };
+    let options = TrainingOptions {
+        name: args.name,
+        candidates: args.features,
+        min_iv: args.min_iv,
+        max_features: args.max_features,
+        scaling: PointsScaling {
+            base_score: args.base_score,
+            base_odds: args.base_odds,
+            pdo: args.pdo,
+        },
+        ..TrainingOptions::default()
+    };
+
+    let (card, report) = train(&histories, &FeaturePipeline::for_config(&config), &options)?;
+    card.save(&args.out)?;
+    if let Some(path) = &args.report {
+        std::fs::write(path, serde_json::to_string_pretty(&report)?)?;
+    }
+    print_summary(&report);
+    Ok(())
+}
+
+fn print_summary(report: &TrainingReport) {
+    println!(
+        "{} histories, {} defaults, training AUC {:.3}",
+        report.samples, report.defaults, report.auc
+    );
+    println!("{:<32} {:>8} {:>12}", "feature", "IV", "coefficient");
+    for f in &report.features {
+        let coefficient = f
+            .coefficient
+            .map(|c| format!("{c:.4}"))
+            .unwrap_or_else(|| "-".into());
+        println!("{:<32} {:>8.4} {:>12}", f.feature, f.iv, coefficient);
+    }
+}
@@ -0,0 +1,99 @@
+//! Slice extraction against the single-pass stream, on synthetic histories
+//! of a few hundred to twenty thousand transactions.
+//!
+//! cargo bench -p shared --bench streaming
+
+use chrono::{Days, NaiveDate};
+use criterion::{BenchmarkId, Criterion, Throughput, criterion_group, criterion_main};
+use shared::credit_scoring::{FeaturePipeline, HeuristicEngine, ScoringConfig, ScoringEngine};
+use shared::openbank::Transaction;
+use std::hint::black_box;
+
+const NARRATIONS: &[(&str, &str, f64)] = &[
+    ("CREDIT", "SALARY ACME LTD", 250_000.0),
+    ("DEBIT", "RENT LANDLORD OKON", 80_000.0),
+    ("DEBIT", "BET9JA deposit", 5_000.0),
+    ("DEBIT", "POS purchase SHOPRITE", 1_500.0),
+    ("CREDIT", "Transfer from ADA OBI", 20_000.0),
+    ("DEBIT", "MTN airtime", 500.0),
+    ("CREDIT", "CARBON loan disbursement", 50_000.0),
+    ("DEBIT", "CARBON loan repayment", 55_000.0),
+];
+
+/// `per_day` transactions a day for as many days as it takes to reach
+/// `count`, cycling through everyday narrations.
+fn history(count: usize, per_day: usize) -> Vec<Transaction> {
+    let start = NaiveDate::from_ymd_opt(2015, 1, 1).unwrap();
+    let mut balance = 100_000.0;
+    (0..count)
+        .map(|i| {
+            let date = start + Days::new((i / per_day) as u64);
+            let (dc, narration, amount) = NARRATIONS[i % NARRATIONS.len()];
+            let amount = amount * (1.0 + (i % 13) as f64 / 100.0);
+            if dc == "CREDIT" {
+                balance += amount;
+            } else {
+                balance -= amount;
+            }
+            Transaction {
+                id: i.to_string(),
+                amount,
+                channel: "MOBILE".into(),
+                authorization_token: "x".into(),
+                transaction_type: "TRF".into(),
+                debit_credit: dc.into(),
+                narration: narration.into(),
+                reference: format!("ref{i}"),
+                transaction_time: date.format("%Y-%m-%d").to_string(),
+                value_date: date.format("%Y-%m-%d").to_string(),
+                balance_after: balance,
+                status: "SUCCESSFUL".into(),
+            }
+        })
+        .collect()
+}
+
+fn extraction(c: &mut Criterion) {
+    let mut group = c.benchmark_group("extract");
+    group.sample_size(10);
+    for count in [500, 5_000, 20_000] {
+        let txs = history(count, 4);
+        group.throughput(Throughput::Elements(count as u64));
+        group.bench_with_input(BenchmarkId::new("slice", count), &txs, |b, txs| {
+            b.iter(|| FeaturePipeline::new().extract(black_box(txs)))
+        });
+        group.bench_with_input(BenchmarkId::new("stream", count), &txs, |b, txs| {
+            b.iter(|| {
+                let mut stream = FeaturePipeline::new().stream();
+                for chunk in black_box(txs).chunks(1_000) {
+                    stream.push_chunk(chunk);
+                }
+                stream.finalize()
+            })
+        });
+    }
+    group.finish();
+}
+
+fn scoring(c: &mut Criterion) {
+    let engine = HeuristicEngine::default();
+    let config = ScoringConfig::default();
+    let txs = history(20_000, 4);
+    let mut group = c.benchmark_group("assess");
+    group.sample_size(10);
+    group.throughput(Throughput::Elements(txs.len() as u64));
+    group.bench_function("slice", |b| {
+        b.iter(|| engine.assess(black_box(&txs), &config))
+    });
+    group.bench_function("stream", |b| {
+        b.iter(|| {
+            let mut stream = engine.stream(&config);
+            stream.push_chunk(black_box(&txs));
+            engine.assess_features(&stream.finalize(), &[], &config)
+        })
+    });
+    group.finish();
+}
+
+criterion_group!(benches, extraction, scoring);
+criterion_main!(benches);
@@ -0,0 +1,185 @@
+use crate::credit_scoring::features::{
+    coefficient_of_variation, from_month_index, mean, month_index, std_dev,
+};
+use crate::openbank::Transaction;
+use chrono::{Datelike, NaiveDateTime};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct MonthlyFlow {
+    pub year: i32,
+    pub month: u32,
+    pub income: f64,
+    pub expense: f64,
+    pub net: f64,
+}
+
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+pub struct CashFlowReport {
+    /// Every month from the first to the last transaction, quiet ones
+    /// included.
+    pub months: Vec<MonthlyFlow>,
+    /// Coefficient of variation of monthly credits.
+    pub income_cv: f64,
+    /// Coefficient of variation of monthly debits.
+    pub expense_cv: f64,
+    /// Share of months where credits beat debits.
+    pub positive_month_share: f64,
+    /// Month with the lowest net flow.
+    pub worst_month: Option<MonthlyFlow>,
+    /// Largest fall of `balance_after` from a running peak.
+    pub max_drawdown: f64,
+    /// `max_drawdown` over the peak it fell from, 0-1.
+    pub max_drawdown_pct: f64,
+}
+
+/// Buckets successful transactions by calendar month and measures how
+/// steady the flows and the balance are.
+pub fn analyse_cash_flow(transactions: &[Transaction]) -> CashFlowReport {
+    let mut dated: Vec<_> = transactions
+        .iter()
+        .filter_map(|t| Some((t.timestamp()?, t)))
+        .collect();
+    dated.sort_by_key(|(ts, _)| *ts);
+    let mut accumulator = CashFlowAccumulator::new();
+    for (_, tx) in dated {
+        accumulator.push(tx);
+    }
+    accumulator.finish()
+}
+
+/// `analyse_cash_flow` one transaction at a time. Memory grows with the
+/// months covered, not the transactions; the drawdown assumes they arrive
+/// in time order.
+#[derive(Clone, Debug)]
+pub struct CashFlowAccumulator {
+    flows: BTreeMap<i32, (f64, f64)>,
+    first: Option<NaiveDateTime>,
+    last: Option<NaiveDateTime>,
+    peak: f64,
+    max_drawdown: f64,
+    max_drawdown_pct: f64,
+}
+
+impl Default for CashFlowAccumulator {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl CashFlowAccumulator {
+    pub fn new() -> Self {
+        Self {
+            flows: BTreeMap::new(),
+            first: None,
+            last: None,
+            peak: f64::NEG_INFINITY,
+            max_drawdown: 0.0,
+            max_drawdown_pct: 0.0,
+        }
+    }
+
+    pub fn push(&mut self, tx: &Transaction) {
+        if tx.is_failed() {
+            return;
+        }
+        let Some(ts) = tx.timestamp() else {
+            return;
+        };
+        self.first = Some(self.first.map_or(ts, |f| f.min(ts)));
+        self.last = Some(self.last.map_or(ts, |l| l.max(ts)));
+
+        let entry = self
+            .flows
+            .entry(month_index((ts.year(), ts.month())))
+            .or_default();
+        if tx.is_credit() {
+            entry.0 += tx.amount;
+        } else if tx.is_debit() {
+            entry.1 += tx.amount;
+        }
+
+        self.peak = self.peak.max(tx.balance_after);
+        let drawdown = self.peak - tx.balance_after;
+        if drawdown > self.max_drawdown {
+            self.max_drawdown = drawdown;
+            self.max_drawdown_pct = if self.peak > 0.0 {
+                (drawdown / self.peak).min(1.0)
+            } else {
+                0.0
+            };
+        }
+    }
+
+    pub fn finish(self) -> CashFlowReport {
+        let (Some(first), Some(last)) = (self.first, self.last) else {
+            return CashFlowReport::default();
+        };
+        let start = month_index((first.year(), first.month()));
+        let end = month_index((last.year(), last.month()));
+        let months: Vec<MonthlyFlow> = (start..=end)
+            .map(|index| {
+                let (year, month) = from_month_index(index);
+                let (income, expense) = self.flows.get(&index).copied().unwrap_or_default();
+                MonthlyFlow {
+                    year,
+                    month,
+                    income,
+                    expense,
+                    net: income - expense,
+                }
+            })
+            .collect();
+
+        let income: Vec<f64> = months.iter().map(|m| m.income).collect();
+        let expense: Vec<f64> = months.iter().map(|m| m.expense).collect();
+
+        CashFlowReport {
+            income_cv: coefficient_of_variation(std_dev(&income), mean(&income)),
+            expense_cv: coefficient_of_variation(std_dev(&expense), mean(&expense)),
+            positive_month_share: months.iter().filter(|m| m.net > 0.0).count() as f64
+                / months.len() as f64,
+            worst_month: months
+                .iter()
+                .min_by(|a, b| a.net.total_cmp(&b.net))
+                .cloned(),
+            max_drawdown: self.max_drawdown,
+            max_drawdown_pct: self.max_drawdown_pct,
+            months,
+        }
+    }
+}
+
+#[cfg(test)]
+mod test_cashflow {
+    use super::*;
+    use crate::test_fixtures;
+
+    fn tx(date: &str, dc: &str, amount: f64, balance_after: f64) -> Transaction {
+        Transaction {
+            balance_after,
+            ..test_fixtures::tx("1", date, dc, amount, "Test")
+        }
+    }
+
+    #[test]
+    fn testing_monthly_buckets_and_drawdown() {
+        let report = analyse_cash_flow(&[
+            tx("2023-01-05", "CREDIT", 10_000.0, 10_000.0),
+            tx("2023-01-20", "DEBIT", 4_000.0, 6_000.0),
+            tx("2023-03-05", "CREDIT", 10_000.0, 16_000.0),
+            tx("2023-03-10", "DEBIT", 12_000.0, 4_000.0),
+            tx("2023-03-28", "CREDIT", 1_000.0, 5_000.0),
+        ]);
+
+        // February is quiet but still counts.
+        assert_eq!(report.months.len(), 3);
+        assert_eq!(report.months[1].net, 0.0);
+        assert!((report.positive_month_share - 1.0 / 3.0).abs() < 1e-9);
+        let worst = report.worst_month.unwrap();
+        assert_eq!((worst.year, worst.month, worst.net), (2023, 3, -1_000.0));
+        assert_eq!(report.max_drawdown, 12_000.0);
+        assert!((report.max_drawdown_pct - 0.75).abs() < 1e-9);
+    }
+}
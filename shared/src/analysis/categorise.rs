@@ -0,0 +1,464 @@
+use super::classifier::NarrationClassifier;
+use crate::credit_scoring::ScoringConfig;
+use crate::openbank::Transaction;
+use crate::result::{Error, Result};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fmt;
+use std::sync::{Arc, Mutex, OnceLock};
+use std::time::SystemTime;
+
+/// Rules for Nigerian banks, billers, lenders and betting platforms.
+const BUNDLED_RULES: &str = include_str!("rules/ng_default.json");
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Category {
+    Salary,
+    Rent,
+    Utilities,
+    Airtime,
+    Gambling,
+    LoanDisbursement,
+    LoanRepayment,
+    BankCharges,
+    Transfer,
+    PosSpend,
+    Thrift,
+    Uncategorised,
+}
+
+impl Category {
+    pub const ALL: [Category; 12] = [
+        Category::Salary,
+        Category::Rent,
+        Category::Utilities,
+        Category::Airtime,
+        Category::Gambling,
+        Category::LoanDisbursement,
+        Category::LoanRepayment,
+        Category::BankCharges,
+        Category::Transfer,
+        Category::PosSpend,
+        Category::Thrift,
+        Category::Uncategorised,
+    ];
+
+    /// Same spelling as the serialized form, used for feature names.
+    pub fn key(&self) -> &'static str {
+        match self {
+            Category::Salary => "salary",
+            Category::Rent => "rent",
+            Category::Utilities => "utilities",
+            Category::Airtime => "airtime",
+            Category::Gambling => "gambling",
+            Category::LoanDisbursement => "loan_disbursement",
+            Category::LoanRepayment => "loan_repayment",
+            Category::BankCharges => "bank_charges",
+            Category::Transfer => "transfer",
+            Category::PosSpend => "pos_spend",
+            Category::Thrift => "thrift",
+            Category::Uncategorised => "uncategorised",
+        }
+    }
+}
+
+impl fmt::Display for Category {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.key())
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Direction {
+    #[serde(rename = "CREDIT")]
+    Credit,
+    #[serde(rename = "DEBIT")]
+    Debit,
+}
+
+/// One categorisation rule. Every condition that is set must hold; a rule
+/// with keywords matches when any keyword appears as whole words in the
+/// narration (case and punctuation are ignored). `Categoriser` normalises
+/// the keywords once when it takes the rule.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct Rule {
+    pub category: Category,
+    #[serde(default)]
+    pub priority: i32,
+    #[serde(default)]
+    pub direction: Option<Direction>,
+    #[serde(default)]
+    pub keywords: Vec<String>,
+    #[serde(default)]
+    pub channels: Vec<String>,
+    #[serde(default)]
+    pub transaction_types: Vec<String>,
+}
+
+impl Rule {
+    /// `narration` and the keywords must both have been through
+    /// `normalise`.
+    pub fn matches(&self, tx: &Transaction, narration: &str) -> bool {
+        if let Some(direction) = self.direction {
+            let ok = match direction {
+                Direction::Credit => tx.is_credit(),
+                Direction::Debit => tx.is_debit(),
+            };
+            if !ok {
+                return false;
+            }
+        }
+        if !self.channels.is_empty()
+            && !self
+                .channels
+                .iter()
+                .any(|c| c.eq_ignore_ascii_case(tx.channel.trim()))
+        {
+            return false;
+        }
+        if !self.transaction_types.is_empty()
+            && !self
+                .transaction_types
+                .iter()
+                .any(|t| t.eq_ignore_ascii_case(tx.transaction_type.trim()))
+        {
+            return false;
+        }
+        self.keywords.is_empty() || self.keywords.iter().any(|k| narration.contains(k.as_str()))
+    }
+
+    fn normalised(mut self) -> Self {
+        for keyword in &mut self.keywords {
+            *keyword = normalise(keyword);
+        }
+        self
+    }
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct RuleSet {
+    pub name: String,
+    pub version: u32,
+    pub rules: Vec<Rule>,
+}
+
+impl RuleSet {
+    pub fn from_json(json: &str) -> Result<Self> {
+        serde_json::from_str(json).map_err(|e| {
+            Error::user("The categorisation rules could not be read.")
+                .add_meta_x("error", &e.to_string())
+        })
+    }
+
+    pub fn bundled() -> &'static RuleSet {
+        static BUNDLED: OnceLock<RuleSet> = OnceLock::new();
+        BUNDLED.get_or_init(|| {
+            RuleSet::from_json(BUNDLED_RULES).expect("bundled categorisation rules are valid")
+        })
+    }
+}
+
+/// Where a category came from.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub enum CategorySource {
+    Override,
+    Rule,
+    Model,
+    None,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+pub struct Categorisation {
+    pub category: Category,
+    /// 1 for rule matches, the model's posterior otherwise.
+    pub confidence: f64,
+    pub source: CategorySource,
+}
+
+/// Assigns a `Category` to transactions. User overrides are checked before
+/// the ruleset, both in priority order (ties keep their listed order). When
+/// no rule matches, the narration model (if any) gets a say, provided it is
+/// at least `min_confidence` sure.
+#[derive(Clone, Debug)]
+pub struct Categoriser {
+    overrides: Vec<Rule>,
+    rules: Vec<Rule>,
+    model: Option<Arc<NarrationClassifier>>,
+    min_confidence: f64,
+    model_warning: Option<String>,
+}
+
+impl Default for Categoriser {
+    fn default() -> Self {
+        Self::new(RuleSet::bundled().clone())
+    }
+}
+
+impl Categoriser {
+    pub fn new(ruleset: RuleSet) -> Self {
+        let mut rules: Vec<Rule> = ruleset.rules.into_iter().map(Rule::normalised).collect();
+        rules.sort_by_key(|r| std::cmp::Reverse(r.priority));
+        Self {
+            overrides: Vec::new(),
+            rules,
+            model: None,
+            min_confidence: 0.6,
+            model_warning: None,
+        }
+    }
+
+    /// Bundled rules plus the config's overrides and narration model. A model
+    /// file that can't be read is skipped, rules still apply, and
+    /// `model_warning` says why. The model is read from disk only the first
+    /// time or after the file changes.
+    pub fn for_config(config: &ScoringConfig) -> Self {
+        let mut categoriser = Self::default().with_overrides(&config.category_overrides);
+        if let Some(path) = &config.narration_model_path {
+            match cached_model(path) {
+                Ok(model) => {
+                    categoriser.model = Some(model);
+                    categoriser.min_confidence = config.min_model_confidence;
+                }
+                Err(_) => {
+                    categoriser.model_warning = Some(format!(
+                        "Narration model {path} could not be read; categorised by rules only."
+                    ))
+                }
+            }
+        }
+        categoriser
+    }
+
+    /// Set when `for_config` couldn't load the narration model.
+    pub fn model_warning(&self) -> Option<&str> {
+        self.model_warning.as_deref()
+    }
+
+    pub fn with_overrides(mut self, overrides: &[Rule]) -> Self {
+        self.overrides = overrides.iter().cloned().map(Rule::normalised).collect();
+        self.overrides
+            .sort_by_key(|r| std::cmp::Reverse(r.priority));
+        self
+    }
+
+    pub fn with_model(mut self, model: NarrationClassifier, min_confidence: f64) -> Self {
+        self.model = Some(Arc::new(model));
+        self.min_confidence = min_confidence;
+        self
+    }
+
+    pub fn categorise(&self, tx: &Transaction) -> Category {
+        self.categorise_detail(tx).category
+    }
+
+    pub fn categorise_detail(&self, tx: &Transaction) -> Categorisation {
+        let narration = normalise(&tx.narration);
+        if let Some(rule) = self.overrides.iter().find(|r| r.matches(tx, &narration)) {
+            return Categorisation {
+                category: rule.category,
+                confidence: 1.0,
+                source: CategorySource::Override,
+            };
+        }
+        if let Some(rule) = self.rules.iter().find(|r| r.matches(tx, &narration)) {
+            return Categorisation {
+                category: rule.category,
+                confidence: 1.0,
+                source: CategorySource::Rule,
+            };
+        }
+        match self.model.as_ref().and_then(|m| m.predict(tx)) {
+            Some(p) if p.confidence >= self.min_confidence => Categorisation {
+                category: p.category,
+                confidence: p.confidence,
+                source: CategorySource::Model,
+            },
+            _ => Categorisation {
+                category: Category::Uncategorised,
+                confidence: 0.0,
+                source: CategorySource::None,
+            },
+        }
+    }
+
+    pub fn matching_rule(&self, tx: &Transaction) -> Option<&Rule> {
+        let narration = normalise(&tx.narration);
+        self.overrides
+            .iter()
+            .chain(self.rules.iter())
+            .find(|r| r.matches(tx, &narration))
+    }
+}
+
+/// Narration models by path, with the file's modification time when read.
+type ModelCache = HashMap<String, (Option<SystemTime>, Arc<NarrationClassifier>)>;
+
+fn cached_model(path: &str) -> Result<Arc<NarrationClassifier>> {
+    static MODELS: OnceLock<Mutex<ModelCache>> = OnceLock::new();
+    let models = MODELS.get_or_init(Mutex::default);
+    let modified = std::fs::metadata(path).and_then(|m| m.modified()).ok();
+    if let Some((at, model)) = models.lock().unwrap().get(path)
+        && *at == modified
+    {
+        return Ok(model.clone());
+    }
+    // Loaded without the lock; a concurrent load of the same file just
+    // replaces an identical entry.
+    let model = Arc::new(NarrationClassifier::load(path)?);
+    models
+        .lock()
+        .unwrap()
+        .insert(path.to_string(), (modified, model.clone()));
+    Ok(model)
+}
+
+/// Uppercases, turns anything that isn't a letter or digit into a single
+/// space and pads both ends, so `contains(" BET9JA ")` is a whole-word match.
+pub fn normalise(text: &str) -> String {
+    let mut out = String::with_capacity(text.len() + 2);
+    out.push(' ');
+    for c in text.chars() {
+        if c.is_alphanumeric() {
+            out.extend(c.to_uppercase());
+        } else if !out.ends_with(' ') {
+            out.push(' ');
+        }
+    }
+    if !out.ends_with(' ') {
+        out.push(' ');
+    }
+    out
+}
+
+#[cfg(test)]
+mod test_categorise {
+    use super::*;
+    use crate::test_fixtures;
+
+    fn tx(narration: &str, debit_credit: &str, channel: &str) -> Transaction {
+        Transaction {
+            channel: channel.into(),
+            transaction_type: "PAYMENT".into(),
+            balance_after: 1_000.0,
+            ..test_fixtures::tx("1", "2023-01-01", debit_credit, 1_000.0, narration)
+        }
+    }
+
+    #[test]
+    fn testing_bundled_rules() {
+        let c = Categoriser::default();
+        let cases = [
+            (
+                "NIP/ACME LTD/SALARY JAN 2023",
+                "CREDIT",
+                "MOBILE",
+                Category::Salary,
+            ),
+            (
+                "NIP TRF TO BET9JA/REF 2231",
+                "DEBIT",
+                "MOBILE",
+                Category::Gambling,
+            ),
+            ("SportyBet deposit", "DEBIT", "WEB", Category::Gambling),
+            ("IKEDC PREPAID 0101", "DEBIT", "WEB", Category::Utilities),
+            ("MTN VTU 08031234567", "DEBIT", "USSD", Category::Airtime),
+            (
+                "SMS ALERT CHARGES FOR MAR",
+                "DEBIT",
+                "SYSTEM",
+                Category::BankCharges,
+            ),
+            (
+                "FAIRMONEY LOAN DISB",
+                "CREDIT",
+                "MOBILE",
+                Category::LoanDisbursement,
+            ),
+            (
+                "FAIRMONEY REPAYMENT",
+                "DEBIT",
+                "MOBILE",
+                Category::LoanRepayment,
+            ),
+            ("HOUSE RENT - MR ADEBAYO", "DEBIT", "MOBILE", Category::Rent),
+            ("Weekly ajo", "DEBIT", "MOBILE", Category::Thrift),
+            ("SHOPRITE LEKKI", "DEBIT", "POS", Category::PosSpend),
+            ("NIP FRM JOHN DOE", "CREDIT", "MOBILE", Category::Transfer),
+            ("Unknown merchant", "DEBIT", "WEB", Category::Uncategorised),
+        ];
+        for (narration, dc, channel, expected) in cases {
+            assert_eq!(
+                c.categorise(&tx(narration, dc, channel)),
+                expected,
+                "{narration}"
+            );
+        }
+    }
+
+    #[test]
+    fn testing_keywords_match_whole_words() {
+        let c = Categoriser::default();
+        // "SAL" is a salary keyword, "SALES" is not a salary.
+        assert_eq!(
+            c.categorise(&tx("SALES PROCEEDS", "CREDIT", "WEB")),
+            Category::Uncategorised
+        );
+    }
+
+    #[test]
+    fn testing_overrides_win() {
+        let overrides = vec![Rule {
+            category: Category::Salary,
+            priority: 0,
+            direction: Some(Direction::Credit),
+            keywords: vec!["MAMA CHIDI".into()],
+            channels: Vec::new(),
+            transaction_types: Vec::new(),
+        }];
+        let c = Categoriser::default().with_overrides(&overrides);
+        assert_eq!(
+            c.categorise(&tx("NIP FRM MAMA CHIDI", "CREDIT", "MOBILE")),
+            Category::Salary
+        );
+    }
+
+    #[test]
+    fn testing_model_fallback_after_rules() {
+        let rent = tx("KINGS LODGE QTRLY", "DEBIT", "MOBILE");
+        let model = NarrationClassifier::train([(&rent, Category::Rent)]);
+        let c = Categoriser::default().with_model(model, 0.5);
+
+        let detail = c.categorise_detail(&tx("KINGS LODGE QTR 2", "DEBIT", "WEB"));
+        assert_eq!(detail.category, Category::Rent);
+        assert_eq!(detail.source, CategorySource::Model);
+
+        // Rules still win where they match.
+        let detail = c.categorise_detail(&tx("BET9JA", "DEBIT", "WEB"));
+        assert_eq!(detail.source, CategorySource::Rule);
+    }
+
+    #[test]
+    fn testing_narration_model_is_cached() {
+        let rent = tx("KINGS LODGE QTRLY", "DEBIT", "MOBILE");
+        let path =
+            std::env::temp_dir().join(format!("narration-model-{}.json", std::process::id()));
+        NarrationClassifier::train([(&rent, Category::Rent)])
+            .save(&path)
+            .unwrap();
+        let config = ScoringConfig {
+            narration_model_path: Some(path.display().to_string()),
+            ..ScoringConfig::default()
+        };
+
+        let first = Categoriser::for_config(&config);
+        let second = Categoriser::for_config(&config);
+        assert!(Arc::ptr_eq(
+            first.model.as_ref().unwrap(),
+            second.model.as_ref().unwrap()
+        ));
+        assert_eq!(second.categorise(&rent), Category::Rent);
+        std::fs::remove_file(&path).unwrap();
+    }
+}
@@ -0,0 +1,267 @@
+use super::categorise::{Category, normalise};
+use crate::openbank::Transaction;
+use crate::result::{Error, Result};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::path::Path;
+
+pub const MODEL_FORMAT_VERSION: u32 = 1;
+
+/// Multinomial naive Bayes over character n-grams of the narration, plus a
+/// direction token. Small enough to train and run on the phone, and the
+/// whole state is counts, so it can keep learning from confirmations.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct NarrationClassifier {
+    pub version: u32,
+    pub min_n: usize,
+    pub max_n: usize,
+    /// Laplace smoothing.
+    pub alpha: f64,
+    class_docs: BTreeMap<Category, u32>,
+    class_tokens: BTreeMap<Category, u64>,
+    token_counts: BTreeMap<String, BTreeMap<Category, u32>>,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+pub struct Prediction {
+    pub category: Category,
+    /// Posterior probability of `category`, 0-1.
+    pub confidence: f64,
+}
+
+impl Default for NarrationClassifier {
+    fn default() -> Self {
+        Self::new(2, 4)
+    }
+}
+
+impl NarrationClassifier {
+    pub fn new(min_n: usize, max_n: usize) -> Self {
+        Self {
+            version: MODEL_FORMAT_VERSION,
+            min_n: min_n.max(1),
+            max_n: max_n.max(min_n.max(1)),
+            alpha: 1.0,
+            class_docs: BTreeMap::new(),
+            class_tokens: BTreeMap::new(),
+            token_counts: BTreeMap::new(),
+        }
+    }
+
+    /// Trains a fresh model from user-confirmed categories.
+    pub fn train<'a>(examples: impl IntoIterator<Item = (&'a Transaction, Category)>) -> Self {
+        let mut model = Self::default();
+        for (tx, category) in examples {
+            model.learn(tx, category);
+        }
+        model
+    }
+
+    /// Adds one confirmed example. `Uncategorised` carries no signal and is
+    /// ignored.
+    pub fn learn(&mut self, tx: &Transaction, category: Category) {
+        if category == Category::Uncategorised {
+            return;
+        }
+        let tokens = self.tokens(tx);
+        *self.class_docs.entry(category).or_default() += 1;
+        *self.class_tokens.entry(category).or_default() += tokens.len() as u64;
+        for token in tokens {
+            *self
+                .token_counts
+                .entry(token)
+                .or_default()
+                .entry(category)
+                .or_default() += 1;
+        }
+    }
+
+    pub fn example_count(&self) -> u32 {
+        self.class_docs.values().sum()
+    }
+
+    pub fn predict(&self, tx: &Transaction) -> Option<Prediction> {
+        let total_docs = self.example_count() as f64;
+        if total_docs == 0.0 {
+            return None;
+        }
+        let tokens = self.tokens(tx);
+        let vocabulary = self.token_counts.len() as f64;
+
+        let log_scores: Vec<(Category, f64)> = self
+            .class_docs
+            .iter()
+            .map(|(category, docs)| {
+                let denominator = self.class_tokens.get(category).copied().unwrap_or(0) as f64
+                    + self.alpha * vocabulary;
+                let likelihood: f64 = tokens
+                    .iter()
+                    .map(|t| {
+                        let count = self
+                            .token_counts
+                            .get(t)
+                            .and_then(|c| c.get(category))
+                            .copied()
+                            .unwrap_or(0) as f64;
+                        ((count + self.alpha) / denominator).ln()
+                    })
+                    .sum();
+                (*category, (*docs as f64 / total_docs).ln() + likelihood)
+            })
+            .collect();
+
+        // Softmax over the log scores for a posterior we can threshold on.
+        let max = log_scores
+            .iter()
+            .map(|(_, s)| *s)
+            .fold(f64::NEG_INFINITY, f64::max);
+        let norm: f64 = log_scores.iter().map(|(_, s)| (s - max).exp()).sum();
+        log_scores
+            .into_iter()
+            .max_by(|a, b| a.1.total_cmp(&b.1))
+            .map(|(category, score)| Prediction {
+                category,
+                confidence: (score - max).exp() / norm,
+            })
+    }
+
+    /// Character n-grams of each word (digits collapsed, since references
+    /// and account numbers are noise) plus the transaction direction.
+    fn tokens(&self, tx: &Transaction) -> Vec<String> {
+        let mut tokens = vec![format!("__{}__", tx.debit_credit.trim().to_uppercase())];
+        let narration = normalise(&tx.narration);
+        for word in narration.split_whitespace() {
+            let word: String = word
+                .chars()
+                .map(|c| if c.is_ascii_digit() { '0' } else { c })
+                .collect();
+            let padded: Vec<char> = format!(" {word} ").chars().collect();
+            for n in self.min_n..=self.max_n {
+                if n > padded.len() {
+                    break;
+                }
+                for gram in padded.windows(n) {
+                    tokens.push(gram.iter().collect());
+                }
+            }
+        }
+        tokens
+    }
+
+    pub fn to_json(&self) -> Result<String> {
+        Ok(serde_json::to_string(self)?)
+    }
+
+    pub fn from_json(json: &str) -> Result<Self> {
+        let model: Self = serde_json::from_str(json)?;
+        if model.version != MODEL_FORMAT_VERSION {
+            return Err(
+                Error::user("The narration model was saved by another version.")
+                    .add_meta_x("version", &model.version.to_string()),
+            );
+        }
+        Ok(model)
+    }
+
+    pub fn save(&self, path: impl AsRef<Path>) -> Result<()> {
+        Ok(std::fs::write(path, self.to_json()?)?)
+    }
+
+    pub fn load(path: impl AsRef<Path>) -> Result<Self> {
+        Self::from_json(&std::fs::read_to_string(path)?)
+    }
+
+    /// `load`, or an untrained model when the file doesn't exist yet. A file
+    /// that exists but can't be read is an error, so it isn't overwritten.
+    pub fn load_or_default(path: impl AsRef<Path>) -> Result<Self> {
+        match std::fs::read_to_string(path) {
+            Ok(text) => Self::from_json(&text),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(Self::default()),
+            Err(e) => Err(e.into()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod test_classifier {
+    use super::*;
+    use crate::test_fixtures;
+
+    fn tx(narration: &str, debit_credit: &str) -> Transaction {
+        Transaction {
+            transaction_type: "PAYMENT".into(),
+            balance_after: 1_000.0,
+            ..test_fixtures::tx("1", "2023-01-01", debit_credit, 1_000.0, narration)
+        }
+    }
+
+    fn model() -> NarrationClassifier {
+        let examples = [
+            (
+                tx("MAMA NKECHI PROVISIONS 0231", "DEBIT"),
+                Category::PosSpend,
+            ),
+            (
+                tx("MAMA NKECHI PROVISIONS 9911", "DEBIT"),
+                Category::PosSpend,
+            ),
+            (tx("KINGS LODGE QTRLY 2231", "DEBIT"), Category::Rent),
+            (tx("KINGS LODGE QTRLY 0412", "DEBIT"), Category::Rent),
+            (
+                tx("ZENITH GLOBAL PAYMENTS MONTHLY", "CREDIT"),
+                Category::Salary,
+            ),
+        ];
+        NarrationClassifier::train(examples.iter().map(|(t, c)| (t, *c)))
+    }
+
+    #[test]
+    fn testing_predicts_unseen_variants() {
+        let model = model();
+        let p = model
+            .predict(&tx("MAMA NKECHI PROVISION STORE", "DEBIT"))
+            .unwrap();
+        assert_eq!(p.category, Category::PosSpend);
+        assert!(p.confidence > 0.9);
+
+        let p = model
+            .predict(&tx("KINGS LODGE QTRLY 7777", "DEBIT"))
+            .unwrap();
+        assert_eq!(p.category, Category::Rent);
+    }
+
+    #[test]
+    fn testing_empty_model_predicts_nothing() {
+        assert!(
+            NarrationClassifier::default()
+                .predict(&tx("ANYTHING", "DEBIT"))
+                .is_none()
+        );
+    }
+
+    #[test]
+    fn testing_model_roundtrip() {
+        let model = model();
+        let restored = NarrationClassifier::from_json(&model.to_json().unwrap()).unwrap();
+        let t = tx("KINGS LODGE", "DEBIT");
+        assert_eq!(restored.predict(&t), model.predict(&t));
+    }
+
+    #[test]
+    fn testing_load_or_default_keeps_corrupt_models() {
+        let dir = std::env::temp_dir().join(format!("classifier-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let missing = dir.join("missing.json");
+        assert_eq!(
+            NarrationClassifier::load_or_default(&missing)
+                .unwrap()
+                .example_count(),
+            0
+        );
+
+        let corrupt = dir.join("corrupt.json");
+        std::fs::write(&corrupt, "{ not json").unwrap();
+        assert!(NarrationClassifier::load_or_default(&corrupt).is_err());
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -0,0 +1,516 @@
+use super::categorise::{Categorisation, Categoriser, Category, CategorySource, normalise};
+use super::income::{jaccard, payer_tokens};
+use crate::credit_scoring::features::{from_month_index, month_index};
+use crate::openbank::Transaction;
+use chrono::Datelike;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, BTreeSet};
+
+/// Fragments that give away a betting or lottery merchant even inside a
+/// longer word, e.g. "PAYSTACK*XBETNG" or "LUCKYLOTTOPAY".
+const GAMBLING_FRAGMENTS: &[&str] = &["BET", "LOTTO", "CASINO", "JACKPOT", "POOLS", "SPORTY"];
+
+/// Ordinary words containing a fragment.
+const FALSE_FRIENDS: &[&str] = &[
+    "ALPHABET",
+    "BETWEEN",
+    "BETTER",
+    "BETTY",
+    "BETHEL",
+    "BETH",
+    "ELIZABETH",
+    "BETA",
+    "DIABETES",
+    "DIABETIC",
+    "ABETTOR",
+    "CARPOOLS",
+    "SWIMMINGPOOLS",
+];
+
+/// Evidence needed before a transaction counts as gambling. Each signal is
+/// worth at most 0.5, so it takes two of them.
+const MIN_EVIDENCE: f64 = 0.8;
+
+/// Transactions with a betting signal in their narration after which a
+/// counterparty counts as a betting platform, with no confident match.
+const MIN_REPEATS: u32 = 3;
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+pub enum GamblingRisk {
+    #[default]
+    None,
+    Low,
+    Moderate,
+    High,
+    Severe,
+}
+
+impl GamblingRisk {
+    pub const ALL: [GamblingRisk; 5] = [
+        GamblingRisk::None,
+        GamblingRisk::Low,
+        GamblingRisk::Moderate,
+        GamblingRisk::High,
+        GamblingRisk::Severe,
+    ];
+
+    /// 0 (none) to 4 (severe), as stored in the feature vector.
+    pub fn level(&self) -> u8 {
+        *self as u8
+    }
+
+    pub fn from_level(level: f64) -> Self {
+        Self::ALL[(level.round().max(0.0) as usize).min(Self::ALL.len() - 1)]
+    }
+
+    fn raised(self) -> Self {
+        Self::from_level(self.level() as f64 + 1.0)
+    }
+}
+
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+pub struct GamblingReport {
+    /// Money sent to betting platforms.
+    pub deposits: f64,
+    pub deposit_count: u32,
+    /// Winnings and withdrawals paid back out.
+    pub withdrawals: f64,
+    pub withdrawal_count: u32,
+    /// `deposits - withdrawals`; negative when the customer is ahead.
+    pub net_loss: f64,
+    pub active_months: u32,
+    /// Gambling transactions per month of history.
+    pub transactions_per_month: f64,
+    /// Deposits over all non-gambling credits.
+    pub income_share: f64,
+    /// Least-squares slope of monthly deposits over their mean; above 0
+    /// means betting is growing.
+    pub trend: f64,
+    pub platforms: Vec<String>,
+    pub risk: GamblingRisk,
+    pub transaction_ids: Vec<String>,
+}
+
+/// How sure the narration alone makes us that `tx` is gambling, 0-1: the
+/// categoriser, and a merchant-name fragment in a word that none of the
+/// matching rule's keywords cover, so one keyword never counts twice.
+fn text_evidence(tx: &Transaction, detail: &Categorisation, categoriser: &Categoriser) -> f64 {
+    let narration = normalise(&tx.narration);
+    let mut evidence = 0.0;
+    let mut rule_words = BTreeSet::new();
+    if detail.category == Category::Gambling {
+        evidence += detail.confidence * 0.5;
+        if detail.source != CategorySource::Model
+            && let Some(rule) = categoriser.matching_rule(tx)
+        {
+            rule_words.extend(
+                rule.keywords
+                    .iter()
+                    .filter(|k| narration.contains(k.as_str()))
+                    .flat_map(|k| k.split_whitespace()),
+            );
+        }
+    }
+    let fragment = narration.split_whitespace().any(|word| {
+        !rule_words.contains(word)
+            && !FALSE_FRIENDS.contains(&word)
+            && GAMBLING_FRAGMENTS.iter().any(|f| word.contains(f))
+    });
+    if fragment {
+        evidence += 0.5;
+    }
+    evidence.min(1.0)
+}
+
+/// Whether a counterparty already judged a betting platform can vouch for
+/// a transaction with `text` evidence of its own, adding the 0.5 it lacks.
+fn can_be_vouched(text: f64) -> bool {
+    text + 0.5 >= MIN_EVIDENCE
+}
+
+/// Deposits, withdrawals and platforms of transactions judged gambling.
+#[derive(Clone, Debug, Default)]
+struct Tally {
+    deposits: f64,
+    deposit_count: u32,
+    withdrawals: f64,
+    withdrawal_count: u32,
+    monthly: BTreeMap<(i32, u32), f64>,
+    platforms: BTreeSet<String>,
+}
+
+impl Tally {
+    fn add(&mut self, tx: &Transaction, detail: &Categorisation, categoriser: &Categoriser) {
+        if tx.is_debit() {
+            self.deposits += tx.amount;
+            self.deposit_count += 1;
+            if let Some(date) = tx.date() {
+                *self.monthly.entry((date.year(), date.month())).or_default() += tx.amount;
+            }
+        } else {
+            self.withdrawals += tx.amount;
+            self.withdrawal_count += 1;
+        }
+        // Only a rule or override can have named the platform.
+        if detail.category == Category::Gambling
+            && detail.source != CategorySource::Model
+            && let Some(rule) = categoriser.matching_rule(tx)
+        {
+            let narration = normalise(&tx.narration);
+            if let Some(k) = rule
+                .keywords
+                .iter()
+                .find(|k| narration.contains(k.as_str()))
+            {
+                self.platforms.insert(k.trim().to_string());
+            }
+        }
+    }
+
+    fn merge(&mut self, other: Tally) {
+        self.deposits += other.deposits;
+        self.deposit_count += other.deposit_count;
+        self.withdrawals += other.withdrawals;
+        self.withdrawal_count += other.withdrawal_count;
+        for (month, amount) in other.monthly {
+            *self.monthly.entry(month).or_default() += amount;
+        }
+        self.platforms.extend(other.platforms);
+    }
+
+    /// `span` is the first and last month of successful activity, `credits`
+    /// the total of successful credits.
+    fn report(
+        self,
+        transaction_ids: Vec<String>,
+        span: Option<((i32, u32), (i32, u32))>,
+        credits: f64,
+    ) -> GamblingReport {
+        let mut report = GamblingReport {
+            deposits: self.deposits,
+            deposit_count: self.deposit_count,
+            withdrawals: self.withdrawals,
+            withdrawal_count: self.withdrawal_count,
+            net_loss: self.deposits - self.withdrawals,
+            active_months: self.monthly.len() as u32,
+            platforms: self.platforms.into_iter().collect(),
+            transaction_ids,
+            ..Default::default()
+        };
+        let Some((first, last)) = span else {
+            return report;
+        };
+        let months_spanned = (month_index(last) - month_index(first) + 1) as usize;
+        report.transactions_per_month =
+            (report.deposit_count + report.withdrawal_count) as f64 / months_spanned as f64;
+
+        let income = credits - report.withdrawals;
+        report.income_share = if income > 0.0 {
+            report.deposits / income
+        } else if report.deposits > 0.0 {
+            1.0
+        } else {
+            0.0
+        };
+
+        // Every month in the history, including the ones without a bet.
+        let series: Vec<f64> = (0..months_spanned)
+            .map(|i| {
+                let key = from_month_index(month_index(first) + i as i32);
+                self.monthly.get(&key).copied().unwrap_or(0.0)
+            })
+            .collect();
+        report.trend = normalised_slope(&series);
+        report.risk = grade(&report);
+        report
+    }
+}
+
+/// Betting deposits, withdrawals and how they trend, graded into a
+/// `GamblingRisk`.
+pub fn detect_gambling(transactions: &[Transaction], categoriser: &Categoriser) -> GamblingReport {
+    let successful: Vec<&Transaction> = transactions.iter().filter(|t| !t.is_failed()).collect();
+    let details: Vec<Categorisation> = successful
+        .iter()
+        .map(|t| categoriser.categorise_detail(t))
+        .collect();
+    let text: Vec<f64> = successful
+        .iter()
+        .zip(&details)
+        .map(|(t, detail)| text_evidence(t, detail, categoriser))
+        .collect();
+    let tokens: Vec<BTreeSet<String>> = successful
+        .iter()
+        .map(|t| payer_tokens(&t.narration))
+        .collect();
+
+    // Counterparties of confident matches vouch for their other
+    // transactions, and so do ones that keep coming back with a signal.
+    let mut known: Vec<&BTreeSet<String>> = Vec::new();
+    let mut repeats: BTreeMap<&BTreeSet<String>, u32> = BTreeMap::new();
+    for (tokens, text) in tokens.iter().zip(&text) {
+        if tokens.is_empty() {
+            continue;
+        }
+        if *text >= MIN_EVIDENCE {
+            known.push(tokens);
+        } else if can_be_vouched(*text) {
+            *repeats.entry(tokens).or_default() += 1;
+        }
+    }
+    known.extend(
+        repeats
+            .into_iter()
+            .filter(|(_, n)| *n >= MIN_REPEATS)
+            .map(|(tokens, _)| tokens),
+    );
+
+    let mut tally = Tally::default();
+    let mut transaction_ids = Vec::new();
+    for (i, tx) in successful.iter().enumerate() {
+        if text[i] >= MIN_EVIDENCE
+            || (can_be_vouched(text[i]) && known.iter().any(|k| jaccard(k, &tokens[i]) >= 0.5))
+        {
+            tally.add(tx, &details[i], categoriser);
+            transaction_ids.push(tx.id.clone());
+        }
+    }
+
+    let months = successful
+        .iter()
+        .filter_map(|t| t.date())
+        .map(|d| (d.year(), d.month()));
+    let span = months.clone().min().zip(months.max());
+    let credits: f64 = successful
+        .iter()
+        .filter(|t| t.is_credit())
+        .map(|t| t.amount)
+        .sum();
+    tally.report(transaction_ids, span, credits)
+}
+
+/// Most counterparties `GamblingAccumulator` holds back while it waits to
+/// learn whether they are betting platforms.
+const MAX_PENDING_COUNTERPARTIES: usize = 20_000;
+
+/// `detect_gambling` one transaction at a time, leaving `transaction_ids`
+/// empty. Transactions with one signal short of the evidence bar are
+/// tallied per counterparty and counted in `finish` if a betting
+/// counterparty turned up that vouches for them, so the result matches the
+/// slice version as long as fewer than `MAX_PENDING_COUNTERPARTIES` are
+/// pending.
+#[derive(Clone, Debug, Default)]
+pub struct GamblingAccumulator {
+    tally: Tally,
+    known: BTreeSet<BTreeSet<String>>,
+    pending: BTreeMap<BTreeSet<String>, Tally>,
+    span: Option<((i32, u32), (i32, u32))>,
+    credits: f64,
+}
+
+impl GamblingAccumulator {
+    pub fn push(&mut self, tx: &Transaction, categoriser: &Categoriser) {
+        if !tx.is_failed() {
+            self.push_categorised(tx, &categoriser.categorise_detail(tx), categoriser);
+        }
+    }
+
+    /// `push` for a transaction the caller has already categorised.
+    pub fn push_categorised(
+        &mut self,
+        tx: &Transaction,
+        detail: &Categorisation,
+        categoriser: &Categoriser,
+    ) {
+        if tx.is_failed() {
+            return;
+        }
+        if let Some(d) = tx.date() {
+            let month = (d.year(), d.month());
+            self.span = Some(match self.span {
+                Some((first, last)) => (first.min(month), last.max(month)),
+                None => (month, month),
+            });
+        }
+        if tx.is_credit() {
+            self.credits += tx.amount;
+        }
+
+        let tokens = payer_tokens(&tx.narration);
+        let text = text_evidence(tx, detail, categoriser);
+        if text >= MIN_EVIDENCE {
+            self.tally.add(tx, detail, categoriser);
+            if !tokens.is_empty() {
+                self.known.insert(tokens);
+            }
+            return;
+        }
+        // Without tokens nothing can vouch for it.
+        if tokens.is_empty() || !can_be_vouched(text) {
+            return;
+        }
+        if self.pending.len() >= MAX_PENDING_COUNTERPARTIES && !self.pending.contains_key(&tokens) {
+            return;
+        }
+        self.pending
+            .entry(tokens)
+            .or_default()
+            .add(tx, detail, categoriser);
+    }
+
+    pub fn finish(mut self) -> GamblingReport {
+        for (tokens, tally) in &self.pending {
+            if tally.deposit_count + tally.withdrawal_count >= MIN_REPEATS {
+                self.known.insert(tokens.clone());
+            }
+        }
+        for (tokens, tally) in self.pending {
+            if self.known.iter().any(|k| jaccard(k, &tokens) >= 0.5) {
+                self.tally.merge(tally);
+            }
+        }
+        self.tally.report(Vec::new(), self.span, self.credits)
+    }
+}
+
+/// Share of income and frequency set the grade; a rising trend or heavy
+/// betting frequency pushes it up one step.
+fn grade(report: &GamblingReport) -> GamblingRisk {
+    if report.deposit_count == 0 {
+        return GamblingRisk::None;
+    }
+    let base = match report.income_share {
+        s if s < 0.05 => GamblingRisk::Low,
+        s if s < 0.15 => GamblingRisk::Moderate,
+        s if s < 0.30 => GamblingRisk::High,
+        _ => GamblingRisk::Severe,
+    };
+    if report.trend > 0.1 || report.transactions_per_month >= 15.0 {
+        base.raised()
+    } else {
+        base
+    }
+}
+
+/// Slope per month relative to the series mean, 0 under three months.
+fn normalised_slope(series: &[f64]) -> f64 {
+    let n = series.len() as f64;
+    let mean = series.iter().sum::<f64>() / n;
+    if series.len() < 3 || mean == 0.0 {
+        return 0.0;
+    }
+    let x_mean = (n - 1.0) / 2.0;
+    let (mut num, mut den) = (0.0, 0.0);
+    for (i, y) in series.iter().enumerate() {
+        let dx = i as f64 - x_mean;
+        num += dx * (y - mean);
+        den += dx * dx;
+    }
+    num / den / mean
+}
+
+#[cfg(test)]
+mod test_gambling {
+    use super::*;
+    use crate::test_fixtures;
+
+    fn tx(id: &str, date: &str, dc: &str, amount: f64, narration: &str) -> Transaction {
+        Transaction {
+            channel: "WEB".into(),
+            transaction_type: "PAYMENT".into(),
+            ..test_fixtures::tx(id, date, dc, amount, narration)
+        }
+    }
+
+    #[test]
+    fn testing_evidence_beyond_keywords() {
+        let history = vec![
+            // Rule, and a fragment in a word the rule didn't match.
+            tx("1", "2023-01-03", "DEBIT", 2_000.0, "XBETNG SPORTS BET"),
+            tx("2", "2023-01-04", "DEBIT", 1_000.0, "BETWEEN FRIENDS CAFE"),
+            // Keywords whose only fragment is the keyword itself.
+            tx("3", "2023-01-05", "DEBIT", 5_000.0, "NIP TRF TO SPORTYBET"),
+            tx("4", "2023-01-06", "CREDIT", 3_000.0, "SPORTYBET NIGERIA"),
+            // Rule, and a counterparty that keeps coming back.
+            tx("5", "2023-01-07", "DEBIT", 500.0, "BABA IJEBU LOTTO"),
+            tx("6", "2023-01-09", "CREDIT", 1_500.0, "BABA IJEBU LOTTO"),
+            tx("7", "2023-01-12", "DEBIT", 700.0, "BABA IJEBU LOTTO"),
+            // Fragment, and the counterparty of a match above.
+            tx("8", "2023-01-10", "DEBIT", 1_000.0, "XBETNG SPORTS WALLET"),
+            tx("9", "2023-01-08", "DEBIT", 9_000.0, "PAYSTACK*JUMIA"),
+        ];
+        let report = detect_gambling(&history, &Categoriser::default());
+        assert_eq!(report.transaction_ids, ["1", "5", "6", "7", "8"]);
+        assert_eq!(report.deposit_count, 4);
+        assert_eq!(report.withdrawal_count, 1);
+        assert!((report.net_loss - 2_700.0).abs() < 1e-9);
+        assert_eq!(report.platforms, vec!["BABA IJEBU", "SPORTS BET"]);
+    }
+
+    #[test]
+    fn testing_one_signal_is_not_enough() {
+        let history: Vec<Transaction> = [
+            "BETSY",
+            "BETHANY",
+            "BETTERLIFE",
+            "ALPHABETICAL",
+            "SWIMMING POOLS",
+            "PAYSTACK*XBETNG",
+        ]
+        .iter()
+        .enumerate()
+        .flat_map(|(i, name)| {
+            [
+                tx(&format!("{i}a"), "2023-01-05", "DEBIT", 2_000.0, name),
+                tx(&format!("{i}b"), "2023-02-05", "DEBIT", 2_000.0, name),
+            ]
+        })
+        .collect();
+        let report = detect_gambling(&history, &Categoriser::default());
+        assert!(
+            report.transaction_ids.is_empty(),
+            "{:?}",
+            report.transaction_ids
+        );
+        assert_eq!(report.risk, GamblingRisk::None);
+    }
+
+    #[test]
+    fn testing_lone_keyword_is_not_gambling() {
+        let history = vec![
+            tx("1", "2023-01-25", "CREDIT", 100_000.0, "SALARY"),
+            tx("2", "2023-01-10", "DEBIT", 2_000.0, "BET9JA"),
+        ];
+        let report = detect_gambling(&history, &Categoriser::default());
+        assert!(report.transaction_ids.is_empty());
+        assert_eq!(report.risk, GamblingRisk::None);
+
+        let mut stream = GamblingAccumulator::default();
+        for t in &history {
+            stream.push(t, &Categoriser::default());
+        }
+        assert_eq!(stream.finish().deposit_count, 0);
+    }
+
+    #[test]
+    fn testing_grades_risk() {
+        let mut history = vec![
+            tx("s1", "2023-01-25", "CREDIT", 100_000.0, "SALARY"),
+            tx("s2", "2023-02-25", "CREDIT", 100_000.0, "SALARY"),
+            tx("s3", "2023-03-25", "CREDIT", 100_000.0, "SALARY"),
+        ];
+        let light = detect_gambling(&history, &Categoriser::default());
+        assert_eq!(light.risk, GamblingRisk::None);
+
+        history.push(tx("b1", "2023-01-10", "DEBIT", 2_000.0, "BET9JA CASINO"));
+        let report = detect_gambling(&history, &Categoriser::default());
+        assert_eq!(report.risk, GamblingRisk::Low);
+
+        // Growing every month and a quarter of income: severe.
+        history.push(tx("b2", "2023-02-10", "DEBIT", 20_000.0, "BET9JA CASINO"));
+        history.push(tx("b3", "2023-03-10", "DEBIT", 55_000.0, "BET9JA CASINO"));
+        let report = detect_gambling(&history, &Categoriser::default());
+        assert!(report.trend > 0.1);
+        assert_eq!(report.risk, GamblingRisk::Severe);
+    }
+}
@@ -0,0 +1,495 @@
+use super::categorise::{Categoriser, Category, normalise};
+use crate::credit_scoring::features::{median, month_index, std_dev};
+use crate::openbank::Transaction;
+use chrono::{Datelike, Days, Months, NaiveDate};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, BTreeSet};
+
+/// Narration words that say how money moved rather than who sent it.
+pub(crate) const STOP_TOKENS: &[&str] = &[
+    "NIP",
+    "TRF",
+    "TRANSFER",
+    "FRM",
+    "FROM",
+    "TO",
+    "REF",
+    "FT",
+    "INWARD",
+    "OUTWARD",
+    "MOBILE",
+    "CREDIT",
+    "DEBIT",
+    "PAYMENT",
+    "PMT",
+    "VIA",
+    "FOR",
+    "THE",
+    "OF",
+    "USSD",
+    "INTERBANK",
+    "INTRABANK",
+    "APP",
+    "BO",
+    "JAN",
+    "FEB",
+    "MAR",
+    "APR",
+    "MAY",
+    "JUN",
+    "JUL",
+    "AUG",
+    "SEP",
+    "SEPT",
+    "OCT",
+    "NOV",
+    "DEC",
+    "JANUARY",
+    "FEBRUARY",
+    "MARCH",
+    "APRIL",
+    "JUNE",
+    "JULY",
+    "AUGUST",
+    "SEPTEMBER",
+    "OCTOBER",
+    "NOVEMBER",
+    "DECEMBER",
+];
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Frequency {
+    Weekly,
+    Biweekly,
+    Monthly,
+    /// Also covers school terms.
+    Quarterly,
+    Annual,
+}
+
+impl Frequency {
+    /// Median gap (days) each frequency accepts.
+    fn detect(median_days: f64) -> Option<Self> {
+        match median_days {
+            d if (5.0..=9.0).contains(&d) => Some(Self::Weekly),
+            d if (12.0..=17.0).contains(&d) => Some(Self::Biweekly),
+            d if (25.0..=38.0).contains(&d) => Some(Self::Monthly),
+            d if (75.0..=130.0).contains(&d) => Some(Self::Quarterly),
+            d if (330.0..=400.0).contains(&d) => Some(Self::Annual),
+            _ => None,
+        }
+    }
+
+    pub fn period_days(&self) -> f64 {
+        match self {
+            Self::Weekly => 7.0,
+            Self::Biweekly => 14.0,
+            Self::Monthly => 30.44,
+            Self::Quarterly => 91.31,
+            Self::Annual => 365.25,
+        }
+    }
+
+    /// The next occurrence after `date`, keeping the day of month for
+    /// calendar frequencies.
+    pub fn advance(&self, date: NaiveDate) -> NaiveDate {
+        let next = match self {
+            Self::Weekly => date.checked_add_days(Days::new(7)),
+            Self::Biweekly => date.checked_add_days(Days::new(14)),
+            Self::Monthly => date.checked_add_months(Months::new(1)),
+            Self::Quarterly => date.checked_add_months(Months::new(3)),
+            Self::Annual => date.checked_add_months(Months::new(12)),
+        };
+        next.unwrap_or(date)
+    }
+
+    /// How far off schedule a payment can land and still be "on time".
+    pub fn tolerance_days(&self) -> f64 {
+        match self {
+            Self::Weekly => 2.0,
+            Self::Biweekly => 3.0,
+            Self::Monthly => 5.0,
+            Self::Quarterly => 15.0,
+            Self::Annual => 30.0,
+        }
+    }
+}
+
+/// A group of transactions that repeat on a schedule with a stable amount.
+/// Shared by the income and obligations detectors.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct RecurringSeries {
+    /// Narration words common to the series, e.g. "ACME LTD SALARY".
+    pub counterparty: String,
+    pub frequency: Frequency,
+    pub category: Category,
+    pub occurrences: u32,
+    pub average_amount: f64,
+    /// Coefficient of variation of the amounts.
+    pub amount_cv: f64,
+    pub first_date: NaiveDate,
+    pub last_date: NaiveDate,
+    /// Share of gaps that landed on schedule.
+    pub regularity: f64,
+    /// Cycles with no payment at all, including overdue ones at the end.
+    pub missed_cycles: u32,
+    /// Cycles paid, but later than the tolerance allows.
+    pub late_cycles: u32,
+    pub transaction_ids: Vec<String>,
+}
+
+impl RecurringSeries {
+    pub fn next_expected(&self) -> NaiveDate {
+        self.frequency.advance(self.last_date)
+    }
+
+    /// Average amount spread over a 30.44 day month.
+    pub fn monthly_amount(&self) -> f64 {
+        self.average_amount * 30.44 / self.frequency.period_days()
+    }
+
+    /// Calendar months in an unbroken run, ending at the latest payment.
+    pub fn continuous_months(&self, dates: &[NaiveDate]) -> u32 {
+        let months: BTreeSet<i32> = dates
+            .iter()
+            .map(|d| month_index((d.year(), d.month())))
+            .collect();
+        let Some(mut current) = months.last().copied() else {
+            return 0;
+        };
+        let mut run = 0;
+        while months.contains(&current) {
+            run += 1;
+            current -= 1;
+        }
+        run
+    }
+}
+
+/// Tunables for `detect_recurring`.
+#[derive(Clone, Copy, Debug)]
+pub struct RecurrenceRules {
+    pub min_occurrences: u32,
+    /// Minimum token overlap (Jaccard) for two narrations to be the same payer.
+    pub min_similarity: f64,
+    pub max_amount_cv: f64,
+    pub min_regularity: f64,
+}
+
+impl Default for RecurrenceRules {
+    fn default() -> Self {
+        Self {
+            min_occurrences: 3,
+            min_similarity: 0.5,
+            max_amount_cv: 0.35,
+            min_regularity: 0.5,
+        }
+    }
+}
+
+struct Cluster<'a> {
+    signature: BTreeSet<String>,
+    members: Vec<(&'a Transaction, NaiveDate, BTreeSet<String>)>,
+}
+
+/// Finds recurring series among `transactions` (callers pre-filter to
+/// credits or debits). `history_end` is the last date we have data for and
+/// is used to count overdue cycles.
+pub fn detect_recurring(
+    transactions: &[&Transaction],
+    categoriser: &Categoriser,
+    rules: &RecurrenceRules,
+    history_end: NaiveDate,
+) -> Vec<RecurringSeries> {
+    let mut dated: Vec<(&Transaction, NaiveDate)> = transactions
+        .iter()
+        .filter(|tx| !tx.is_failed())
+        .filter_map(|tx| tx.date().map(|d| (*tx, d)))
+        .collect();
+    dated.sort_by_key(|(_, d)| *d);
+
+    let mut clusters: Vec<Cluster> = Vec::new();
+    for (tx, date) in dated {
+        let tokens = payer_tokens(&tx.narration);
+        if tokens.is_empty() {
+            continue;
+        }
+        let best = clusters
+            .iter_mut()
+            .map(|c| (jaccard(&c.signature, &tokens), c))
+            .filter(|(s, _)| *s >= rules.min_similarity)
+            .max_by(|a, b| a.0.total_cmp(&b.0));
+        match best {
+            Some((_, cluster)) => cluster.members.push((tx, date, tokens)),
+            None => clusters.push(Cluster {
+                signature: tokens.clone(),
+                members: vec![(tx, date, tokens)],
+            }),
+        }
+    }
+
+    clusters
+        .into_iter()
+        .filter_map(|c| series_from_cluster(c, categoriser, rules, history_end))
+        .collect()
+}
+
+fn series_from_cluster(
+    cluster: Cluster,
+    categoriser: &Categoriser,
+    rules: &RecurrenceRules,
+    history_end: NaiveDate,
+) -> Option<RecurringSeries> {
+    let members = &cluster.members;
+    if (members.len() as u32) < rules.min_occurrences {
+        return None;
+    }
+
+    let mut gaps: Vec<f64> = members
+        .windows(2)
+        .map(|w| (w[1].1 - w[0].1).num_days() as f64)
+        .filter(|g| *g > 0.0)
+        .collect();
+    if gaps.is_empty() {
+        return None;
+    }
+    let frequency = Frequency::detect(median(&mut gaps))?;
+    let period = frequency.period_days();
+    let tolerance = frequency.tolerance_days();
+
+    let mut on_time = 0;
+    let mut late_cycles = 0;
+    let mut missed_cycles = 0;
+    for gap in &gaps {
+        let cycles = (gap / period).round().max(1.0);
+        let deviation = gap - cycles * period;
+        missed_cycles += cycles as u32 - 1;
+        if deviation.abs() <= tolerance {
+            on_time += 1;
+        } else if deviation > tolerance {
+            late_cycles += 1;
+        }
+    }
+    let regularity = on_time as f64 / gaps.len() as f64;
+
+    let amounts: Vec<f64> = members.iter().map(|(tx, _, _)| tx.amount).collect();
+    let average_amount = amounts.iter().sum::<f64>() / amounts.len() as f64;
+    let amount_cv = if average_amount > 0.0 {
+        std_dev(&amounts) / average_amount
+    } else {
+        0.0
+    };
+    if amount_cv > rules.max_amount_cv || regularity < rules.min_regularity {
+        return None;
+    }
+
+    let first_date = members.first()?.1;
+    let last_date = members.last()?.1;
+    // Payments that should have arrived between the last one and the end
+    // of the statement.
+    let overdue = (history_end - last_date).num_days() as f64 - tolerance;
+    if overdue > 0.0 {
+        missed_cycles += (overdue / period).floor() as u32;
+    }
+
+    Some(RecurringSeries {
+        counterparty: common_tokens(members),
+        frequency,
+        category: majority_category(members.iter().map(|(tx, _, _)| categoriser.categorise(tx))),
+        occurrences: members.len() as u32,
+        average_amount,
+        amount_cv,
+        first_date,
+        last_date,
+        regularity,
+        missed_cycles,
+        late_cycles,
+        transaction_ids: members.iter().map(|(tx, _, _)| tx.id.clone()).collect(),
+    })
+}
+
+/// Recurring credits and what they say about the applicant's employment.
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+pub struct IncomeReport {
+    pub streams: Vec<RecurringSeries>,
+    /// Counterparty of the largest salary-like stream.
+    pub likely_employer: Option<String>,
+    pub months_continuous_employment: u32,
+    pub missed_pay_cycles: u32,
+    pub late_pay_cycles: u32,
+    pub income_source_count: u32,
+    /// Share of all credits that came from recurring streams.
+    pub recurring_income_share: f64,
+}
+
+impl IncomeReport {
+    pub fn primary(&self) -> Option<&RecurringSeries> {
+        self.streams
+            .iter()
+            .filter(|s| is_salary_like(s))
+            .max_by(|a, b| a.monthly_amount().total_cmp(&b.monthly_amount()))
+    }
+}
+
+fn is_salary_like(series: &RecurringSeries) -> bool {
+    series.category == Category::Salary
+        || (series.frequency == Frequency::Monthly && series.amount_cv <= 0.1)
+}
+
+/// Finds recurring credits and the likely employer. Loan disbursements are
+/// left out, they are not income.
+pub fn detect_income(transactions: &[Transaction], categoriser: &Categoriser) -> IncomeReport {
+    match transactions.iter().filter_map(|t| t.date()).max() {
+        Some(history_end) => detect_income_until(transactions, categoriser, history_end),
+        None => IncomeReport::default(),
+    }
+}
+
+/// `detect_income` for a history that ends on `history_end`, which may be
+/// later than the last transaction given.
+pub(crate) fn detect_income_until(
+    transactions: &[Transaction],
+    categoriser: &Categoriser,
+    history_end: NaiveDate,
+) -> IncomeReport {
+    let credits: Vec<&Transaction> = transactions
+        .iter()
+        .filter(|t| t.is_credit() && !t.is_failed())
+        .filter(|t| categoriser.categorise(t) != Category::LoanDisbursement)
+        .collect();
+    let total_credit: f64 = credits.iter().map(|t| t.amount).sum();
+
+    let streams = detect_recurring(
+        &credits,
+        categoriser,
+        &RecurrenceRules::default(),
+        history_end,
+    );
+
+    let mut report = IncomeReport {
+        income_source_count: streams.len() as u32,
+        recurring_income_share: if total_credit > 0.0 {
+            let ids: BTreeSet<&str> = streams
+                .iter()
+                .flat_map(|s| s.transaction_ids.iter().map(String::as_str))
+                .collect();
+            credits
+                .iter()
+                .filter(|t| ids.contains(t.id.as_str()))
+                .map(|t| t.amount)
+                .sum::<f64>()
+                / total_credit
+        } else {
+            0.0
+        },
+        streams,
+        ..Default::default()
+    };
+
+    if let Some(primary) = report.primary().cloned() {
+        let ids: BTreeSet<&str> = primary.transaction_ids.iter().map(String::as_str).collect();
+        let dates: Vec<NaiveDate> = credits
+            .iter()
+            .filter(|t| ids.contains(t.id.as_str()))
+            .filter_map(|t| t.date())
+            .collect();
+        report.likely_employer = Some(primary.counterparty.clone());
+        report.months_continuous_employment = primary.continuous_months(&dates);
+        report.missed_pay_cycles = primary.missed_cycles;
+        report.late_pay_cycles = primary.late_cycles;
+    }
+
+    report
+}
+
+/// Narration words that identify the counterparty.
+pub(crate) fn payer_tokens(narration: &str) -> BTreeSet<String> {
+    normalise(narration)
+        .split_whitespace()
+        .filter(|w| w.len() > 1 && !w.chars().any(|c| c.is_ascii_digit()))
+        .filter(|w| !STOP_TOKENS.contains(w))
+        .map(str::to_string)
+        .collect()
+}
+
+pub(crate) fn jaccard(a: &BTreeSet<String>, b: &BTreeSet<String>) -> f64 {
+    let union = a.union(b).count();
+    if union == 0 {
+        return 0.0;
+    }
+    a.intersection(b).count() as f64 / union as f64
+}
+
+/// Tokens present in at least 60% of members, in first-seen order.
+fn common_tokens(members: &[(&Transaction, NaiveDate, BTreeSet<String>)]) -> String {
+    let mut counts: BTreeMap<&str, usize> = BTreeMap::new();
+    for (_, _, tokens) in members {
+        for t in tokens {
+            *counts.entry(t).or_default() += 1;
+        }
+    }
+    let needed = (members.len() as f64 * 0.6).ceil() as usize;
+    let mut seen = BTreeSet::new();
+    normalise(&members[0].0.narration)
+        .split_whitespace()
+        .filter(|w| counts.get(w).is_some_and(|c| *c >= needed))
+        .filter(|w| seen.insert(w.to_string()))
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+fn majority_category(categories: impl Iterator<Item = Category>) -> Category {
+    let mut counts: BTreeMap<Category, usize> = BTreeMap::new();
+    for c in categories {
+        *counts.entry(c).or_default() += 1;
+    }
+    counts
+        .into_iter()
+        .max_by_key(|(_, n)| *n)
+        .map(|(c, _)| c)
+        .unwrap_or(Category::Uncategorised)
+}
+
+#[cfg(test)]
+mod test_income {
+    use super::*;
+    use crate::test_fixtures;
+
+    fn credit(id: &str, date: &str, amount: f64, narration: &str) -> Transaction {
+        test_fixtures::tx(id, date, "CREDIT", amount, narration)
+    }
+
+    #[test]
+    fn testing_detects_salary_and_missed_cycle() {
+        let history = vec![
+            credit("1", "2023-01-25", 250_000.0, "NIP/ACME LTD/SALARY JAN 2023"),
+            credit("2", "2023-02-24", 250_000.0, "NIP/ACME LTD/SALARY FEB 2023"),
+            credit("3", "2023-03-27", 250_000.0, "NIP/ACME LTD/SALARY MAR 2023"),
+            // April skipped
+            credit("4", "2023-05-25", 255_000.0, "NIP/ACME LTD/SALARY MAY 2023"),
+            credit("5", "2023-06-26", 255_000.0, "NIP/ACME LTD/SALARY JUN 2023"),
+            credit("6", "2023-03-02", 4_000.0, "NIP FRM CHIDI OKAFOR"),
+            credit("7", "2023-06-10", 90_000.0, "FAIRMONEY LOAN DISB"),
+        ];
+        let report = detect_income(&history, &Categoriser::default());
+
+        assert_eq!(report.income_source_count, 1);
+        assert_eq!(report.likely_employer.as_deref(), Some("ACME LTD SALARY"));
+        assert_eq!(report.missed_pay_cycles, 1);
+        assert_eq!(report.late_pay_cycles, 0);
+        assert_eq!(report.months_continuous_employment, 2);
+        assert!(report.recurring_income_share > 0.99);
+    }
+
+    #[test]
+    fn testing_irregular_credits_are_not_recurring() {
+        let history = vec![
+            credit("1", "2023-01-03", 5_000.0, "NIP FRM JOHN"),
+            credit("2", "2023-01-09", 80_000.0, "NIP FRM JOHN"),
+            credit("3", "2023-03-30", 1_000.0, "NIP FRM JOHN"),
+        ];
+        let report = detect_income(&history, &Categoriser::default());
+        assert!(report.streams.is_empty());
+        assert_eq!(report.likely_employer, None);
+    }
+}
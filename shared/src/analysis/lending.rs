@@ -0,0 +1,337 @@
+use super::categorise::{Categoriser, Category, normalise};
+use super::income::STOP_TOKENS;
+use crate::openbank::Transaction;
+use chrono::{Days, NaiveDate};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, BTreeSet};
+
+/// Digital lenders and microfinance banks, with the spellings they use in
+/// narrations.
+const LENDERS: &[(&str, &[&str])] = &[
+    ("Carbon", &["CARBON", "PAYLATER"]),
+    ("FairMoney", &["FAIRMONEY", "FAIR MONEY"]),
+    ("PalmCredit", &["PALMCREDIT", "PALM CREDIT"]),
+    (
+        "Branch",
+        &["BRANCH INTL", "BRANCH INTERNATIONAL", "BRANCHLOAN"],
+    ),
+    ("Renmoney", &["RENMONEY"]),
+    ("Aella", &["AELLA"]),
+    ("KiaKia", &["KIAKIA"]),
+    ("OKash", &["OKASH"]),
+    ("QuickCheck", &["QUICKCHECK"]),
+    ("EasyBuy", &["EASYBUY"]),
+    ("NewCredit", &["NEWCREDIT"]),
+    ("LCredit", &["LCREDIT"]),
+    ("Specta", &["SPECTA"]),
+    ("Migo", &["MIGO"]),
+    ("Page Financials", &["PAGE FINANCIALS"]),
+    ("Lidya", &["LIDYA"]),
+    ("LAPO", &["LAPO"]),
+    ("Accion", &["ACCION"]),
+    ("Baobab", &["BAOBAB"]),
+    ("FINCA", &["FINCA"]),
+    ("AB Microfinance", &["AB MICROFINANCE", "AB MFB"]),
+];
+
+const MFB_MARKERS: &[&str] = &["MFB", "MICROFINANCE"];
+
+/// Words that make a narration a loan even when its category isn't one.
+const LOAN_WORDS: &[&str] = &[
+    "LOAN",
+    "REPAY",
+    "REPAYMENT",
+    "DISB",
+    "DISBURSEMENT",
+    "DISBURSED",
+    "INSTALLMENT",
+    "INSTALMENT",
+];
+
+#[derive(Clone, Copy, Debug, Serialize, Deserialize)]
+#[serde(default)]
+pub struct LendingRules {
+    /// Typical app-loan tenor; a loan is due this long after disbursement.
+    pub loan_term_days: u64,
+    /// Days past due before a repayment counts as late.
+    pub grace_days: u64,
+}
+
+impl Default for LendingRules {
+    fn default() -> Self {
+        Self {
+            loan_term_days: 30,
+            grace_days: 3,
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub enum LoanStatus {
+    Open,
+    /// Still open past its due date (and grace).
+    Overdue,
+    RepaidOnTime,
+    RepaidLate,
+}
+
+/// A loan we saw disbursed, with the repayments matched to it.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct Loan {
+    pub lender: String,
+    pub disbursed_on: NaiveDate,
+    pub principal: f64,
+    pub repaid: f64,
+    pub due_date: NaiveDate,
+    pub closed_on: Option<NaiveDate>,
+    pub status: LoanStatus,
+    /// The disbursement, then every repayment matched to it.
+    pub transaction_ids: Vec<String>,
+}
+
+impl Loan {
+    pub fn outstanding(&self) -> f64 {
+        match self.closed_on {
+            Some(_) => 0.0,
+            None => (self.principal - self.repaid).max(0.0),
+        }
+    }
+}
+
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+pub struct LendingReport {
+    pub loans: Vec<Loan>,
+    /// Every lender seen, disbursing or collecting.
+    pub lenders: Vec<String>,
+    /// Unrepaid principal on loans still open at the end of the statement.
+    pub outstanding: f64,
+    pub active_loans: u32,
+    /// Most lenders with a loan open on the same day.
+    pub max_concurrent_lenders: u32,
+    pub on_time_repayments: u32,
+    pub late_repayments: u32,
+    pub overdue_loans: u32,
+    /// Repayments to loans taken before the statement starts.
+    pub prior_debt_repayments: u32,
+}
+
+impl LendingReport {
+    /// Loans from more than one lender open at the same time.
+    pub fn stacked(&self) -> bool {
+        self.max_concurrent_lenders >= 2
+    }
+
+    /// Share of judged loans repaid on time; `None` when no loan is due yet.
+    pub fn on_time_rate(&self) -> Option<f64> {
+        let judged = self.on_time_repayments + self.late_repayments + self.overdue_loans;
+        (judged > 0).then(|| self.on_time_repayments as f64 / judged as f64)
+    }
+}
+
+/// Name of the lender behind a loan transaction, if it is one we know.
+/// Only loan categories, or narrations with a loan word, count, so other
+/// transfers to and from a lender's account don't. Loan narrations naming
+/// a microfinance bank are credited to that bank.
+pub fn lender_name(tx: &Transaction, categoriser: &Categoriser) -> Option<String> {
+    categorised_lender_name(tx, categoriser.categorise(tx))
+}
+
+/// `lender_name` for a transaction already categorised as `category`.
+pub(crate) fn categorised_lender_name(tx: &Transaction, category: Category) -> Option<String> {
+    let narration = normalise(&tx.narration);
+    let words: Vec<&str> = narration.split_whitespace().collect();
+    let is_loan = matches!(
+        category,
+        Category::LoanDisbursement | Category::LoanRepayment
+    ) || words.iter().any(|w| LOAN_WORDS.contains(w));
+    if !is_loan {
+        return None;
+    }
+    for (name, aliases) in LENDERS {
+        if aliases.iter().any(|a| narration.contains(&normalise(a))) {
+            return Some(name.to_string());
+        }
+    }
+    let at = words.iter().position(|w| MFB_MARKERS.contains(w))?;
+    let name: Vec<&str> = words[..at]
+        .iter()
+        .rev()
+        .take_while(|w| !STOP_TOKENS.contains(w) && !w.chars().any(|c| c.is_ascii_digit()))
+        .take(2)
+        .copied()
+        .collect();
+    (!name.is_empty()).then(|| {
+        let name: Vec<&str> = name.into_iter().rev().collect();
+        format!("{} MFB", name.join(" "))
+    })
+}
+
+/// Matches lender credits (disbursements) with later lender debits
+/// (repayments), oldest loan first, to estimate what is still owed and how
+/// punctually loans are repaid.
+pub fn detect_lending(
+    transactions: &[Transaction],
+    categoriser: &Categoriser,
+    rules: &LendingRules,
+) -> LendingReport {
+    match transactions.iter().filter_map(|t| t.date()).max() {
+        Some(history_end) => detect_lending_until(transactions, categoriser, rules, history_end),
+        None => LendingReport::default(),
+    }
+}
+
+/// `detect_lending` for a history that ends on `history_end`, which may be
+/// later than the last transaction given.
+pub(crate) fn detect_lending_until(
+    transactions: &[Transaction],
+    categoriser: &Categoriser,
+    rules: &LendingRules,
+    history_end: NaiveDate,
+) -> LendingReport {
+    let mut events: Vec<(NaiveDate, String, &Transaction)> = transactions
+        .iter()
+        .filter(|t| !t.is_failed())
+        .filter_map(|t| Some((t.date()?, lender_name(t, categoriser)?, t)))
+        .collect();
+    // Disbursements before repayments on the same day.
+    events.sort_by_key(|(date, _, t)| (*date, t.is_debit()));
+
+    let mut report = LendingReport::default();
+    let mut lenders = BTreeSet::new();
+    let mut open: BTreeMap<String, Vec<usize>> = BTreeMap::new();
+    for (date, lender, tx) in events {
+        lenders.insert(lender.clone());
+        if tx.is_credit() {
+            open.entry(lender.clone())
+                .or_default()
+                .push(report.loans.len());
+            report.loans.push(Loan {
+                due_date: date
+                    .checked_add_days(Days::new(rules.loan_term_days))
+                    .unwrap_or(date),
+                lender,
+                disbursed_on: date,
+                principal: tx.amount,
+                repaid: 0.0,
+                closed_on: None,
+                status: LoanStatus::Open,
+                transaction_ids: vec![tx.id.clone()],
+            });
+            continue;
+        }
+        let queue = open.entry(lender).or_default();
+        let Some(&index) = queue.first() else {
+            report.prior_debt_repayments += 1;
+            continue;
+        };
+        // Anything over the principal is interest and fees on the same loan.
+        let loan = &mut report.loans[index];
+        loan.repaid += tx.amount;
+        loan.transaction_ids.push(tx.id.clone());
+        if loan.repaid >= loan.principal {
+            loan.closed_on = Some(date);
+            queue.remove(0);
+        }
+    }
+
+    for loan in &mut report.loans {
+        let deadline = loan
+            .due_date
+            .checked_add_days(Days::new(rules.grace_days))
+            .unwrap_or(loan.due_date);
+        loan.status = match loan.closed_on {
+            Some(closed) if closed <= deadline => LoanStatus::RepaidOnTime,
+            Some(_) => LoanStatus::RepaidLate,
+            None if history_end > deadline => LoanStatus::Overdue,
+            None => LoanStatus::Open,
+        };
+        match loan.status {
+            LoanStatus::RepaidOnTime => report.on_time_repayments += 1,
+            LoanStatus::RepaidLate => report.late_repayments += 1,
+            LoanStatus::Overdue => report.overdue_loans += 1,
+            LoanStatus::Open => {}
+        }
+        if loan.closed_on.is_none() {
+            report.active_loans += 1;
+            report.outstanding += loan.outstanding();
+        }
+    }
+    report.max_concurrent_lenders = report
+        .loans
+        .iter()
+        .map(|l| l.disbursed_on)
+        .map(|day| {
+            report
+                .loans
+                .iter()
+                .filter(|l| l.disbursed_on <= day && l.closed_on.unwrap_or(history_end) >= day)
+                .map(|l| l.lender.as_str())
+                .collect::<BTreeSet<_>>()
+                .len() as u32
+        })
+        .max()
+        .unwrap_or(0);
+    report.lenders = lenders.into_iter().collect();
+    report
+}
+
+#[cfg(test)]
+mod test_lending {
+    use super::*;
+    use crate::test_fixtures::tx;
+
+    #[test]
+    fn testing_lender_names() {
+        let c = Categoriser::default();
+        let cases = [
+            ("FAIRMONEY LOAN DISB", "CREDIT", Some("FairMoney")),
+            ("NIP/BRANCH INTL/REPAY", "DEBIT", Some("Branch")),
+            (
+                "LOAN DISBURSEMENT FRM HASAL MFB",
+                "CREDIT",
+                Some("HASAL MFB"),
+            ),
+            ("NIP FRM JOHN DOE", "CREDIT", None),
+            // A transfer through an MFB is not a loan.
+            ("NIP TRF TO SPARKLE MFB", "DEBIT", None),
+            // Nor is other business with a lender.
+            ("ACCION STAFF SALARY", "CREDIT", None),
+            ("NIP TRF TO AB MICROFINANCE SAVINGS", "DEBIT", None),
+            ("LAPO LOAN REPAY", "DEBIT", Some("LAPO")),
+        ];
+        for (narration, dc, expected) in cases {
+            assert_eq!(
+                lender_name(&tx("1", "2023-01-01", dc, 1.0, narration), &c).as_deref(),
+                expected,
+                "{narration}"
+            );
+        }
+    }
+
+    #[test]
+    fn testing_stacked_loans_and_punctuality() {
+        let history = vec![
+            tx("1", "2023-01-02", "CREDIT", 20_000.0, "CARBON LOAN DISB"),
+            tx("2", "2023-01-20", "DEBIT", 23_000.0, "CARBON REPAYMENT"),
+            tx("3", "2023-02-01", "CREDIT", 30_000.0, "FAIRMONEY LOAN DISB"),
+            tx("4", "2023-02-10", "CREDIT", 15_000.0, "OKASH LOAN"),
+            tx("5", "2023-03-01", "DEBIT", 10_000.0, "FAIRMONEY REPAYMENT"),
+            tx("6", "2023-03-20", "DEBIT", 25_000.0, "FAIRMONEY REPAYMENT"),
+            tx("7", "2023-01-05", "DEBIT", 5_000.0, "PALMCREDIT REPAYMENT"),
+            tx("8", "2023-04-30", "DEBIT", 2_000.0, "SHOPRITE"),
+        ];
+        let report = detect_lending(&history, &Categoriser::default(), &LendingRules::default());
+
+        assert_eq!(report.loans.len(), 3);
+        assert_eq!(report.lenders.len(), 4);
+        assert_eq!(report.prior_debt_repayments, 1);
+        assert_eq!(report.on_time_repayments, 1);
+        assert_eq!(report.late_repayments, 1);
+        // OKash was never repaid and is long past due.
+        assert_eq!(report.overdue_loans, 1);
+        assert_eq!(report.active_loans, 1);
+        assert!((report.outstanding - 15_000.0).abs() < 1e-9);
+        assert!(report.stacked());
+        assert!((report.on_time_rate().unwrap() - 1.0 / 3.0).abs() < 1e-9);
+    }
+}
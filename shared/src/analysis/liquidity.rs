@@ -0,0 +1,202 @@
+use crate::openbank::Transaction;
+use chrono::{Datelike, NaiveDate, NaiveDateTime};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+/// Buffers beyond a year all read the same.
+const MAX_BUFFER_DAYS: f64 = 365.0;
+
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+pub struct DailyBalance {
+    pub date: NaiveDate,
+    /// End-of-day balance.
+    pub balance: f64,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+pub struct MonthlyMinimum {
+    pub year: i32,
+    pub month: u32,
+    pub balance: f64,
+}
+
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+pub struct LiquidityReport {
+    /// One entry per calendar day of the history; quiet days carry the
+    /// previous balance forward.
+    pub daily: Vec<DailyBalance>,
+    pub low_balance_threshold: f64,
+    pub days_below_threshold: u32,
+    pub days_overdrawn: u32,
+    pub monthly_minimums: Vec<MonthlyMinimum>,
+    pub average_daily_balance: f64,
+    pub average_daily_expense: f64,
+    /// Days of average spending the average balance would cover.
+    pub buffer_days: f64,
+}
+
+impl LiquidityReport {
+    pub fn low_balance_share(&self) -> f64 {
+        share(self.days_below_threshold, self.daily.len())
+    }
+
+    pub fn overdraft_share(&self) -> f64 {
+        share(self.days_overdrawn, self.daily.len())
+    }
+}
+
+fn share(days: u32, total: usize) -> f64 {
+    if total == 0 {
+        0.0
+    } else {
+        days as f64 / total as f64
+    }
+}
+
+/// Rebuilds the end-of-day balance from `balance_after` and measures how
+/// often the account runs thin.
+pub fn analyse_liquidity(
+    transactions: &[Transaction],
+    low_balance_threshold: f64,
+) -> LiquidityReport {
+    let mut accumulator = LiquidityAccumulator::new(low_balance_threshold);
+    for tx in transactions {
+        accumulator.push(tx);
+    }
+    accumulator.finish()
+}
+
+/// `analyse_liquidity` one transaction at a time. Memory grows with the days
+/// covered, not the transactions.
+#[derive(Clone, Debug)]
+pub struct LiquidityAccumulator {
+    low_balance_threshold: f64,
+    /// Latest transaction of each day and the balance it left.
+    end_of_day: BTreeMap<NaiveDate, (NaiveDateTime, f64)>,
+    total_debit: f64,
+}
+
+impl LiquidityAccumulator {
+    pub fn new(low_balance_threshold: f64) -> Self {
+        Self {
+            low_balance_threshold,
+            end_of_day: BTreeMap::new(),
+            total_debit: 0.0,
+        }
+    }
+
+    pub fn push(&mut self, tx: &Transaction) {
+        if tx.is_failed() {
+            return;
+        }
+        let Some(ts) = tx.timestamp() else {
+            return;
+        };
+        // Later transactions on a day overwrite earlier ones.
+        let entry = self
+            .end_of_day
+            .entry(ts.date())
+            .or_insert((ts, tx.balance_after));
+        if ts >= entry.0 {
+            *entry = (ts, tx.balance_after);
+        }
+        if tx.is_debit() {
+            self.total_debit += tx.amount;
+        }
+    }
+
+    pub fn finish(self) -> LiquidityReport {
+        let low_balance_threshold = self.low_balance_threshold;
+        let mut report = LiquidityReport {
+            low_balance_threshold,
+            ..Default::default()
+        };
+        let (Some(first), Some(last)) = (
+            self.end_of_day.keys().next().copied(),
+            self.end_of_day.keys().next_back().copied(),
+        ) else {
+            return report;
+        };
+
+        let mut minimums: BTreeMap<(i32, u32), f64> = BTreeMap::new();
+        let mut balance = 0.0;
+        for date in first.iter_days().take_while(|d| *d <= last) {
+            if let Some((_, b)) = self.end_of_day.get(&date) {
+                balance = *b;
+            }
+            report.daily.push(DailyBalance { date, balance });
+            if balance < low_balance_threshold {
+                report.days_below_threshold += 1;
+            }
+            if balance < 0.0 {
+                report.days_overdrawn += 1;
+            }
+            minimums
+                .entry((date.year(), date.month()))
+                .and_modify(|m| *m = m.min(balance))
+                .or_insert(balance);
+        }
+        report.monthly_minimums = minimums
+            .into_iter()
+            .map(|((year, month), balance)| MonthlyMinimum {
+                year,
+                month,
+                balance,
+            })
+            .collect();
+
+        let days = report.daily.len() as f64;
+        report.average_daily_balance = report.daily.iter().map(|d| d.balance).sum::<f64>() / days;
+        report.average_daily_expense = self.total_debit / days;
+        report.buffer_days = if report.average_daily_balance <= 0.0 {
+            0.0
+        } else if report.average_daily_expense > 0.0 {
+            (report.average_daily_balance / report.average_daily_expense).min(MAX_BUFFER_DAYS)
+        } else {
+            MAX_BUFFER_DAYS
+        };
+        report
+    }
+}
+
+#[cfg(test)]
+mod test_liquidity {
+    use super::*;
+    use crate::test_fixtures;
+
+    fn tx(time: &str, dc: &str, amount: f64, balance_after: f64) -> Transaction {
+        Transaction {
+            balance_after,
+            ..test_fixtures::tx("1", time, dc, amount, "Test")
+        }
+    }
+
+    #[test]
+    fn testing_daily_balance_series() {
+        let report = analyse_liquidity(
+            &[
+                tx("2023-01-30T09:00:00", "CREDIT", 3_000.0, 3_000.0),
+                tx("2023-01-30T18:00:00", "DEBIT", 2_500.0, 500.0),
+                tx("2023-02-02T10:00:00", "DEBIT", 1_000.0, -500.0),
+                tx("2023-02-03T10:00:00", "CREDIT", 6_500.0, 6_000.0),
+            ],
+            1_000.0,
+        );
+
+        // 30, 31 Jan then 1, 2, 3 Feb; 31 Jan and 1 Feb carry 500 forward.
+        assert_eq!(report.daily.len(), 5);
+        assert_eq!(report.daily[1].balance, 500.0);
+        assert_eq!(report.days_below_threshold, 4);
+        assert_eq!(report.days_overdrawn, 1);
+        assert_eq!(
+            report
+                .monthly_minimums
+                .iter()
+                .map(|m| m.balance)
+                .collect::<Vec<_>>(),
+            vec![500.0, -500.0]
+        );
+        // 7,000 / 5 balance over 3,500 / 5 spend.
+        assert!((report.buffer_days - 2.0).abs() < 1e-9);
+    }
+}
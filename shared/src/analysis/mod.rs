@@ -0,0 +1,19 @@
+pub mod cashflow;
+pub mod categorise;
+pub mod classifier;
+pub mod gambling;
+pub mod income;
+pub mod lending;
+pub mod liquidity;
+pub mod obligations;
+
+pub use cashflow::{CashFlowAccumulator, CashFlowReport, MonthlyFlow, analyse_cash_flow};
+pub use categorise::{Categorisation, Categoriser, Category, Rule, RuleSet};
+pub use classifier::{NarrationClassifier, Prediction};
+pub use gambling::{GamblingAccumulator, GamblingReport, GamblingRisk, detect_gambling};
+pub use income::{IncomeReport, RecurringSeries, detect_income};
+pub use lending::{LendingReport, LendingRules, Loan, LoanStatus, detect_lending};
+pub use liquidity::{
+    DailyBalance, LiquidityAccumulator, LiquidityReport, MonthlyMinimum, analyse_liquidity,
+};
+pub use obligations::{BillDue, Obligation, ObligationsReport, bill_calendar, detect_obligations};
@@ -0,0 +1,299 @@
+use super::features::{FeatureVector, names};
+use super::training::fit_logistic;
+use super::{CreditScore, RiskBand, classify_risk};
+use crate::result::{Error, Result};
+use serde::{Deserialize, Serialize};
+
+pub const BUREAU_MIN: u16 = 300;
+pub const BUREAU_MAX: u16 = 850;
+
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+pub struct CalibrationPoint {
+    pub score: f64,
+    pub probability_of_default: f64,
+}
+
+/// Maps an engine's raw 0-100 `total_score` to a probability of default.
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub enum Calibration {
+    /// Keep whatever PD the engine reported, if any.
+    #[default]
+    None,
+    /// `PD = 1 / (1 + e^-(intercept + slope * score))`.
+    Platt { intercept: f64, slope: f64 },
+    /// Step-wise non-increasing curve, linear between points and flat
+    /// beyond the ends.
+    Isotonic { points: Vec<CalibrationPoint> },
+}
+
+impl Calibration {
+    /// Logistic fit of default on the raw score.
+    pub fn fit_platt(samples: &[(f64, bool)]) -> Result<Self> {
+        check_samples(samples)?;
+        let rows: Vec<Vec<f64>> = samples.iter().map(|(s, _)| vec![1.0, *s]).collect();
+        let y: Vec<f64> = samples.iter().map(|(_, d)| *d as u8 as f64).collect();
+        let beta = fit_logistic(&rows, &y, 1e-6);
+        Ok(Self::Platt {
+            intercept: beta[0],
+            slope: beta[1],
+        })
+    }
+
+    /// Pool-adjacent-violators: default rates that never rise with score.
+    pub fn fit_isotonic(samples: &[(f64, bool)]) -> Result<Self> {
+        check_samples(samples)?;
+        let mut sorted = samples.to_vec();
+        sorted.sort_by(|a, b| a.0.total_cmp(&b.0));
+
+        // (score sum, defaults, count), ties start in one block.
+        let mut blocks: Vec<(f64, f64, f64)> = Vec::new();
+        for (i, (score, defaulted)) in sorted.iter().enumerate() {
+            let tied = i > 0 && sorted[i - 1].0 == *score;
+            match blocks.last_mut() {
+                Some(last) if tied => {
+                    last.0 += score;
+                    last.1 += *defaulted as u8 as f64;
+                    last.2 += 1.0;
+                }
+                _ => blocks.push((*score, *defaulted as u8 as f64, 1.0)),
+            }
+            while blocks.len() > 1 {
+                let (a, b) = (blocks[blocks.len() - 2], blocks[blocks.len() - 1]);
+                if b.1 / b.2 <= a.1 / a.2 {
+                    break;
+                }
+                blocks.pop();
+                *blocks.last_mut().unwrap() = (a.0 + b.0, a.1 + b.1, a.2 + b.2);
+            }
+        }
+        Ok(Self::Isotonic {
+            points: blocks
+                .into_iter()
+                .map(|(sum, defaults, count)| CalibrationPoint {
+                    score: sum / count,
+                    probability_of_default: defaults / count,
+                })
+                .collect(),
+        })
+    }
+
+    pub fn probability_of_default(&self, score: f64) -> Option<f64> {
+        match self {
+            Self::None => None,
+            Self::Platt { intercept, slope } => {
+                Some(1.0 / (1.0 + (-(intercept + slope * score)).exp()))
+            }
+            Self::Isotonic { points } => {
+                let (first, last) = (points.first()?, points.last()?);
+                if score <= first.score {
+                    return Some(first.probability_of_default);
+                }
+                if score >= last.score {
+                    return Some(last.probability_of_default);
+                }
+                let upper = points.iter().position(|p| p.score > score)?;
+                let (a, b) = (points[upper - 1], points[upper]);
+                let t = (score - a.score) / (b.score - a.score);
+                Some(
+                    a.probability_of_default
+                        + t * (b.probability_of_default - a.probability_of_default),
+                )
+            }
+        }
+    }
+}
+
+/// Both fits need defaulted and repaid samples: with one class Platt's
+/// intercept runs off to infinity and isotonic maps every score to a PD of
+/// 0 or 1.
+fn check_samples(samples: &[(f64, bool)]) -> Result<()> {
+    if samples.is_empty() {
+        return Err(Error::user(
+            "There are no scored histories to calibrate on.",
+        ));
+    }
+    let defaults = samples.iter().filter(|(_, d)| *d).count();
+    if defaults == 0 || defaults == samples.len() {
+        return Err(Error::user(
+            "Calibration needs both defaulted and repaid customers.",
+        ));
+    }
+    Ok(())
+}
+
+/// One score shown several ways.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct ScoreScales {
+    /// 0-100, `100 * (1 - PD)` when a PD is known.
+    pub percent: u8,
+    /// 300-850, the range consumer bureaus use.
+    pub bureau: u16,
+    /// A (best) to E.
+    pub grade: String,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct ScaleRules {
+    #[serde(default)]
+    pub calibration: Calibration,
+    /// Letter grades by minimum 0-100 score, like `risk_bands`.
+    #[serde(default = "default_grades")]
+    pub grades: Vec<RiskBand>,
+}
+
+impl Default for ScaleRules {
+    fn default() -> Self {
+        Self {
+            calibration: Calibration::default(),
+            grades: default_grades(),
+        }
+    }
+}
+
+pub fn default_grades() -> Vec<RiskBand> {
+    [(80, "A"), (65, "B"), (50, "C"), (35, "D"), (0, "E")]
+        .into_iter()
+        .map(|(min_score, label)| RiskBand {
+            min_score,
+            label: label.into(),
+        })
+        .collect()
+}
+
+impl ScaleRules {
+    pub fn scales(&self, percent: u8) -> ScoreScales {
+        let span = (BUREAU_MAX - BUREAU_MIN) as f64;
+        ScoreScales {
+            percent,
+            bureau: BUREAU_MIN + (span * percent as f64 / 100.0).round() as u16,
+            grade: classify_risk(percent, &self.grades),
+        }
+    }
+
+    /// Calibrates the raw score when configured, restating `total_score`
+    /// and `risk_level` from the PD, then fills in `scales`. A score with no
+    /// transactions behind it is left uncalibrated.
+    pub fn apply(
+        &self,
+        features: &FeatureVector,
+        score: &mut CreditScore,
+        risk_bands: &[RiskBand],
+    ) {
+        let has_history = features.get(names::TRANSACTION_COUNT) > 0.0;
+        if let Some(pd) = self
+            .calibration
+            .probability_of_default(score.total_score as f64)
+            .filter(|_| has_history)
+        {
+            score.probability_of_default = Some(pd);
+            score.total_score = ((1.0 - pd) * 100.0).clamp(0.0, 100.0).round() as u8;
+            score.risk_level = classify_risk(score.total_score, risk_bands);
+        }
+        score.scales = Some(self.scales(score.total_score));
+    }
+}
+
+#[cfg(test)]
+mod test_calibration {
+    use super::*;
+
+    /// Raw scores 0-100 where the true PD is `sigmoid(2 - 0.06 * score)`.
+    fn samples() -> Vec<(f64, bool)> {
+        let mut seed: u64 = 7;
+        let mut next = || {
+            seed = seed
+                .wrapping_mul(6364136223846793005)
+                .wrapping_add(1442695040888963407);
+            (seed >> 33) as f64 / (1u64 << 31) as f64
+        };
+        (0..4000)
+            .map(|_| {
+                let score = (next() * 100.0).round();
+                let pd = 1.0 / (1.0 + (-(2.0 - 0.06 * score)).exp());
+                (score, next() < pd)
+            })
+            .collect()
+    }
+
+    #[test]
+    fn testing_platt_recovers_curve() {
+        let Calibration::Platt { intercept, slope } = Calibration::fit_platt(&samples()).unwrap()
+        else {
+            panic!("expected Platt");
+        };
+        assert!((intercept - 2.0).abs() < 0.3, "{intercept}");
+        assert!((slope + 0.06).abs() < 0.01, "{slope}");
+    }
+
+    #[test]
+    fn testing_calibration_needs_both_classes() {
+        assert!(Calibration::fit_platt(&[]).is_err());
+        assert!(Calibration::fit_isotonic(&[]).is_err());
+        let repaid = [(80.0, false), (60.0, false), (40.0, false)];
+        assert!(Calibration::fit_platt(&repaid).is_err());
+        assert!(Calibration::fit_isotonic(&repaid).is_err());
+        let defaulted = [(80.0, true), (60.0, true)];
+        assert!(Calibration::fit_isotonic(&defaulted).is_err());
+    }
+
+    #[test]
+    fn testing_isotonic_is_monotonic() {
+        let calibration = Calibration::fit_isotonic(&samples()).unwrap();
+        let Calibration::Isotonic { points } = &calibration else {
+            panic!("expected isotonic");
+        };
+        assert!(
+            points
+                .windows(2)
+                .all(|w| w[1].probability_of_default <= w[0].probability_of_default)
+        );
+        let low = calibration.probability_of_default(10.0).unwrap();
+        let high = calibration.probability_of_default(90.0).unwrap();
+        assert!(low > 0.6 && high < 0.1, "{low} {high}");
+        // Flat beyond the ends.
+        assert_eq!(
+            calibration.probability_of_default(-5.0),
+            Some(points[0].probability_of_default)
+        );
+    }
+
+    #[test]
+    fn testing_scales() {
+        let rules = ScaleRules {
+            calibration: Calibration::Platt {
+                intercept: 0.0,
+                slope: 0.0,
+            },
+            ..ScaleRules::default()
+        };
+        let mut fv = FeatureVector::new();
+        fv.set(names::TRANSACTION_COUNT, 12.0);
+        let mut score = CreditScore::from_components(Vec::new(), Vec::new(), &[]);
+        score.total_score = 90;
+        rules.apply(&fv, &mut score, &super::super::default_risk_bands());
+
+        // A coin flip, whatever the raw score.
+        assert_eq!(score.probability_of_default, Some(0.5));
+        assert_eq!(score.total_score, 50);
+        assert_eq!(score.risk_level, "Medium");
+        assert_eq!(
+            score.scales,
+            Some(ScoreScales {
+                percent: 50,
+                bureau: 575,
+                grade: "C".into(),
+            })
+        );
+
+        // Nothing to calibrate without history.
+        let bands = super::super::default_risk_bands();
+        let mut empty = super::super::no_history_score(&bands);
+        rules.apply(&FeatureVector::new(), &mut empty, &bands);
+        assert_eq!(empty.total_score, 0);
+        assert_eq!(empty.probability_of_default, None);
+        assert_eq!(empty.scales.map(|s| s.percent), Some(0));
+
+        assert_eq!(ScaleRules::default().scales(100).bureau, BUREAU_MAX);
+        assert_eq!(ScaleRules::default().scales(0).grade, "E");
+    }
+}
@@ -0,0 +1,256 @@
+use super::features::{FeaturePipeline, FeatureVector};
+use super::streaming::FeatureStream;
+use super::{CreditScore, ScoringConfig, ScoringModel};
+use super::{
+    gbdt::GbdtEngine, heuristic::HeuristicEngine, recency::RecencyEngine,
+    scorecard::ScorecardEngine,
+};
+use crate::analysis::categorise::Categoriser;
+use crate::openbank::Transaction;
+use crate::result::{Error, Result};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+#[cfg(feature = "onnx")]
+use std::collections::HashMap;
+use std::sync::Arc;
+#[cfg(feature = "onnx")]
+use std::sync::Mutex;
+#[cfg(feature = "onnx")]
+use std::time::SystemTime;
+
+/// A scoring model: turns extracted features into a `CreditScore`.
+///
+/// Engines only see features, never the command layer. To ship a new model,
+/// implement this trait and register it in `ModelRegistry::default()` (or at
+/// runtime with `ModelRegistry::register`), then select it with
+/// `ScoringModel::Registered { id }`.
+pub trait ScoringEngine: Send + Sync {
+    /// Stable identifier, used as the registry key.
+    fn id(&self) -> &str;
+    fn name(&self) -> &str;
+    fn version(&self) -> &str;
+    fn description(&self) -> &str {
+        ""
+    }
+    /// Parameters the engine accepts, for the UI to render a config form.
+    fn config_schema(&self) -> Vec<ConfigField> {
+        Vec::new()
+    }
+
+    /// Feature extraction step. Override when the model needs to weight or
+    /// filter transactions differently, but keep using `FeaturePipeline`.
+    fn extract(&self, transactions: &[Transaction], config: &ScoringConfig) -> FeatureVector {
+        FeaturePipeline::for_config(config).extract(transactions)
+    }
+
+    /// Single-pass counterpart of `extract`, for histories too large to
+    /// hold at once. Override alongside `extract`.
+    fn stream<'a>(&'a self, config: &ScoringConfig) -> FeatureStream<'a> {
+        FeaturePipeline::for_config(config).stream()
+    }
+
+    fn score(&self, features: &FeatureVector, config: &ScoringConfig) -> CreditScore;
+
+    fn evaluate(&self, transactions: &[Transaction], config: &ScoringConfig) -> CreditScore {
+        self.score(&self.extract(transactions, config), config)
+    }
+
+    /// `evaluate` behind the config's data-sufficiency gate.
+    fn assess(&self, transactions: &[Transaction], config: &ScoringConfig) -> Result<CreditScore> {
+        self.assess_features(&self.extract(transactions, config), transactions, config)
+    }
+
+    /// `assess` on features already extracted, e.g. by `stream`.
+    /// `transactions` only supplies the evidence behind each reason and may
+    /// be empty.
+    fn assess_features(
+        &self,
+        features: &FeatureVector,
+        transactions: &[Transaction],
+        config: &ScoringConfig,
+    ) -> Result<CreditScore> {
+        let categoriser = Categoriser::for_config(config);
+        let mut score = self.score(features, config);
+        score.reasons =
+            config
+                .reasons
+                .explain(features, transactions, config, &categoriser, |fv| {
+                    self.score(fv, config)
+                });
+        config
+            .scales
+            .apply(features, &mut score, &config.risk_bands);
+        let mut score = config.sufficiency.apply(features, score)?;
+        if let Some(warning) = categoriser.model_warning() {
+            score.data_warnings.push(warning.to_string());
+        }
+        Ok(score)
+    }
+
+    fn info(&self) -> EngineInfo {
+        EngineInfo {
+            id: self.id().to_string(),
+            name: self.name().to_string(),
+            version: self.version().to_string(),
+            description: self.description().to_string(),
+            config_schema: self.config_schema(),
+        }
+    }
+}
+
+/// What the UI gets when listing engines.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct EngineInfo {
+    pub id: String,
+    pub name: String,
+    pub version: String,
+    pub description: String,
+    pub config_schema: Vec<ConfigField>,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct ConfigField {
+    pub key: String,
+    pub label: String,
+    pub kind: FieldKind,
+    pub default: Option<serde_json::Value>,
+    pub required: bool,
+}
+
+impl ConfigField {
+    pub fn new(key: &str, label: &str, kind: FieldKind) -> Self {
+        Self {
+            key: key.to_string(),
+            label: label.to_string(),
+            kind,
+            default: None,
+            required: true,
+        }
+    }
+
+    pub fn default_x(mut self, value: serde_json::Value) -> Self {
+        self.default = Some(value);
+        self
+    }
+
+    pub fn optional_x(mut self) -> Self {
+        self.required = false;
+        self
+    }
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub enum FieldKind {
+    Number,
+    Integer,
+    Date,
+    Text,
+    Choice(Vec<String>),
+    /// Free-form structured value (e.g. decay buckets), edited as JSON.
+    Json,
+}
+
+/// Engines keyed by id.
+#[derive(Clone)]
+pub struct ModelRegistry {
+    engines: BTreeMap<String, Arc<dyn ScoringEngine>>,
+    /// ONNX engines `resolve` has loaded, keyed by their spec as JSON, with
+    /// the file's modification time when loaded. Shared between clones.
+    #[cfg(feature = "onnx")]
+    onnx: Arc<Mutex<OnnxCache>>,
+}
+
+#[cfg(feature = "onnx")]
+type OnnxCache = HashMap<String, (Option<SystemTime>, Arc<dyn ScoringEngine>)>;
+
+impl Default for ModelRegistry {
+    /// Registry holding the built-in engines that work with default
+    /// parameters. Scorecard, GBDT and ONNX models need a fitted artifact,
+    /// so they are only reachable through their `ScoringModel` variant.
+    fn default() -> Self {
+        let mut registry = Self::new();
+        registry.register(HeuristicEngine::default());
+        registry.register(RecencyEngine::default());
+        registry
+    }
+}
+
+impl ModelRegistry {
+    pub fn new() -> Self {
+        Self {
+            engines: BTreeMap::new(),
+            #[cfg(feature = "onnx")]
+            onnx: Arc::default(),
+        }
+    }
+
+    /// Adds (or replaces) the engine under its id.
+    pub fn register(&mut self, engine: impl ScoringEngine + 'static) {
+        self.engines
+            .insert(engine.id().to_string(), Arc::new(engine));
+    }
+
+    pub fn get(&self, id: &str) -> Option<Arc<dyn ScoringEngine>> {
+        self.engines.get(id).cloned()
+    }
+
+    /// The engines `ScoringModel::Registered` can name.
+    pub fn list(&self) -> Vec<EngineInfo> {
+        self.engines.values().map(|e| e.info()).collect()
+    }
+
+    /// Builds the engine a config asks for. Built-in variants carry their
+    /// own parameters; `Registered` looks the id up as-is.
+    pub fn resolve(&self, model: &ScoringModel) -> Result<Arc<dyn ScoringEngine>> {
+        match model {
+            ScoringModel::HeuristicWeighted { target_balance } => Ok(Arc::new(HeuristicEngine {
+                target_balance: *target_balance,
+            })),
+            ScoringModel::RecencyWeighted { decay, as_of } => Ok(Arc::new(RecencyEngine {
+                decay: decay.clone(),
+                as_of: *as_of,
+            })),
+            ScoringModel::Scorecard { card } => {
+                Ok(Arc::new(ScorecardEngine { card: card.clone() }))
+            }
+            ScoringModel::Gbdt { model } => Ok(Arc::new(GbdtEngine {
+                model: model.clone(),
+            })),
+            #[cfg(feature = "onnx")]
+            ScoringModel::Onnx { spec } => self.onnx_engine(spec),
+            #[cfg(not(feature = "onnx"))]
+            ScoringModel::Onnx { .. } => Err(Error::user(
+                "This build can't run ONNX models; enable the `onnx` feature.",
+            )),
+            ScoringModel::Registered { id } => self.get(id).ok_or_else(|| {
+                let known: Vec<&str> = self.engines.keys().map(String::as_str).collect();
+                Error::notfound("No scoring model is registered under that id.")
+                    .add_meta_x("model_id", id)
+                    .add_meta_x("registered", &known.join(", "))
+            }),
+        }
+    }
+
+    /// The ONNX engine for `spec`, read from disk only the first time or
+    /// after the file changes.
+    #[cfg(feature = "onnx")]
+    fn onnx_engine(&self, spec: &super::onnx::OnnxSpec) -> Result<Arc<dyn ScoringEngine>> {
+        let key = serde_json::to_string(spec)?;
+        let modified = std::fs::metadata(&spec.path)
+            .and_then(|m| m.modified())
+            .ok();
+        if let Some((at, engine)) = self.onnx.lock().unwrap().get(&key)
+            && *at == modified
+        {
+            return Ok(engine.clone());
+        }
+        // Loaded without the lock; a concurrent load of the same spec just
+        // replaces an identical entry.
+        let engine: Arc<dyn ScoringEngine> = Arc::new(super::onnx::OnnxEngine::load(spec)?);
+        self.onnx
+            .lock()
+            .unwrap()
+            .insert(key, (modified, engine.clone()));
+        Ok(engine)
+    }
+}
@@ -0,0 +1,818 @@
+use super::streaming::{FeatureStream, QuantileSketch};
+use super::{LiquidityRules, ScoringConfig};
+use crate::analysis::cashflow::{CashFlowReport, analyse_cash_flow};
+use crate::analysis::categorise::{Categoriser, Category};
+use crate::analysis::gambling::{GamblingReport, detect_gambling};
+use crate::analysis::income::detect_income_until;
+use crate::analysis::lending::{LendingRules, detect_lending_until};
+use crate::analysis::liquidity::{LiquidityReport, analyse_liquidity};
+use crate::analysis::obligations::detect_obligations_until;
+use crate::openbank::Transaction;
+use chrono::{Datelike, NaiveDate};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, BTreeSet};
+
+/// Bump whenever a feature is added, removed or changes meaning, so stored
+/// vectors (and models trained on them) can be told apart.
+pub const FEATURE_SET_VERSION: u32 = 9;
+
+/// Feature names produced by `FeaturePipeline`. Share features are keyed
+/// by prefix plus the lowercased value, e.g. `channel_share.atm`.
+pub mod names {
+    pub const TRANSACTION_COUNT: &str = "transaction_count";
+    pub const CREDIT_COUNT: &str = "credit_count";
+    pub const DEBIT_COUNT: &str = "debit_count";
+    pub const FAILED_COUNT: &str = "failed_count";
+    /// Failed transactions, summed by weight.
+    pub const FAILED_WEIGHT: &str = "failed_weight";
+    pub const SUCCESS_RATE: &str = "success_rate";
+
+    pub const TOTAL_CREDIT: &str = "total_credit";
+    pub const TOTAL_DEBIT: &str = "total_debit";
+    pub const NET_FLOW: &str = "net_flow";
+    pub const INCOME_MEAN: &str = "income_mean";
+    pub const INCOME_MEDIAN: &str = "income_median";
+    pub const INCOME_MAX: &str = "income_max";
+    pub const EXPENSE_MEAN: &str = "expense_mean";
+
+    pub const BALANCE_AVG: &str = "balance_avg";
+    pub const BALANCE_MIN: &str = "balance_min";
+    pub const BALANCE_MAX: &str = "balance_max";
+    pub const BALANCE_STD: &str = "balance_std";
+    pub const BALANCE_LAST: &str = "balance_last";
+
+    /// Coefficient of variation of monthly credit totals.
+    pub const INCOME_VOLATILITY: &str = "income_volatility";
+    /// Coefficient of variation of balances.
+    pub const BALANCE_VOLATILITY: &str = "balance_volatility";
+    /// Coefficient of variation of monthly debit totals.
+    pub const EXPENSE_VOLATILITY: &str = "expense_volatility";
+    /// Share of months where credits beat debits.
+    pub const POSITIVE_MONTH_SHARE: &str = "positive_month_share";
+    /// Net flow of the worst month.
+    pub const WORST_MONTH_NET: &str = "worst_month_net";
+    /// Largest fall of the balance from a running peak.
+    pub const MAX_DRAWDOWN: &str = "max_drawdown";
+    /// `max_drawdown` over that peak, 0-1.
+    pub const MAX_DRAWDOWN_PCT: &str = "max_drawdown_pct";
+
+    /// Days whose closing balance was under the configured threshold.
+    pub const DAYS_BELOW_THRESHOLD: &str = "days_below_threshold";
+    pub const LOW_BALANCE_SHARE: &str = "low_balance_share";
+    pub const DAYS_OVERDRAWN: &str = "days_overdrawn";
+    pub const OVERDRAFT_SHARE: &str = "overdraft_share";
+    /// Mean of each month's lowest closing balance.
+    pub const MONTHLY_MIN_BALANCE: &str = "monthly_min_balance";
+    /// Days of average spending the average daily balance covers.
+    pub const BUFFER_DAYS: &str = "buffer_days";
+
+    pub const HISTORY_DAYS: &str = "history_days";
+    pub const ACTIVE_DAYS: &str = "active_days";
+    pub const MONTHS_SPANNED: &str = "months_spanned";
+    pub const INCOME_MONTHS: &str = "income_months";
+    /// Longest run of months with no transactions at all.
+    pub const LONGEST_GAP_MONTHS: &str = "longest_gap_months";
+
+    pub const CHANNEL_SHARE: &str = "channel_share";
+    pub const TYPE_SHARE: &str = "type_share";
+    /// Share of total credit amount per `Category`.
+    pub const CREDIT_SHARE: &str = "credit_share";
+    /// Share of total debit amount per `Category`.
+    pub const DEBIT_SHARE: &str = "debit_share";
+
+    /// 1 when a salary-like recurring credit was found.
+    pub const SALARY_DETECTED: &str = "salary_detected";
+    /// Unbroken months of pay from the likely employer.
+    pub const MONTHS_EMPLOYED: &str = "months_employed";
+    pub const MISSED_PAY_CYCLES: &str = "missed_pay_cycles";
+    pub const LATE_PAY_CYCLES: &str = "late_pay_cycles";
+    pub const INCOME_SOURCE_COUNT: &str = "income_source_count";
+    pub const RECURRING_INCOME_SHARE: &str = "recurring_income_share";
+    pub const PRIMARY_INCOME_AMOUNT: &str = "primary_income_amount";
+
+    pub const OBLIGATION_COUNT: &str = "obligation_count";
+    /// Recurring debits spread to a monthly amount.
+    pub const MONTHLY_OBLIGATIONS: &str = "monthly_obligations";
+    /// Loan installments per month over monthly income.
+    pub const DEBT_SERVICE_RATIO: &str = "debt_service_ratio";
+    /// All recurring obligations per month over monthly income.
+    pub const OBLIGATION_RATIO: &str = "obligation_ratio";
+
+    /// Digital lenders and microfinance banks seen in the history.
+    pub const LENDER_COUNT: &str = "lender_count";
+    pub const ACTIVE_LOANS: &str = "active_loans";
+    /// Unrepaid principal on open loans.
+    pub const LOAN_OUTSTANDING: &str = "loan_outstanding";
+    /// Most lenders with a loan open at once; 2 or more is loan stacking.
+    pub const MAX_CONCURRENT_LENDERS: &str = "max_concurrent_lenders";
+    pub const ON_TIME_REPAYMENTS: &str = "on_time_repayments";
+    pub const LATE_REPAYMENTS: &str = "late_repayments";
+    pub const OVERDUE_LOANS: &str = "overdue_loans";
+
+    pub const GAMBLING_DEPOSITS: &str = "gambling_deposits";
+    pub const GAMBLING_WITHDRAWALS: &str = "gambling_withdrawals";
+    pub const GAMBLING_NET_LOSS: &str = "gambling_net_loss";
+    /// Gambling transactions per month.
+    pub const GAMBLING_FREQUENCY: &str = "gambling_frequency";
+    /// Deposits to betting platforms over other credits.
+    pub const GAMBLING_INCOME_SHARE: &str = "gambling_income_share";
+    /// Relative monthly slope of deposits, above 0 when growing.
+    pub const GAMBLING_TREND: &str = "gambling_trend";
+    /// `GamblingRisk` level, 0 (none) to 4 (severe).
+    pub const GAMBLING_RISK: &str = "gambling_risk";
+}
+
+/// Named, versioned features computed from a transaction history. This is
+/// what every scoring engine consumes and what gets stored for audit.
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct FeatureVector {
+    pub version: u32,
+    pub values: BTreeMap<String, f64>,
+}
+
+impl FeatureVector {
+    pub fn new() -> Self {
+        Self {
+            version: FEATURE_SET_VERSION,
+            values: BTreeMap::new(),
+        }
+    }
+
+    /// Missing features read as 0, which is what every count or sum would be.
+    pub fn get(&self, name: &str) -> f64 {
+        self.try_get(name).unwrap_or(0.0)
+    }
+
+    pub fn try_get(&self, name: &str) -> Option<f64> {
+        self.values.get(name).copied()
+    }
+
+    pub fn set(&mut self, name: &str, value: f64) {
+        self.values.insert(name.to_string(), value);
+    }
+
+    /// All `prefix.*` features with the prefix stripped.
+    pub fn group(&self, prefix: &str) -> BTreeMap<&str, f64> {
+        self.values
+            .iter()
+            .filter_map(|(k, v)| {
+                k.strip_prefix(prefix)
+                    .and_then(|rest| rest.strip_prefix('.'))
+                    .map(|rest| (rest, *v))
+            })
+            .collect()
+    }
+
+    fn set_shares(&mut self, prefix: &str, counts: BTreeMap<String, u32>, total: u32) {
+        if total == 0 {
+            return;
+        }
+        for (key, count) in counts {
+            self.set(&share_key(prefix, &key), count as f64 / total as f64);
+        }
+    }
+
+    fn set_amount_shares(&mut self, prefix: &str, amounts: BTreeMap<Category, f64>, total: f64) {
+        if total <= 0.0 {
+            return;
+        }
+        for (category, amount) in amounts {
+            self.set(&share_key(prefix, category.key()), amount / total);
+        }
+    }
+
+    /// Share of credits (or debits) that fell in `category`.
+    pub fn category_share(&self, prefix: &str, category: Category) -> f64 {
+        self.get(&share_key(prefix, category.key()))
+    }
+}
+
+pub fn share_key(prefix: &str, value: &str) -> String {
+    format!("{prefix}.{}", value.trim().to_lowercase().replace(' ', "_"))
+}
+
+pub(crate) type Weighting<'a> = Box<dyn Fn(&Transaction) -> Option<f64> + 'a>;
+
+/// Turns transactions into a `FeatureVector`. By default every transaction
+/// counts once; `weighted` lets a model down-weight (or drop, with `None`)
+/// individual transactions, which scales amounts, balances and failures.
+pub struct FeaturePipeline<'a> {
+    weighting: Option<Weighting<'a>>,
+    categoriser: Categoriser,
+    low_balance_threshold: f64,
+    lending: LendingRules,
+    until: Option<NaiveDate>,
+}
+
+impl Default for FeaturePipeline<'_> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<'a> FeaturePipeline<'a> {
+    pub fn new() -> Self {
+        Self {
+            weighting: None,
+            categoriser: Categoriser::default(),
+            low_balance_threshold: LiquidityRules::default().low_balance_threshold,
+            lending: LendingRules::default(),
+            until: None,
+        }
+    }
+
+    /// Pipeline using the config's categorisation overrides, liquidity
+    /// threshold and lending rules.
+    pub fn for_config(config: &ScoringConfig) -> Self {
+        Self::new()
+            .categoriser(Categoriser::for_config(config))
+            .low_balance_threshold(config.liquidity.low_balance_threshold)
+            .lending(config.lending)
+    }
+
+    pub fn weighted(mut self, weighting: impl Fn(&Transaction) -> Option<f64> + 'a) -> Self {
+        self.weighting = Some(Box::new(weighting));
+        self
+    }
+
+    pub fn categoriser(mut self, categoriser: Categoriser) -> Self {
+        self.categoriser = categoriser;
+        self
+    }
+
+    pub fn low_balance_threshold(mut self, threshold: f64) -> Self {
+        self.low_balance_threshold = threshold;
+        self
+    }
+
+    pub fn lending(mut self, rules: LendingRules) -> Self {
+        self.lending = rules;
+        self
+    }
+
+    /// Leaves out transactions dated after `date`, to score the history as
+    /// it stood that day.
+    pub fn until(mut self, date: NaiveDate) -> Self {
+        self.until = Some(date);
+        self
+    }
+
+    fn weight(&self, tx: &Transaction) -> Option<f64> {
+        match &self.weighting {
+            Some(f) => f(tx),
+            None => Some(1.0),
+        }
+    }
+
+    pub fn extract(&self, transactions: &[Transaction]) -> FeatureVector {
+        let kept: Vec<Transaction>;
+        let transactions = match self.until {
+            Some(until) => {
+                kept = transactions
+                    .iter()
+                    .filter(|t| t.date().is_none_or(|d| d <= until))
+                    .cloned()
+                    .collect();
+                &kept
+            }
+            None => transactions,
+        };
+
+        let mut core = CoreStats::new(Incomes::Exact(Vec::new()));
+        for tx in transactions {
+            if let Some(w) = self.weight(tx) {
+                core.push(tx, w, self.categoriser.categorise(tx));
+            }
+        }
+
+        let mut fv = FeatureVector::new();
+        let Some(totals) = core.finish(&mut fv) else {
+            return fv;
+        };
+        set_pattern_features(
+            &mut fv,
+            transactions,
+            transactions.iter().filter_map(|t| t.date()).max(),
+            &self.categoriser,
+            &self.lending,
+            &totals,
+        );
+        set_cash_flow_features(&mut fv, &analyse_cash_flow(transactions));
+        set_liquidity_features(
+            &mut fv,
+            &analyse_liquidity(transactions, self.low_balance_threshold),
+        );
+        set_gambling_features(&mut fv, &detect_gambling(transactions, &self.categoriser));
+        fv
+    }
+
+    /// Same features from transactions pushed one at a time; see
+    /// `FeatureStream`.
+    pub fn stream(self) -> FeatureStream<'a> {
+        FeatureStream::new(
+            self.weighting,
+            self.categoriser,
+            self.low_balance_threshold,
+            self.lending,
+            self.until,
+        )
+    }
+}
+
+/// Credit amounts, kept whole for exact medians or sketched to bound memory.
+pub(crate) enum Incomes {
+    Exact(Vec<f64>),
+    Sketch(QuantileSketch),
+}
+
+/// What `CoreStats::finish` hands on to the pattern features.
+pub(crate) struct Totals {
+    total_credit: f64,
+    months_spanned: i32,
+}
+
+/// Running totals behind every feature that doesn't need pattern
+/// detection. Memory grows with the days, months and distinct channels
+/// seen, plus the incomes when they are kept exact.
+pub(crate) struct CoreStats {
+    count: u32,
+    credit_count: u32,
+    debit_count: u32,
+    failed_count: u32,
+    failed_weight: f64,
+    total_weight: f64,
+    success_weight: f64,
+    total_credit: f64,
+    total_debit: f64,
+    balance_sum: f64,
+    /// Unweighted running mean and squared deviations (Welford).
+    balance_mean: f64,
+    balance_m2: f64,
+    balance_min: f64,
+    balance_max: f64,
+    latest_balance: Option<f64>,
+    incomes: Incomes,
+    income_sum: f64,
+    income_max: f64,
+    last_balance: Option<(NaiveDate, f64)>,
+    days: BTreeSet<NaiveDate>,
+    monthly_income: BTreeMap<(i32, u32), f64>,
+    months: BTreeSet<(i32, u32)>,
+    channels: BTreeMap<String, u32>,
+    types: BTreeMap<String, u32>,
+    credit_categories: BTreeMap<Category, f64>,
+    debit_categories: BTreeMap<Category, f64>,
+}
+
+impl CoreStats {
+    pub(crate) fn new(incomes: Incomes) -> Self {
+        Self {
+            count: 0,
+            credit_count: 0,
+            debit_count: 0,
+            failed_count: 0,
+            failed_weight: 0.0,
+            total_weight: 0.0,
+            success_weight: 0.0,
+            total_credit: 0.0,
+            total_debit: 0.0,
+            balance_sum: 0.0,
+            balance_mean: 0.0,
+            balance_m2: 0.0,
+            balance_min: f64::INFINITY,
+            balance_max: f64::NEG_INFINITY,
+            latest_balance: None,
+            incomes,
+            income_sum: 0.0,
+            income_max: 0.0,
+            last_balance: None,
+            days: BTreeSet::new(),
+            monthly_income: BTreeMap::new(),
+            months: BTreeSet::new(),
+            channels: BTreeMap::new(),
+            types: BTreeMap::new(),
+            credit_categories: BTreeMap::new(),
+            debit_categories: BTreeMap::new(),
+        }
+    }
+
+    pub(crate) fn push(&mut self, tx: &Transaction, w: f64, category: Category) {
+        self.count += 1;
+        self.total_weight += w;
+
+        // Track balance history for average calculation
+        self.balance_sum += tx.balance_after * w;
+        let delta = tx.balance_after - self.balance_mean;
+        self.balance_mean += delta / self.count as f64;
+        self.balance_m2 += delta * (tx.balance_after - self.balance_mean);
+        self.balance_min = self.balance_min.min(tx.balance_after);
+        self.balance_max = self.balance_max.max(tx.balance_after);
+        self.latest_balance = Some(tx.balance_after);
+
+        let date = tx.date();
+        if let Some(d) = date {
+            self.days.insert(d);
+            self.months.insert((d.year(), d.month()));
+            if self.last_balance.is_none_or(|(last, _)| d >= last) {
+                self.last_balance = Some((d, tx.balance_after));
+            }
+        }
+
+        if tx.is_failed() {
+            self.failed_count += 1;
+            self.failed_weight += w;
+            return; // Skip financial sums for failed transactions
+        }
+
+        self.success_weight += w;
+        *self.channels.entry(tx.channel.clone()).or_default() += 1;
+        *self.types.entry(tx.transaction_type.clone()).or_default() += 1;
+
+        match tx.debit_credit.as_str() {
+            "CREDIT" => {
+                self.credit_count += 1;
+                self.total_credit += tx.amount * w;
+                self.income_sum += tx.amount;
+                self.income_max = self.income_max.max(tx.amount);
+                match &mut self.incomes {
+                    Incomes::Exact(amounts) => amounts.push(tx.amount),
+                    Incomes::Sketch(sketch) => sketch.insert(tx.amount),
+                }
+                *self.credit_categories.entry(category).or_default() += tx.amount * w;
+                if let Some(d) = date {
+                    *self
+                        .monthly_income
+                        .entry((d.year(), d.month()))
+                        .or_default() += tx.amount * w;
+                }
+            }
+            "DEBIT" => {
+                self.debit_count += 1;
+                self.total_debit += tx.amount * w;
+                *self.debit_categories.entry(category).or_default() += tx.amount * w;
+            }
+            _ => {}
+        }
+    }
+
+    /// Sets the features and returns what the pattern features need, or
+    /// `None` when nothing was pushed.
+    pub(crate) fn finish(self, fv: &mut FeatureVector) -> Option<Totals> {
+        fv.set(names::TRANSACTION_COUNT, self.count as f64);
+        fv.set(names::CREDIT_COUNT, self.credit_count as f64);
+        fv.set(names::DEBIT_COUNT, self.debit_count as f64);
+        fv.set(names::FAILED_COUNT, self.failed_count as f64);
+        fv.set(names::FAILED_WEIGHT, self.failed_weight);
+        fv.set(names::TOTAL_CREDIT, self.total_credit);
+        fv.set(names::TOTAL_DEBIT, self.total_debit);
+        fv.set(names::NET_FLOW, self.total_credit - self.total_debit);
+
+        if self.count == 0 {
+            return None;
+        }
+
+        let avg_balance = if self.total_weight > 0.0 {
+            self.balance_sum / self.total_weight
+        } else {
+            0.0
+        };
+        let success_rate = if self.total_weight > 0.0 {
+            self.success_weight / self.total_weight
+        } else {
+            0.0
+        };
+        fv.set(names::SUCCESS_RATE, success_rate);
+
+        let income_count = match &self.incomes {
+            Incomes::Exact(amounts) => amounts.len() as u64,
+            Incomes::Sketch(sketch) => sketch.count(),
+        };
+        if income_count > 0 {
+            fv.set(names::INCOME_MEAN, self.income_sum / income_count as f64);
+            let median = match self.incomes {
+                Incomes::Exact(mut amounts) => median(&mut amounts),
+                Incomes::Sketch(sketch) => sketch.median(),
+            };
+            fv.set(names::INCOME_MEDIAN, median);
+            fv.set(names::INCOME_MAX, self.income_max);
+        }
+        if self.debit_count > 0 {
+            fv.set(
+                names::EXPENSE_MEAN,
+                self.total_debit / self.debit_count as f64,
+            );
+        }
+
+        fv.set(names::BALANCE_AVG, avg_balance);
+        fv.set(names::BALANCE_MIN, self.balance_min);
+        fv.set(names::BALANCE_MAX, self.balance_max);
+        // Population standard deviation, 0 under two balances.
+        let balance_std = if self.count < 2 {
+            0.0
+        } else {
+            (self.balance_m2 / self.count as f64).sqrt()
+        };
+        fv.set(names::BALANCE_STD, balance_std);
+        fv.set(
+            names::BALANCE_LAST,
+            self.last_balance
+                .map(|(_, b)| b)
+                .or(self.latest_balance)
+                .unwrap_or(0.0),
+        );
+        fv.set(
+            names::BALANCE_VOLATILITY,
+            coefficient_of_variation(balance_std, self.balance_mean),
+        );
+
+        // Months between first and last seen month, inclusive, so a silent
+        // month in the middle still counts against income consistency.
+        let months_spanned = match (self.months.first(), self.months.last()) {
+            (Some(first), Some(last)) => month_index(*last) - month_index(*first) + 1,
+            _ => 0,
+        };
+        let mut monthly: Vec<f64> = Vec::new();
+        if let Some(first) = self.months.first() {
+            for i in 0..months_spanned {
+                let key = from_month_index(month_index(*first) + i);
+                monthly.push(self.monthly_income.get(&key).copied().unwrap_or(0.0));
+            }
+        }
+        fv.set(
+            names::INCOME_VOLATILITY,
+            coefficient_of_variation(std_dev(&monthly), mean(&monthly)),
+        );
+
+        let history_days = match (self.days.first(), self.days.last()) {
+            (Some(first), Some(last)) => (*last - *first).num_days() + 1,
+            _ => 0,
+        };
+        fv.set(names::HISTORY_DAYS, history_days as f64);
+        fv.set(names::ACTIVE_DAYS, self.days.len() as f64);
+        fv.set(names::MONTHS_SPANNED, months_spanned as f64);
+        fv.set(names::INCOME_MONTHS, self.monthly_income.len() as f64);
+        let longest_gap = self
+            .months
+            .iter()
+            .map(|m| month_index(*m))
+            .collect::<Vec<_>>()
+            .windows(2)
+            .map(|w| w[1] - w[0] - 1)
+            .max()
+            .unwrap_or(0);
+        fv.set(names::LONGEST_GAP_MONTHS, longest_gap as f64);
+
+        let successful = self.count - self.failed_count;
+        fv.set_shares(names::CHANNEL_SHARE, self.channels, successful);
+        fv.set_shares(names::TYPE_SHARE, self.types, successful);
+        fv.set_amount_shares(
+            names::CREDIT_SHARE,
+            self.credit_categories,
+            self.total_credit,
+        );
+        fv.set_amount_shares(names::DEBIT_SHARE, self.debit_categories, self.total_debit);
+
+        Some(Totals {
+            total_credit: self.total_credit,
+            months_spanned,
+        })
+    }
+}
+
+/// Income, obligation and lending features, which look for recurring
+/// patterns across `transactions` in a history ending on `history_end`
+/// (`None` when no transaction is dated).
+pub(crate) fn set_pattern_features(
+    fv: &mut FeatureVector,
+    transactions: &[Transaction],
+    history_end: Option<NaiveDate>,
+    categoriser: &Categoriser,
+    lending_rules: &LendingRules,
+    totals: &Totals,
+) {
+    let income = history_end
+        .map(|end| detect_income_until(transactions, categoriser, end))
+        .unwrap_or_default();
+    let primary = income.primary();
+    fv.set(names::SALARY_DETECTED, primary.is_some() as u8 as f64);
+    fv.set(
+        names::PRIMARY_INCOME_AMOUNT,
+        primary.map(|p| p.average_amount).unwrap_or(0.0),
+    );
+    fv.set(
+        names::MONTHS_EMPLOYED,
+        income.months_continuous_employment as f64,
+    );
+    fv.set(names::MISSED_PAY_CYCLES, income.missed_pay_cycles as f64);
+    fv.set(names::LATE_PAY_CYCLES, income.late_pay_cycles as f64);
+    fv.set(
+        names::INCOME_SOURCE_COUNT,
+        income.income_source_count as f64,
+    );
+    fv.set(names::RECURRING_INCOME_SHARE, income.recurring_income_share);
+
+    // Salary when we found one, otherwise average credits per month.
+    let monthly_income = match primary {
+        Some(p) => p.monthly_amount(),
+        None if totals.months_spanned > 0 => totals.total_credit / totals.months_spanned as f64,
+        None => 0.0,
+    };
+    let obligations = history_end
+        .map(|end| detect_obligations_until(transactions, categoriser, monthly_income, end))
+        .unwrap_or_default();
+    fv.set(
+        names::OBLIGATION_COUNT,
+        obligations.obligations.len() as f64,
+    );
+    fv.set(names::MONTHLY_OBLIGATIONS, obligations.monthly_commitment);
+    fv.set(names::DEBT_SERVICE_RATIO, obligations.debt_service_ratio);
+    fv.set(names::OBLIGATION_RATIO, obligations.obligation_ratio);
+
+    let lending = history_end
+        .map(|end| detect_lending_until(transactions, categoriser, lending_rules, end))
+        .unwrap_or_default();
+    fv.set(names::LENDER_COUNT, lending.lenders.len() as f64);
+    fv.set(names::ACTIVE_LOANS, lending.active_loans as f64);
+    fv.set(names::LOAN_OUTSTANDING, lending.outstanding);
+    fv.set(
+        names::MAX_CONCURRENT_LENDERS,
+        lending.max_concurrent_lenders as f64,
+    );
+    fv.set(names::ON_TIME_REPAYMENTS, lending.on_time_repayments as f64);
+    fv.set(names::LATE_REPAYMENTS, lending.late_repayments as f64);
+    fv.set(names::OVERDUE_LOANS, lending.overdue_loans as f64);
+}
+
+pub(crate) fn set_cash_flow_features(fv: &mut FeatureVector, cash_flow: &CashFlowReport) {
+    fv.set(names::EXPENSE_VOLATILITY, cash_flow.expense_cv);
+    fv.set(names::POSITIVE_MONTH_SHARE, cash_flow.positive_month_share);
+    fv.set(
+        names::WORST_MONTH_NET,
+        cash_flow.worst_month.as_ref().map(|m| m.net).unwrap_or(0.0),
+    );
+    fv.set(names::MAX_DRAWDOWN, cash_flow.max_drawdown);
+    fv.set(names::MAX_DRAWDOWN_PCT, cash_flow.max_drawdown_pct);
+}
+
+pub(crate) fn set_liquidity_features(fv: &mut FeatureVector, liquidity: &LiquidityReport) {
+    fv.set(
+        names::DAYS_BELOW_THRESHOLD,
+        liquidity.days_below_threshold as f64,
+    );
+    fv.set(names::LOW_BALANCE_SHARE, liquidity.low_balance_share());
+    fv.set(names::DAYS_OVERDRAWN, liquidity.days_overdrawn as f64);
+    fv.set(names::OVERDRAFT_SHARE, liquidity.overdraft_share());
+    let minimums: Vec<f64> = liquidity
+        .monthly_minimums
+        .iter()
+        .map(|m| m.balance)
+        .collect();
+    fv.set(names::MONTHLY_MIN_BALANCE, mean(&minimums));
+    fv.set(names::BUFFER_DAYS, liquidity.buffer_days);
+}
+
+pub(crate) fn set_gambling_features(fv: &mut FeatureVector, gambling: &GamblingReport) {
+    fv.set(names::GAMBLING_DEPOSITS, gambling.deposits);
+    fv.set(names::GAMBLING_WITHDRAWALS, gambling.withdrawals);
+    fv.set(names::GAMBLING_NET_LOSS, gambling.net_loss);
+    fv.set(names::GAMBLING_FREQUENCY, gambling.transactions_per_month);
+    fv.set(names::GAMBLING_INCOME_SHARE, gambling.income_share);
+    fv.set(names::GAMBLING_TREND, gambling.trend);
+    fv.set(names::GAMBLING_RISK, gambling.risk.level() as f64);
+}
+
+pub fn extract_features(transactions: &[Transaction]) -> FeatureVector {
+    FeaturePipeline::new().extract(transactions)
+}
+
+pub(crate) fn month_index((year, month): (i32, u32)) -> i32 {
+    year * 12 + month as i32 - 1
+}
+
+pub(crate) fn from_month_index(index: i32) -> (i32, u32) {
+    (index.div_euclid(12), index.rem_euclid(12) as u32 + 1)
+}
+
+pub(crate) fn mean(values: &[f64]) -> f64 {
+    if values.is_empty() {
+        return 0.0;
+    }
+    values.iter().sum::<f64>() / values.len() as f64
+}
+
+pub(crate) fn median(values: &mut [f64]) -> f64 {
+    if values.is_empty() {
+        return 0.0;
+    }
+    values.sort_by(f64::total_cmp);
+    let mid = values.len() / 2;
+    if values.len().is_multiple_of(2) {
+        (values[mid - 1] + values[mid]) / 2.0
+    } else {
+        values[mid]
+    }
+}
+
+/// Population standard deviation.
+pub(crate) fn std_dev(values: &[f64]) -> f64 {
+    if values.len() < 2 {
+        return 0.0;
+    }
+    let m = mean(values);
+    (values.iter().map(|v| (v - m).powi(2)).sum::<f64>() / values.len() as f64).sqrt()
+}
+
+pub(crate) fn coefficient_of_variation(std_dev: f64, mean: f64) -> f64 {
+    if mean.abs() < f64::EPSILON {
+        0.0
+    } else {
+        std_dev / mean.abs()
+    }
+}
+
+#[cfg(test)]
+mod test_features {
+    use super::*;
+    use crate::test_fixtures;
+
+    fn tx(time: &str, debit_credit: &str, amount: f64, balance: f64, channel: &str) -> Transaction {
+        Transaction {
+            channel: channel.into(),
+            balance_after: balance,
+            ..test_fixtures::tx("1", time, debit_credit, amount, "Test")
+        }
+    }
+
+    #[test]
+    fn testing_extract_features() {
+        let history = vec![
+            tx("2023-01-05", "CREDIT", 1_000.0, 1_000.0, "MOBILE"),
+            tx("2023-01-07", "DEBIT", 400.0, 600.0, "POS"),
+            tx("2023-03-05", "CREDIT", 3_000.0, 3_600.0, "MOBILE"),
+            tx("2023-03-06", "DEBIT", 600.0, 3_000.0, "ATM"),
+        ];
+        let fv = extract_features(&history);
+
+        assert_eq!(fv.version, FEATURE_SET_VERSION);
+        assert_eq!(fv.get(names::TOTAL_CREDIT), 4_000.0);
+        assert_eq!(fv.get(names::NET_FLOW), 3_000.0);
+        assert_eq!(fv.get(names::INCOME_MEDIAN), 2_000.0);
+        assert_eq!(fv.get(names::EXPENSE_MEAN), 500.0);
+        assert_eq!(fv.get(names::BALANCE_MIN), 600.0);
+        assert_eq!(fv.get(names::BALANCE_LAST), 3_000.0);
+        assert_eq!(fv.get(names::ACTIVE_DAYS), 4.0);
+        assert_eq!(fv.get(names::HISTORY_DAYS), 61.0);
+        // January and March have income, February is a silent month.
+        assert_eq!(fv.get(names::MONTHS_SPANNED), 3.0);
+        assert_eq!(fv.get(names::INCOME_MONTHS), 2.0);
+        assert!((fv.get(names::INCOME_VOLATILITY) - 0.935).abs() < 1e-3);
+        assert_eq!(fv.group(names::CHANNEL_SHARE)["mobile"], 0.5);
+    }
+
+    #[test]
+    fn testing_category_shares() {
+        let mut history = vec![
+            tx("2023-01-05", "CREDIT", 3_000.0, 3_000.0, "MOBILE"),
+            tx("2023-01-07", "DEBIT", 250.0, 2_750.0, "WEB"),
+            tx("2023-01-08", "DEBIT", 750.0, 2_000.0, "POS"),
+        ];
+        history[0].narration = "ACME LTD SALARY".into();
+        history[1].narration = "BET9JA DEPOSIT".into();
+        let fv = extract_features(&history);
+
+        assert_eq!(
+            fv.category_share(names::CREDIT_SHARE, Category::Salary),
+            1.0
+        );
+        assert_eq!(
+            fv.category_share(names::DEBIT_SHARE, Category::Gambling),
+            0.25
+        );
+        assert_eq!(
+            fv.category_share(names::DEBIT_SHARE, Category::PosSpend),
+            0.75
+        );
+    }
+
+    #[test]
+    fn testing_weighted_pipeline_drops_and_scales() {
+        let history = vec![
+            tx("2023-01-05", "CREDIT", 1_000.0, 1_000.0, "MOBILE"),
+            tx("not a date", "CREDIT", 5_000.0, 6_000.0, "MOBILE"),
+        ];
+        let fv = FeaturePipeline::new()
+            .weighted(|tx| tx.date().map(|_| 0.5))
+            .extract(&history);
+
+        assert_eq!(fv.get(names::TRANSACTION_COUNT), 1.0);
+        assert_eq!(fv.get(names::TOTAL_CREDIT), 500.0);
+        assert_eq!(fv.get(names::BALANCE_AVG), 1_000.0);
+    }
+
+    #[test]
+    fn testing_feature_vector_roundtrip() {
+        let fv = extract_features(&[tx("2023-01-05", "CREDIT", 1.0, 1.0, "ATM")]);
+        let json = serde_json::to_string(&fv).unwrap();
+        assert_eq!(serde_json::from_str::<FeatureVector>(&json).unwrap(), fv);
+    }
+}
@@ -0,0 +1,725 @@
+use super::engine::{ConfigField, FieldKind, ScoringEngine};
+use super::features::{FeatureVector, names};
+use super::{CreditScore, ScoreComponent, ScoringConfig, classify_risk, no_history_score};
+use crate::result::{Error, Result};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::path::Path;
+
+/// Contributions shown as factors, largest first.
+const TOP_FACTORS: usize = 5;
+
+/// A node of a regression tree. `cover` is the (weighted) count of training
+/// rows that reached it, which TreeSHAP needs to average over unknowns.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum Node {
+    Leaf {
+        value: f64,
+        cover: f64,
+    },
+    Split {
+        /// Index into `TreeEnsemble::features`.
+        feature: usize,
+        threshold: f64,
+        /// `value <= threshold` goes left (LightGBM); otherwise `<` (XGBoost).
+        #[serde(default)]
+        inclusive: bool,
+        /// Where a missing feature goes.
+        #[serde(default)]
+        default_left: bool,
+        cover: f64,
+        left: Box<Node>,
+        right: Box<Node>,
+    },
+}
+
+impl Node {
+    fn cover(&self) -> f64 {
+        match self {
+            Self::Leaf { cover, .. } | Self::Split { cover, .. } => *cover,
+        }
+    }
+
+    fn goes_left(&self, value: Option<f64>) -> bool {
+        match self {
+            Self::Leaf { .. } => true,
+            Self::Split {
+                threshold,
+                inclusive,
+                default_left,
+                ..
+            } => match value {
+                Some(v) if *inclusive => v <= *threshold,
+                Some(v) => v < *threshold,
+                None => *default_left,
+            },
+        }
+    }
+
+    fn predict(&self, x: &[Option<f64>]) -> f64 {
+        match self {
+            Self::Leaf { value, .. } => *value,
+            Self::Split {
+                feature,
+                left,
+                right,
+                ..
+            } => {
+                if self.goes_left(x[*feature]) {
+                    left.predict(x)
+                } else {
+                    right.predict(x)
+                }
+            }
+        }
+    }
+
+    /// Output averaged over the training rows, weighted by cover.
+    fn expected_value(&self) -> f64 {
+        match self {
+            Self::Leaf { value, .. } => *value,
+            Self::Split { left, right, .. } => {
+                (left.expected_value() * left.cover() + right.expected_value() * right.cover())
+                    / (left.cover() + right.cover())
+            }
+        }
+    }
+
+    fn validate(&self, features: usize) -> Result<()> {
+        match self {
+            Self::Leaf { .. } => Ok(()),
+            Self::Split {
+                feature,
+                left,
+                right,
+                ..
+            } => {
+                if *feature >= features {
+                    return Err(Error::user("A tree splits on an unknown feature.")
+                        .add_meta_x("feature", &feature.to_string()));
+                }
+                if left.cover() + right.cover() <= 0.0 {
+                    return Err(Error::user("A tree node has no cover."));
+                }
+                left.validate(features)?;
+                right.validate(features)
+            }
+        }
+    }
+}
+
+/// Gradient-boosted trees for binary classification, trained with
+/// label 1 = default. The margin is `base_margin` plus every tree's leaf,
+/// in log-odds of default.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct TreeEnsemble {
+    pub name: String,
+    #[serde(default)]
+    pub version: String,
+    /// Feature names (see `features::names`) the splits refer to by index.
+    pub features: Vec<String>,
+    #[serde(default)]
+    pub base_margin: f64,
+    pub trees: Vec<Node>,
+}
+
+/// Why the ensemble gave the margin it did: `expected_margin` plus every
+/// contribution adds up to `margin`.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct Explanation {
+    pub margin: f64,
+    pub probability_of_default: f64,
+    pub expected_margin: f64,
+    /// SHAP value per feature in log-odds of default, in `features` order.
+    pub contributions: Vec<(String, f64)>,
+}
+
+fn read_error(e: impl ToString) -> Error {
+    Error::user("The tree model could not be read.").add_meta_x("error", &e.to_string())
+}
+
+impl TreeEnsemble {
+    pub fn from_json(json: &str) -> Result<Self> {
+        let model: Self = serde_json::from_str(json).map_err(read_error)?;
+        model.validate()?;
+        Ok(model)
+    }
+
+    /// Reads an XGBoost `dump_model(..., dump_format="json", with_stats=True)`
+    /// file. Splits named `f<n>` refer to `features[n]`, and are an error
+    /// past the end of `features`; any other name is taken as the feature
+    /// itself. `base_score` is XGBoost's, a probability strictly between 0
+    /// and 1.
+    pub fn from_xgboost_dump(
+        name: &str,
+        json: &str,
+        features: &[String],
+        base_score: f64,
+    ) -> Result<Self> {
+        if !(base_score > 0.0 && base_score < 1.0) {
+            return Err(
+                Error::user("The base score must be strictly between 0 and 1.")
+                    .add_meta_x("base_score", &base_score.to_string()),
+            );
+        }
+        let trees: Vec<Value> = serde_json::from_str(json).map_err(read_error)?;
+        let mut model = Self {
+            name: name.to_string(),
+            version: String::new(),
+            features: features.to_vec(),
+            base_margin: (base_score / (1.0 - base_score)).ln(),
+            trees: Vec::with_capacity(trees.len()),
+        };
+        for tree in &trees {
+            let node = xgboost_node(tree, &mut model.features)?;
+            model.trees.push(node);
+        }
+        model.validate()?;
+        Ok(model)
+    }
+
+    /// Reads a LightGBM `Booster.dump_model()` JSON. The starting score is
+    /// already folded into the first tree, so `base_margin` is 0.
+    pub fn from_lightgbm_json(name: &str, json: &str) -> Result<Self> {
+        let dump: Value = serde_json::from_str(json).map_err(read_error)?;
+        let features = dump["feature_names"]
+            .as_array()
+            .ok_or_else(|| read_error("missing feature_names"))?
+            .iter()
+            .map(|f| f.as_str().unwrap_or_default().to_string())
+            .collect();
+        let trees = dump["tree_info"]
+            .as_array()
+            .ok_or_else(|| read_error("missing tree_info"))?
+            .iter()
+            .map(|t| lightgbm_node(&t["tree_structure"]))
+            .collect::<Result<_>>()?;
+        let model = Self {
+            name: name.to_string(),
+            version: dump["version"].as_str().unwrap_or_default().to_string(),
+            features,
+            base_margin: 0.0,
+            trees,
+        };
+        model.validate()?;
+        Ok(model)
+    }
+
+    /// Reads a model file in any of the supported shapes: this crate's own
+    /// JSON, a LightGBM dump (has `tree_info`) or an XGBoost dump (an array).
+    /// An XGBoost dump has no feature list, so its splits must use feature
+    /// names; use `from_xgboost_dump` for one trained on bare `f<n>`s.
+    pub fn load(path: impl AsRef<Path>) -> Result<Self> {
+        let path = path.as_ref();
+        let text = std::fs::read_to_string(path)?;
+        let name = path
+            .file_stem()
+            .and_then(|s| s.to_str())
+            .unwrap_or("Tree model");
+        let value: Value = serde_json::from_str(&text).map_err(read_error)?;
+        if value.is_array() {
+            Self::from_xgboost_dump(name, &text, &[], 0.5)
+        } else if value.get("tree_info").is_some() {
+            Self::from_lightgbm_json(name, &text)
+        } else {
+            Self::from_json(&text)
+        }
+    }
+
+    pub fn validate(&self) -> Result<()> {
+        if self.trees.is_empty() {
+            return Err(Error::user("The tree model has no trees."));
+        }
+        self.trees
+            .iter()
+            .try_for_each(|t| t.validate(self.features.len()))
+    }
+
+    fn inputs(&self, features: &FeatureVector) -> Vec<Option<f64>> {
+        self.features.iter().map(|f| features.try_get(f)).collect()
+    }
+
+    pub fn margin(&self, features: &FeatureVector) -> f64 {
+        let x = self.inputs(features);
+        self.base_margin + self.trees.iter().map(|t| t.predict(&x)).sum::<f64>()
+    }
+
+    pub fn probability_of_default(&self, features: &FeatureVector) -> f64 {
+        sigmoid(self.margin(features))
+    }
+
+    /// Exact path-dependent TreeSHAP (Lundberg et al., 2018).
+    pub fn explain(&self, features: &FeatureVector) -> Explanation {
+        let x = self.inputs(features);
+        let mut phi = vec![0.0; self.features.len()];
+        for tree in &self.trees {
+            tree_shap(tree, &x, &mut phi, Vec::new(), 0, 1.0, 1.0, None);
+        }
+        let expected_margin =
+            self.base_margin + self.trees.iter().map(Node::expected_value).sum::<f64>();
+        let margin = self.margin(features);
+        Explanation {
+            margin,
+            probability_of_default: sigmoid(margin),
+            expected_margin,
+            contributions: self.features.iter().cloned().zip(phi).collect(),
+        }
+    }
+}
+
+fn sigmoid(z: f64) -> f64 {
+    1.0 / (1.0 + (-z).exp())
+}
+
+fn feature_index(name: &str, features: &mut Vec<String>) -> Result<usize> {
+    if let Some(i) = features.iter().position(|f| f == name) {
+        return Ok(i);
+    }
+    if let Some(i) = name.strip_prefix('f').and_then(|n| n.parse::<usize>().ok()) {
+        return if i < features.len() {
+            Ok(i)
+        } else {
+            Err(
+                Error::user("A split refers to a feature by number the model doesn't name.")
+                    .add_meta_x("feature", name)
+                    .add_meta_x("features", &features.len().to_string()),
+            )
+        };
+    }
+    features.push(name.to_string());
+    Ok(features.len() - 1)
+}
+
+fn xgboost_node(node: &Value, features: &mut Vec<String>) -> Result<Node> {
+    let cover = node["cover"].as_f64().unwrap_or(1.0);
+    if let Some(value) = node["leaf"].as_f64() {
+        return Ok(Node::Leaf { value, cover });
+    }
+    let child = |id: &Value| {
+        node["children"]
+            .as_array()
+            .and_then(|c| c.iter().find(|c| c["nodeid"] == *id))
+            .ok_or_else(|| read_error("a split is missing a child"))
+    };
+    let (yes, no) = (child(&node["yes"])?, child(&node["no"])?);
+    let split = node["split"]
+        .as_str()
+        .ok_or_else(|| read_error("a split has no feature"))?;
+    Ok(Node::Split {
+        feature: feature_index(split, features)?,
+        threshold: node["split_condition"].as_f64().unwrap_or_default(),
+        inclusive: false,
+        default_left: node["missing"] == node["yes"],
+        cover,
+        left: Box::new(xgboost_node(yes, features)?),
+        right: Box::new(xgboost_node(no, features)?),
+    })
+}
+
+fn lightgbm_node(node: &Value) -> Result<Node> {
+    if let Some(value) = node["leaf_value"].as_f64() {
+        return Ok(Node::Leaf {
+            value,
+            cover: node["leaf_count"].as_f64().unwrap_or(1.0),
+        });
+    }
+    let feature = node["split_feature"]
+        .as_u64()
+        .ok_or_else(|| read_error("a split has no feature"))?;
+    if node["decision_type"].as_str().is_some_and(|d| d != "<=") {
+        return Err(read_error("only numerical `<=` splits are supported"));
+    }
+    Ok(Node::Split {
+        feature: feature as usize,
+        threshold: node["threshold"].as_f64().unwrap_or_default(),
+        inclusive: true,
+        default_left: node["default_left"].as_bool().unwrap_or(true),
+        cover: node["internal_count"].as_f64().unwrap_or(1.0),
+        left: Box::new(lightgbm_node(&node["left_child"])?),
+        right: Box::new(lightgbm_node(&node["right_child"])?),
+    })
+}
+
+/// One feature on the path from the root, with the share of paths that
+/// go through it when it is unknown (`zero`) or known (`one`).
+#[derive(Clone, Copy)]
+struct PathElement {
+    feature: Option<usize>,
+    zero: f64,
+    one: f64,
+    weight: f64,
+}
+
+fn extend_path(
+    path: &mut Vec<PathElement>,
+    depth: usize,
+    zero: f64,
+    one: f64,
+    feature: Option<usize>,
+) {
+    path.truncate(depth);
+    path.push(PathElement {
+        feature,
+        zero,
+        one,
+        weight: if depth == 0 { 1.0 } else { 0.0 },
+    });
+    let d = depth as f64;
+    for i in (0..depth).rev() {
+        path[i + 1].weight += one * path[i].weight * (i as f64 + 1.0) / (d + 1.0);
+        path[i].weight = zero * path[i].weight * (d - i as f64) / (d + 1.0);
+    }
+}
+
+fn unwind_path(path: &mut [PathElement], depth: usize, index: usize) {
+    let PathElement { zero, one, .. } = path[index];
+    let d = depth as f64;
+    let mut next = path[depth].weight;
+    for i in (0..depth).rev() {
+        if one != 0.0 {
+            let previous = path[i].weight;
+            path[i].weight = next * (d + 1.0) / ((i as f64 + 1.0) * one);
+            next = previous - path[i].weight * zero * (d - i as f64) / (d + 1.0);
+        } else {
+            path[i].weight = path[i].weight * (d + 1.0) / (zero * (d - i as f64));
+        }
+    }
+    for i in index..depth {
+        path[i].feature = path[i + 1].feature;
+        path[i].zero = path[i + 1].zero;
+        path[i].one = path[i + 1].one;
+    }
+}
+
+/// Total weight the path would have with element `index` taken out.
+fn unwound_sum(path: &[PathElement], depth: usize, index: usize) -> f64 {
+    let PathElement { zero, one, .. } = path[index];
+    let d = depth as f64;
+    let mut next = path[depth].weight;
+    let mut total = 0.0;
+    for i in (0..depth).rev() {
+        if one != 0.0 {
+            let part = next * (d + 1.0) / ((i as f64 + 1.0) * one);
+            total += part;
+            next = path[i].weight - part * zero * (d - i as f64) / (d + 1.0);
+        } else {
+            total += path[i].weight / zero * (d + 1.0) / (d - i as f64);
+        }
+    }
+    total
+}
+
+#[allow(clippy::too_many_arguments)]
+fn tree_shap(
+    node: &Node,
+    x: &[Option<f64>],
+    phi: &mut [f64],
+    mut path: Vec<PathElement>,
+    mut depth: usize,
+    zero: f64,
+    one: f64,
+    feature: Option<usize>,
+) {
+    extend_path(&mut path, depth, zero, one, feature);
+    match node {
+        Node::Leaf { value, .. } => {
+            for i in 1..=depth {
+                let element = path[i];
+                let w = unwound_sum(&path, depth, i);
+                if let Some(f) = element.feature {
+                    phi[f] += w * (element.one - element.zero) * value;
+                }
+            }
+        }
+        Node::Split {
+            feature: split,
+            left,
+            right,
+            ..
+        } => {
+            let (hot, cold) = if node.goes_left(x[*split]) {
+                (left, right)
+            } else {
+                (right, left)
+            };
+            let cover = node.cover();
+            let (mut incoming_zero, mut incoming_one) = (1.0, 1.0);
+            if let Some(k) = (1..=depth).find(|&k| path[k].feature == Some(*split)) {
+                incoming_zero = path[k].zero;
+                incoming_one = path[k].one;
+                unwind_path(&mut path, depth, k);
+                depth -= 1;
+            }
+            tree_shap(
+                hot,
+                x,
+                phi,
+                path.clone(),
+                depth + 1,
+                hot.cover() / cover * incoming_zero,
+                incoming_one,
+                Some(*split),
+            );
+            tree_shap(
+                cold,
+                x,
+                phi,
+                path,
+                depth + 1,
+                cold.cover() / cover * incoming_zero,
+                0.0,
+                Some(*split),
+            );
+        }
+    }
+}
+
+/// Runs a `TreeEnsemble`. `total_score` is `100 * (1 - PD)`; each feature
+/// becomes a component worth its SHAP value in log-odds of repayment, so
+/// positive components helped.
+#[derive(Clone, Debug)]
+pub struct GbdtEngine {
+    pub model: TreeEnsemble,
+}
+
+impl ScoringEngine for GbdtEngine {
+    fn id(&self) -> &str {
+        "gbdt"
+    }
+
+    fn name(&self) -> &str {
+        &self.model.name
+    }
+
+    fn version(&self) -> &str {
+        &self.model.version
+    }
+
+    fn description(&self) -> &str {
+        "Gradient-boosted trees exported from XGBoost or LightGBM, explained with TreeSHAP."
+    }
+
+    fn config_schema(&self) -> Vec<ConfigField> {
+        vec![
+            ConfigField::new("model", "Tree model", FieldKind::Json)
+                .default_x(serde_json::to_value(&self.model).unwrap_or_default()),
+        ]
+    }
+
+    fn score(&self, features: &FeatureVector, config: &ScoringConfig) -> CreditScore {
+        if features.get(names::TRANSACTION_COUNT) == 0.0 {
+            return no_history_score(&config.risk_bands);
+        }
+        let explanation = self.model.explain(features);
+        let components = explanation
+            .contributions
+            .iter()
+            .map(|(feature, phi)| ScoreComponent::new(feature, -phi, 1.0))
+            .collect();
+
+        let mut ranked: Vec<&(String, f64)> = explanation
+            .contributions
+            .iter()
+            .filter(|(_, phi)| *phi != 0.0)
+            .collect();
+        ranked.sort_by(|a, b| b.1.abs().total_cmp(&a.1.abs()));
+        let factors = ranked
+            .into_iter()
+            .take(TOP_FACTORS)
+            .map(|(feature, phi)| {
+                let effect = if *phi > 0.0 { "raised" } else { "lowered" };
+                format!("{feature} {effect} risk")
+            })
+            .collect();
+
+        let pd = explanation.probability_of_default;
+        let total_score = ((1.0 - pd) * 100.0).clamp(0.0, 100.0).round() as u8;
+        let mut score = CreditScore::from_components(components, factors, &config.risk_bands);
+        score.total_score = total_score;
+        score.risk_level = classify_risk(total_score, &config.risk_bands);
+        score.probability_of_default = Some(pd);
+        score
+    }
+}
+
+#[cfg(test)]
+mod test_gbdt {
+    use super::*;
+
+    /// Two trees over three features; `success_rate` appears twice on one
+    /// path so the unwinding code gets exercised.
+    const XGB_DUMP: &str = r#"[
+      { "nodeid": 0, "split": "success_rate", "split_condition": 0.9, "yes": 1, "no": 2,
+        "missing": 1, "cover": 100,
+        "children": [
+          { "nodeid": 1, "split": "f1", "split_condition": 3, "yes": 3, "no": 4,
+            "missing": 4, "cover": 60,
+            "children": [
+              { "nodeid": 3, "leaf": 0.8, "cover": 25 },
+              { "nodeid": 4, "split": "success_rate", "split_condition": 0.5,
+                "yes": 5, "no": 6, "missing": 5, "cover": 35,
+                "children": [
+                  { "nodeid": 5, "leaf": 0.4, "cover": 15 },
+                  { "nodeid": 6, "leaf": -0.1, "cover": 20 }
+                ] }
+            ] },
+          { "nodeid": 2, "leaf": -0.6, "cover": 40 }
+        ] },
+      { "nodeid": 0, "split": "gambling_income_share", "split_condition": 0.1,
+        "yes": 1, "no": 2, "missing": 1, "cover": 100,
+        "children": [
+          { "nodeid": 1, "leaf": -0.2, "cover": 80 },
+          { "nodeid": 2, "leaf": 0.9, "cover": 20 }
+        ] }
+    ]"#;
+
+    fn model() -> TreeEnsemble {
+        let features = vec!["success_rate".to_string(), "months_employed".to_string()];
+        TreeEnsemble::from_xgboost_dump("test", XGB_DUMP, &features, 0.2).unwrap()
+    }
+
+    /// Expected output with only the features in `known` set, unknowns
+    /// averaged by cover, the conditional expectation TreeSHAP explains.
+    fn conditional(node: &Node, x: &[Option<f64>], known: &[bool]) -> f64 {
+        match node {
+            Node::Leaf { value, .. } => *value,
+            Node::Split {
+                feature,
+                left,
+                right,
+                ..
+            } => {
+                if known[*feature] {
+                    let next = if node.goes_left(x[*feature]) {
+                        left
+                    } else {
+                        right
+                    };
+                    conditional(next, x, known)
+                } else {
+                    (conditional(left, x, known) * left.cover()
+                        + conditional(right, x, known) * right.cover())
+                        / node.cover()
+                }
+            }
+        }
+    }
+
+    fn brute_force_shap(model: &TreeEnsemble, x: &[Option<f64>]) -> Vec<f64> {
+        let n = x.len();
+        let factorial = |k: usize| (1..=k).product::<usize>() as f64;
+        let value = |mask: usize| {
+            let known: Vec<bool> = (0..n).map(|i| mask & (1 << i) != 0).collect();
+            model
+                .trees
+                .iter()
+                .map(|t| conditional(t, x, &known))
+                .sum::<f64>()
+        };
+        (0..n)
+            .map(|i| {
+                (0..1usize << n)
+                    .filter(|mask| mask & (1 << i) == 0)
+                    .map(|mask| {
+                        let size = mask.count_ones() as usize;
+                        let weight = factorial(size) * factorial(n - size - 1) / factorial(n);
+                        weight * (value(mask | (1 << i)) - value(mask))
+                    })
+                    .sum()
+            })
+            .collect()
+    }
+
+    #[test]
+    fn testing_reads_xgboost_dump() {
+        let model = model();
+        assert_eq!(
+            model.features,
+            vec!["success_rate", "months_employed", "gambling_income_share"]
+        );
+        let mut fv = FeatureVector::new();
+        fv.set(names::SUCCESS_RATE, 0.7);
+        fv.set(names::MONTHS_EMPLOYED, 5.0);
+        fv.set(names::GAMBLING_INCOME_SHARE, 0.0);
+        // 0.7 < 0.9, 5 >= 3, 0.7 >= 0.5; then 0 < 0.1.
+        let margin = (0.2f64 / 0.8).ln() - 0.1 - 0.2;
+        assert!((model.margin(&fv) - margin).abs() < 1e-12);
+
+        // Missing months_employed follows `missing` to the right.
+        fv.values.remove(names::MONTHS_EMPLOYED);
+        assert!((model.margin(&fv) - margin).abs() < 1e-12);
+    }
+
+    #[test]
+    fn testing_rejects_unnamed_features_and_bad_base_scores() {
+        // `f1` needs a second entry in the feature list.
+        assert!(TreeEnsemble::from_xgboost_dump("test", XGB_DUMP, &[], 0.5).is_err());
+        let features = vec!["success_rate".to_string(), "months_employed".to_string()];
+        for base_score in [0.0, 1.0, f64::NAN] {
+            assert!(
+                TreeEnsemble::from_xgboost_dump("test", XGB_DUMP, &features, base_score).is_err(),
+                "{base_score}"
+            );
+        }
+    }
+
+    #[test]
+    fn testing_tree_shap_matches_brute_force() {
+        let model = model();
+        for (rate, months, gambling) in [
+            (0.7, Some(5.0), 0.0),
+            (0.3, Some(5.0), 0.4),
+            (0.95, Some(1.0), 0.0),
+            (0.4, None, 0.2),
+        ] {
+            let mut fv = FeatureVector::new();
+            fv.set(names::SUCCESS_RATE, rate);
+            if let Some(m) = months {
+                fv.set(names::MONTHS_EMPLOYED, m);
+            }
+            fv.set(names::GAMBLING_INCOME_SHARE, gambling);
+
+            let explanation = model.explain(&fv);
+            let expected = brute_force_shap(&model, &model.inputs(&fv));
+            for ((_, phi), want) in explanation.contributions.iter().zip(&expected) {
+                assert!((phi - want).abs() < 1e-9, "{phi} vs {want}");
+            }
+            // Local accuracy: contributions add up to the margin.
+            assert!((explanation.margin - model.margin(&fv)).abs() < 1e-9);
+        }
+    }
+
+    #[test]
+    fn testing_reads_lightgbm_dump() {
+        let dump = r#"{
+          "version": "v4",
+          "feature_names": ["success_rate", "months_employed"],
+          "tree_info": [
+            { "tree_structure": {
+                "split_feature": 0, "threshold": 0.9, "decision_type": "<=",
+                "default_left": true, "internal_count": 100,
+                "left_child": { "leaf_value": 0.5, "leaf_count": 60 },
+                "right_child": { "leaf_value": -1.0, "leaf_count": 40 } } }
+          ]
+        }"#;
+        let model = TreeEnsemble::from_lightgbm_json("lgb", dump).unwrap();
+        let mut fv = FeatureVector::new();
+        // Inclusive: exactly 0.9 goes left.
+        fv.set(names::SUCCESS_RATE, 0.9);
+        fv.set(names::TRANSACTION_COUNT, 50.0);
+        assert_eq!(model.margin(&fv), 0.5);
+
+        let score = GbdtEngine {
+            model: model.clone(),
+        }
+        .score(&fv, &ScoringConfig::default());
+        let pd = sigmoid(0.5);
+        assert_eq!(score.probability_of_default, Some(pd));
+        assert_eq!(score.total_score, ((1.0 - pd) * 100.0).round() as u8);
+        assert_eq!(score.factors, vec!["success_rate raised risk"]);
+
+        let json = serde_json::to_string(&model).unwrap();
+        assert_eq!(TreeEnsemble::from_json(&json).unwrap(), model);
+    }
+}
@@ -0,0 +1,133 @@
+use super::engine::{ConfigField, FieldKind, ScoringEngine};
+use super::features::{FeatureVector, names};
+use super::{CreditScore, ScoreComponent, ScoringConfig, ScoringModel};
+use super::{
+    cashflow_stability_score, component, component_factors, gambling_component, gambling_factor,
+    lending_factors, liquidity_factors, liquidity_rules_component, no_history_score,
+    stability_factors,
+};
+use crate::openbank::Transaction;
+
+/// Fixed-threshold weighted model: Income(0.4), Liquidity(0.3),
+/// CashFlow(0.2), Reliability(0.1), less 20 points per failed transaction.
+#[derive(Clone, Debug)]
+pub struct HeuristicEngine {
+    pub target_balance: f64,
+}
+
+impl Default for HeuristicEngine {
+    fn default() -> Self {
+        Self {
+            target_balance: 5000.,
+        }
+    }
+}
+
+impl ScoringEngine for HeuristicEngine {
+    fn id(&self) -> &str {
+        "heuristic_weighted"
+    }
+
+    fn name(&self) -> &str {
+        "Heuristic (weighted)"
+    }
+
+    fn version(&self) -> &str {
+        "1.0.0"
+    }
+
+    fn description(&self) -> &str {
+        "Weighted income, liquidity, cash flow and reliability buckets with a flat penalty per failed transaction."
+    }
+
+    fn config_schema(&self) -> Vec<ConfigField> {
+        vec![
+            ConfigField::new("target_balance", "Target balance", FieldKind::Number)
+                .default_x(self.target_balance.into()),
+        ]
+    }
+
+    fn score(&self, features: &FeatureVector, config: &ScoringConfig) -> CreditScore {
+        if features.get(names::TRANSACTION_COUNT) == 0.0 {
+            return no_history_score(&config.risk_bands);
+        }
+
+        // 1. NORMALIZATION STEP (Scoring buckets)
+        // We convert raw numbers into a 0-100 score based on "Business Logic"
+
+        // Logic: Income > 10,000 gets max points (Adjust threshold as needed)
+        let total_credit = features.get(names::TOTAL_CREDIT);
+        let income_score = (total_credit / 10_000.0 * 100.0).min(100.0);
+
+        // Logic: Avg Balance at or above the target gets max points
+        let liquidity_score = if self.target_balance > 0.0 {
+            (features.get(names::BALANCE_AVG) / self.target_balance * 100.0).min(100.0)
+        } else {
+            100.0
+        };
+
+        // Logic: Share of months in the black, less for erratic income
+        let cashflow_score = cashflow_stability_score(features);
+
+        // Logic: Pure percentage (0.95 -> 95 points)
+        let reliability_score = features.get(names::SUCCESS_RATE) * 100.0;
+
+        // 2. PENALTY STEP
+        // Deduct 20 points for every failed transaction (severe penalty)
+        let penalty = features.get(names::FAILED_WEIGHT) * 20.0;
+
+        // 3. WEIGHTED CALCULATION STEP
+        let components = vec![
+            ScoreComponent::new(component::INCOME, income_score, 0.4),
+            ScoreComponent::new(component::LIQUIDITY, liquidity_score, 0.3),
+            ScoreComponent::new(component::CASHFLOW, cashflow_score, 0.2),
+            ScoreComponent::new(component::RELIABILITY, reliability_score, 0.1),
+            ScoreComponent::new(component::PENALTIES, -penalty, 1.0),
+            gambling_component(features, config),
+            liquidity_rules_component(features, config),
+        ];
+
+        let mut factors = Vec::new();
+        // Income in at least 80% of the months we can see (and more than a
+        // couple of them) reads as a regular earner.
+        let months_spanned = features.get(names::MONTHS_SPANNED);
+        if months_spanned >= 3.0 && features.get(names::INCOME_MONTHS) >= months_spanned * 0.8 {
+            factors.push("Consistent Income".to_string());
+        }
+        if features.get(names::MONTHS_EMPLOYED) >= 3.0 {
+            factors.push("Regular Salary".to_string());
+        }
+        if features.get(names::MISSED_PAY_CYCLES) > 0.0 {
+            factors.push("Missed Pay Cycles".to_string());
+        }
+        factors.extend(component_factors(&components));
+        if features.get(names::DEBT_SERVICE_RATIO) > 0.4 {
+            factors.push("High Debt Service".to_string());
+        }
+        factors.extend(stability_factors(features));
+        factors.extend(liquidity_factors(features));
+        factors.extend(lending_factors(features));
+        factors.extend(gambling_factor(features));
+        let failed_count = features.get(names::FAILED_COUNT);
+        if failed_count >= 3.0 {
+            factors.push("Frequent Failed Transactions".to_string());
+        } else if failed_count > 0.0 {
+            factors.push("Failed Transactions".to_string());
+        }
+
+        CreditScore::from_components(components, factors, &config.risk_bands)
+    }
+}
+
+/// Scores with the heuristic model, taking its target balance from
+/// `config.model` when that selects it.
+pub fn calculate_heuristic_score(
+    transactions: &[Transaction],
+    config: &ScoringConfig,
+) -> CreditScore {
+    let engine = match config.model {
+        ScoringModel::HeuristicWeighted { target_balance } => HeuristicEngine { target_balance },
+        _ => HeuristicEngine::default(),
+    };
+    engine.evaluate(transactions, config)
+}
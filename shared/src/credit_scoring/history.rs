@@ -0,0 +1,260 @@
+use super::engine::ScoringEngine;
+use super::{CreditScore, ScoringConfig};
+use crate::openbank::Transaction;
+use crate::result::Result;
+use chrono::{DateTime, Months, NaiveDate, Utc};
+use serde::{Deserialize, Serialize};
+use std::path::Path;
+
+/// The stretch of statement a score was computed from.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct DataWindow {
+    pub from: NaiveDate,
+    pub to: NaiveDate,
+    pub transactions: usize,
+}
+
+impl DataWindow {
+    pub fn of(transactions: &[Transaction]) -> Option<Self> {
+        let dates = transactions.iter().filter_map(|t| t.date());
+        Some(Self {
+            from: dates.clone().min()?,
+            to: dates.max()?,
+            transactions: transactions.len(),
+        })
+    }
+}
+
+/// A computed score and everything needed to reproduce it.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct ScoreRecord {
+    pub id: String,
+    pub computed_at: DateTime<Utc>,
+    pub model_id: String,
+    pub model_version: String,
+    /// See `config_hash`.
+    pub config_hash: String,
+    pub window: Option<DataWindow>,
+    pub score: CreditScore,
+}
+
+/// FNV-1a of the config as JSON, so two records share a hash exactly when
+/// they were scored with the same settings.
+pub fn config_hash(config: &ScoringConfig) -> String {
+    let json = serde_json::to_string(config).unwrap_or_default();
+    let hash = json.bytes().fold(0xcbf29ce484222325u64, |h, b| {
+        (h ^ b as u64).wrapping_mul(0x100000001b3)
+    });
+    format!("{hash:016x}")
+}
+
+/// How the score moved over the last `months` months.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct Trajectory {
+    pub months: u32,
+    pub records: usize,
+    /// Latest score minus the first one in the period.
+    pub change: i32,
+    /// Least-squares slope, in points per month.
+    pub slope_per_month: f64,
+}
+
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct ScoreTrend {
+    pub latest: Option<u8>,
+    pub change_since_last: Option<i32>,
+    /// `None` with fewer than two scores in the period.
+    pub three_months: Option<Trajectory>,
+    pub six_months: Option<Trajectory>,
+    pub twelve_months: Option<Trajectory>,
+}
+
+/// Every score computed on this device, oldest first.
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+pub struct ScoreHistory {
+    pub records: Vec<ScoreRecord>,
+}
+
+impl ScoreHistory {
+    pub fn record(
+        &mut self,
+        engine: &dyn ScoringEngine,
+        config: &ScoringConfig,
+        transactions: &[Transaction],
+        score: CreditScore,
+        computed_at: DateTime<Utc>,
+    ) -> &ScoreRecord {
+        let id = self
+            .records
+            .iter()
+            .filter_map(|r| r.id.parse::<u64>().ok())
+            .max()
+            .map_or(1, |n| n + 1)
+            .to_string();
+        self.records.push(ScoreRecord {
+            id: id.clone(),
+            computed_at,
+            model_id: engine.id().to_string(),
+            model_version: engine.version().to_string(),
+            config_hash: config_hash(config),
+            window: DataWindow::of(transactions),
+            score,
+        });
+        self.records.sort_by_key(|r| r.computed_at);
+        self.get(&id).unwrap()
+    }
+
+    pub fn get(&self, id: &str) -> Option<&ScoreRecord> {
+        self.records.iter().find(|r| r.id == id)
+    }
+
+    pub fn trend(&self, as_of: DateTime<Utc>) -> ScoreTrend {
+        let mut recent = self.records.iter().rev();
+        let latest = recent.next().map(|r| r.score.total_score);
+        ScoreTrend {
+            latest,
+            change_since_last: latest
+                .zip(recent.next())
+                .map(|(l, prev)| l as i32 - prev.score.total_score as i32),
+            three_months: self.trajectory(as_of, 3),
+            six_months: self.trajectory(as_of, 6),
+            twelve_months: self.trajectory(as_of, 12),
+        }
+    }
+
+    fn trajectory(&self, as_of: DateTime<Utc>, months: u32) -> Option<Trajectory> {
+        let since = as_of.checked_sub_months(Months::new(months))?;
+        let period: Vec<&ScoreRecord> = self
+            .records
+            .iter()
+            .filter(|r| r.computed_at >= since && r.computed_at <= as_of)
+            .collect();
+        let (first, last) = (period.first()?, period.last()?);
+        if period.len() < 2 {
+            return None;
+        }
+
+        let points: Vec<(f64, f64)> = period
+            .iter()
+            .map(|r| {
+                let days = (r.computed_at - first.computed_at).num_seconds() as f64 / 86_400.0;
+                (days / 30.44, r.score.total_score as f64)
+            })
+            .collect();
+        let n = points.len() as f64;
+        let mean_x = points.iter().map(|p| p.0).sum::<f64>() / n;
+        let mean_y = points.iter().map(|p| p.1).sum::<f64>() / n;
+        let sxx: f64 = points.iter().map(|p| (p.0 - mean_x).powi(2)).sum();
+        let sxy: f64 = points.iter().map(|p| (p.0 - mean_x) * (p.1 - mean_y)).sum();
+
+        Some(Trajectory {
+            months,
+            records: period.len(),
+            change: last.score.total_score as i32 - first.score.total_score as i32,
+            slope_per_month: if sxx > 0.0 { sxy / sxx } else { 0.0 },
+        })
+    }
+
+    /// Writes a temporary file next to `path` and renames it over, so a
+    /// reader never sees a half-written history.
+    pub fn save(&self, path: impl AsRef<Path>) -> Result<()> {
+        let path = path.as_ref();
+        let temp = path.with_extension("json.tmp");
+        std::fs::write(&temp, serde_json::to_string(self)?)?;
+        Ok(std::fs::rename(temp, path)?)
+    }
+
+    pub fn load(path: impl AsRef<Path>) -> Result<Self> {
+        Ok(serde_json::from_str(&std::fs::read_to_string(path)?)?)
+    }
+
+    /// `load`, or an empty history when the file doesn't exist yet. A file
+    /// that exists but can't be read is an error, so it isn't overwritten.
+    pub fn load_or_default(path: impl AsRef<Path>) -> Result<Self> {
+        match std::fs::read_to_string(path) {
+            Ok(text) => Ok(serde_json::from_str(&text)?),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(Self::default()),
+            Err(e) => Err(e.into()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod test_history {
+    use super::*;
+    use crate::credit_scoring::HeuristicEngine;
+    use chrono::TimeZone;
+
+    fn at(year: i32, month: u32, day: u32) -> DateTime<Utc> {
+        Utc.with_ymd_and_hms(year, month, day, 12, 0, 0).unwrap()
+    }
+
+    fn score(total: u8) -> CreditScore {
+        let mut score = CreditScore::from_components(Vec::new(), Vec::new(), &[]);
+        score.total_score = total;
+        score
+    }
+
+    #[test]
+    fn testing_trends() {
+        let engine = HeuristicEngine::default();
+        let config = ScoringConfig::default();
+        let mut history = ScoreHistory::default();
+        for (when, total) in [
+            (at(2023, 1, 1), 40),
+            (at(2023, 7, 1), 50),
+            (at(2023, 10, 1), 55),
+            (at(2023, 11, 1), 60),
+            (at(2023, 12, 1), 65),
+        ] {
+            history.record(&engine, &config, &[], score(total), when);
+        }
+
+        let record = history.get("5").unwrap();
+        assert_eq!(record.model_id, engine.id());
+        assert_eq!(record.config_hash, config_hash(&config));
+        assert_eq!(record.window, None);
+
+        let trend = history.trend(at(2023, 12, 15));
+        assert_eq!(trend.latest, Some(65));
+        assert_eq!(trend.change_since_last, Some(5));
+        let three = trend.three_months.unwrap();
+        assert_eq!((three.records, three.change), (3, 10));
+        assert!((three.slope_per_month - 5.0).abs() < 0.2, "{three:?}");
+        assert_eq!(trend.six_months.unwrap().change, 15);
+        assert_eq!(trend.twelve_months.unwrap().records, 5);
+
+        // One score is no trajectory.
+        assert_eq!(history.trend(at(2024, 12, 15)).three_months, None);
+    }
+
+    #[test]
+    fn testing_config_hash() {
+        let config = ScoringConfig::default();
+        let mut other = ScoringConfig::default();
+        other.liquidity.low_balance_threshold += 1.0;
+        assert_eq!(config_hash(&config), config_hash(&ScoringConfig::default()));
+        assert_ne!(config_hash(&config), config_hash(&other));
+        assert_eq!(config_hash(&config).len(), 16);
+    }
+
+    #[test]
+    fn testing_load_or_default() {
+        let path = std::env::temp_dir().join(format!("score-history-{}.json", std::process::id()));
+        let _ = std::fs::remove_file(&path);
+        assert!(
+            ScoreHistory::load_or_default(&path)
+                .unwrap()
+                .records
+                .is_empty()
+        );
+
+        std::fs::write(&path, "{ not json").unwrap();
+        assert!(ScoreHistory::load_or_default(&path).is_err());
+
+        ScoreHistory::default().save(&path).unwrap();
+        assert!(ScoreHistory::load_or_default(&path).is_ok());
+        assert!(!path.with_extension("json.tmp").exists());
+        std::fs::remove_file(&path).unwrap();
+    }
+}
@@ -0,0 +1,225 @@
+use super::engine::ScoringEngine;
+use super::{CreditScore, ScoringConfig};
+use crate::openbank::Transaction;
+use crate::result::{Error, Result};
+use chrono::{Days, NaiveDate};
+use serde::{Deserialize, Serialize};
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct HorizonRules {
+    /// Window lengths, each ending on the last transaction.
+    pub days: Vec<u32>,
+    /// Short- and long-term scores closer than this many points count as
+    /// stable.
+    pub stable_band: f64,
+}
+
+impl Default for HorizonRules {
+    fn default() -> Self {
+        Self {
+            days: vec![30, 90, 180, 365],
+            stable_band: 5.0,
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Momentum {
+    Improving,
+    Stable,
+    Deteriorating,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct WindowScore {
+    pub days: u32,
+    pub from: NaiveDate,
+    pub transactions: usize,
+    /// Whether the history reaches back the full window.
+    pub complete: bool,
+    pub score: Option<CreditScore>,
+    /// Why the window couldn't be scored, e.g. too little history under
+    /// `InsufficientHistory::Refuse`.
+    pub error: Option<Error>,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct HorizonSummary {
+    pub as_of: NaiveDate,
+    /// Shortest window first.
+    pub windows: Vec<WindowScore>,
+    /// Shortest scored window minus the longest, in points.
+    pub momentum_points: f64,
+    pub momentum: Momentum,
+}
+
+/// Scores the last N days for every window in `config.horizons`, sorting
+/// the history once and scoring each window on a slice of it.
+pub fn score_horizons(
+    engine: &dyn ScoringEngine,
+    transactions: &[Transaction],
+    config: &ScoringConfig,
+) -> Result<HorizonSummary> {
+    let mut dated: Vec<Transaction> = transactions
+        .iter()
+        .filter(|t| t.date().is_some())
+        .cloned()
+        .collect();
+    dated.sort_by_key(|t| t.timestamp());
+    let (Some(first), Some(as_of)) = (
+        dated.first().and_then(|t| t.date()),
+        dated.last().and_then(|t| t.date()),
+    ) else {
+        return Err(Error::user("There are no dated transactions to score."));
+    };
+
+    let mut days = config.horizons.days.clone();
+    days.sort_unstable();
+    days.dedup();
+    let windows: Vec<WindowScore> = days
+        .into_iter()
+        .map(|days| {
+            // The window covers `days` calendar days up to and including
+            // `as_of`.
+            let from = as_of
+                .checked_sub_days(Days::new(days.saturating_sub(1) as u64))
+                .unwrap_or(NaiveDate::MIN);
+            let start = dated.partition_point(|t| t.date().is_some_and(|d| d < from));
+            let window = &dated[start..];
+            let (score, error) = match engine.assess(window, config) {
+                Ok(score) => (Some(score), None),
+                Err(e) => (None, Some(e)),
+            };
+            WindowScore {
+                days,
+                from,
+                transactions: window.len(),
+                complete: first <= from,
+                score,
+                error,
+            }
+        })
+        .collect();
+
+    let mut scored = windows
+        .iter()
+        .filter_map(|w| w.score.as_ref().map(|s| s.total_score as f64));
+    let momentum_points = match (scored.next(), scored.next_back()) {
+        (Some(short), Some(long)) => short - long,
+        _ => 0.0,
+    };
+    let momentum = if momentum_points > config.horizons.stable_band {
+        Momentum::Improving
+    } else if momentum_points < -config.horizons.stable_band {
+        Momentum::Deteriorating
+    } else {
+        Momentum::Stable
+    };
+
+    Ok(HorizonSummary {
+        as_of,
+        windows,
+        momentum_points,
+        momentum,
+    })
+}
+
+#[cfg(test)]
+mod test_horizons {
+    use super::*;
+    use crate::credit_scoring::{HeuristicEngine, InsufficientHistory};
+    use crate::test_fixtures;
+
+    fn tx(
+        id: usize,
+        date: NaiveDate,
+        dc: &str,
+        amount: f64,
+        balance: f64,
+        status: &str,
+    ) -> Transaction {
+        let date = date.format("%Y-%m-%d").to_string();
+        Transaction {
+            balance_after: balance,
+            status: status.into(),
+            ..test_fixtures::tx(&id.to_string(), &date, dc, amount, "Transfer")
+        }
+    }
+
+    /// A year of weekly activity: failed payments and an empty account for
+    /// the first nine months, a healthy balance for the last three.
+    fn history() -> Vec<Transaction> {
+        let start = NaiveDate::from_ymd_opt(2023, 1, 2).unwrap();
+        (0..52)
+            .flat_map(|week| {
+                let date = start + Days::new(week * 7);
+                let recovered = week >= 39;
+                let balance = if recovered { 60_000.0 } else { 200.0 };
+                let status = if recovered { "SUCCESSFUL" } else { "FAILED" };
+                [
+                    tx(
+                        2 * week as usize,
+                        date,
+                        "CREDIT",
+                        20_000.0,
+                        balance + 20_000.0,
+                        "SUCCESSFUL",
+                    ),
+                    tx(
+                        2 * week as usize + 1,
+                        date + Days::new(1),
+                        "DEBIT",
+                        20_000.0,
+                        balance,
+                        status,
+                    ),
+                ]
+            })
+            .collect()
+    }
+
+    #[test]
+    fn testing_windows_and_momentum() {
+        let summary = score_horizons(
+            &HeuristicEngine::default(),
+            &history(),
+            &ScoringConfig::default(),
+        )
+        .unwrap();
+
+        assert_eq!(
+            summary.as_of,
+            NaiveDate::from_ymd_opt(2023, 12, 26).unwrap()
+        );
+        let days: Vec<u32> = summary.windows.iter().map(|w| w.days).collect();
+        assert_eq!(days, vec![30, 90, 180, 365]);
+        assert!(
+            summary
+                .windows
+                .windows(2)
+                .all(|w| w[0].transactions <= w[1].transactions)
+        );
+        assert!(summary.windows[2].complete);
+        assert_eq!(summary.windows[3].transactions, 104);
+        assert!(!summary.windows[3].complete);
+        assert_eq!(
+            summary.momentum,
+            Momentum::Improving,
+            "{}",
+            summary.momentum_points
+        );
+    }
+
+    #[test]
+    fn testing_refused_windows_are_kept() {
+        let mut config = ScoringConfig::default();
+        config.sufficiency.on_insufficient = InsufficientHistory::Refuse;
+        let summary = score_horizons(&HeuristicEngine::default(), &history(), &config).unwrap();
+
+        // Thirty days is under the three months the rules ask for.
+        let short = &summary.windows[0];
+        assert!(short.score.is_none() && short.error.is_some());
+        assert!(summary.windows[3].score.is_some());
+    }
+}
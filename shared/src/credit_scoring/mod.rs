@@ -0,0 +1,1165 @@
+pub mod calibration;
+pub mod engine;
+pub mod features;
+pub mod gbdt;
+pub mod heuristic;
+pub mod history;
+pub mod horizons;
+pub mod onnx;
+pub mod plan;
+pub mod reasons;
+pub mod recency;
+pub mod scorecard;
+pub mod simulator;
+pub mod streaming;
+pub mod sufficiency;
+pub mod training;
+
+use crate::analysis::categorise::Rule;
+use crate::analysis::gambling::GamblingRisk;
+use crate::analysis::lending::LendingRules;
+use crate::openbank::Transaction;
+use crate::result::Result;
+use chrono::NaiveDate;
+use features::names;
+use serde::{Deserialize, Serialize};
+
+pub use calibration::{Calibration, ScaleRules, ScoreScales};
+pub use engine::{ModelRegistry, ScoringEngine};
+pub use features::{FeaturePipeline, FeatureVector, extract_features};
+pub use gbdt::{Explanation, GbdtEngine, TreeEnsemble};
+pub use heuristic::{HeuristicEngine, calculate_heuristic_score};
+pub use history::{DataWindow, ScoreHistory, ScoreRecord, ScoreTrend, Trajectory, config_hash};
+pub use horizons::{HorizonRules, HorizonSummary, Momentum, WindowScore, score_horizons};
+#[cfg(feature = "onnx")]
+pub use onnx::OnnxEngine;
+pub use onnx::{OnnxOutput, OnnxSpec};
+pub use plan::{Action, ActionProgress, ActionStatus, ImprovementPlan, PlanProgress, Target};
+pub use reasons::{Locale, Reason, ReasonCode, ReasonRules};
+pub use recency::{DecayBucket, RecencyDecay, RecencyEngine, calculate_recency_score};
+pub use scorecard::{PointsScaling, Scorecard, ScorecardEngine};
+pub use simulator::{Scenario, Simulation, simulate};
+pub use streaming::FeatureStream;
+pub use sufficiency::{InsufficientHistory, SufficiencyCheck, SufficiencyRules};
+pub use training::{LabelledHistory, TrainingOptions, TrainingReport, train, train_from_features};
+
+// Configuration for our scoring model (Public Input)
+#[derive(Clone, Serialize, Deserialize)]
+pub struct ScoringConfig {
+    pub model: ScoringModel,
+    #[serde(default = "default_risk_bands")]
+    pub risk_bands: Vec<RiskBand>,
+    /// User rules, checked before the bundled categorisation rules.
+    #[serde(default)]
+    pub category_overrides: Vec<Rule>,
+    /// Trained `NarrationClassifier` used for narrations no rule matches.
+    #[serde(default)]
+    pub narration_model_path: Option<String>,
+    #[serde(default = "default_min_model_confidence")]
+    pub min_model_confidence: f64,
+    #[serde(default)]
+    pub gambling_penalty: GamblingPenalty,
+    #[serde(default)]
+    pub liquidity: LiquidityRules,
+    /// Loan tenor and grace period for the lending features.
+    #[serde(default)]
+    pub lending: LendingRules,
+    /// Minimum history; see `SufficiencyRules`.
+    #[serde(default)]
+    pub sufficiency: SufficiencyRules,
+    /// Calibration and the extra scales the score is shown on.
+    #[serde(default)]
+    pub scales: ScaleRules,
+    /// Reference profile and language for reason codes.
+    #[serde(default)]
+    pub reasons: ReasonRules,
+    /// Windows scored by `score_horizons`.
+    #[serde(default)]
+    pub horizons: HorizonRules,
+}
+
+fn default_min_model_confidence() -> f64 {
+    0.6
+}
+
+impl Default for ScoringConfig {
+    fn default() -> Self {
+        Self {
+            model: ScoringModel::default(),
+            risk_bands: default_risk_bands(),
+            category_overrides: Vec::new(),
+            narration_model_path: None,
+            min_model_confidence: default_min_model_confidence(),
+            gambling_penalty: GamblingPenalty::default(),
+            liquidity: LiquidityRules::default(),
+            lending: LendingRules::default(),
+            sufficiency: SufficiencyRules::default(),
+            scales: ScaleRules::default(),
+            reasons: ReasonRules::default(),
+            horizons: HorizonRules::default(),
+        }
+    }
+}
+
+#[derive(Clone, Serialize, Deserialize)]
+pub enum ScoringModel {
+    HeuristicWeighted {
+        /// Average balance that earns full liquidity points.
+        target_balance: f64,
+    },
+    /// Same buckets as the heuristic model, but every transaction is
+    /// weighted by how old it is relative to `as_of`.
+    RecencyWeighted {
+        decay: RecencyDecay,
+        as_of: Option<NaiveDate>, // None => today
+    },
+    /// Logistic-regression scorecard, usually read with `Scorecard::load`.
+    Scorecard { card: Scorecard },
+    /// XGBoost or LightGBM trees, usually read with `TreeEnsemble::load`.
+    Gbdt { model: TreeEnsemble },
+    /// An ONNX file, loaded when the engine is resolved. Needs the `onnx`
+    /// feature.
+    Onnx { spec: OnnxSpec },
+    /// Any engine added to the `ModelRegistry`, looked up by id.
+    Registered { id: String },
+}
+
+impl Default for ScoringModel {
+    fn default() -> Self {
+        Self::HeuristicWeighted {
+            target_balance: 5000.,
+        }
+    }
+}
+
+impl ScoringModel {
+    /// Registry id of the engine this config selects.
+    pub fn id(&self) -> &str {
+        match self {
+            Self::HeuristicWeighted { .. } => "heuristic_weighted",
+            Self::RecencyWeighted { .. } => "recency_weighted",
+            Self::Scorecard { .. } => "scorecard",
+            Self::Gbdt { .. } => "gbdt",
+            Self::Onnx { .. } => "onnx",
+            Self::Registered { id } => id,
+        }
+    }
+}
+
+/// Points taken off the score for each `GamblingRisk` grade.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct GamblingPenalty {
+    pub low: f64,
+    pub moderate: f64,
+    pub high: f64,
+    pub severe: f64,
+}
+
+impl Default for GamblingPenalty {
+    fn default() -> Self {
+        Self {
+            low: 0.0,
+            moderate: 5.0,
+            high: 15.0,
+            severe: 30.0,
+        }
+    }
+}
+
+impl GamblingPenalty {
+    pub fn points(&self, risk: GamblingRisk) -> f64 {
+        match risk {
+            GamblingRisk::None => 0.0,
+            GamblingRisk::Low => self.low,
+            GamblingRisk::Moderate => self.moderate,
+            GamblingRisk::High => self.high,
+            GamblingRisk::Severe => self.severe,
+        }
+    }
+}
+
+/// Low-balance threshold for the liquidity features, and optional score
+/// adjustments built on them. Penalties are the points lost if every day
+/// qualified, scaled by the share of days that actually did.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct LiquidityRules {
+    pub low_balance_threshold: f64,
+    #[serde(default)]
+    pub low_balance_penalty: Option<f64>,
+    #[serde(default)]
+    pub overdraft_penalty: Option<f64>,
+    #[serde(default)]
+    pub buffer_bonus: Option<BufferBonus>,
+}
+
+impl Default for LiquidityRules {
+    fn default() -> Self {
+        Self {
+            low_balance_threshold: 1_000.0,
+            low_balance_penalty: None,
+            overdraft_penalty: None,
+            buffer_bonus: None,
+        }
+    }
+}
+
+/// Points added when the balance covers at least `min_days` of spending.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct BufferBonus {
+    pub min_days: f64,
+    pub points: f64,
+}
+
+impl LiquidityRules {
+    pub fn adjustment(&self, features: &FeatureVector) -> f64 {
+        let mut points = 0.0;
+        if let Some(penalty) = self.low_balance_penalty {
+            points -= penalty * features.get(names::LOW_BALANCE_SHARE);
+        }
+        if let Some(penalty) = self.overdraft_penalty {
+            points -= penalty * features.get(names::OVERDRAFT_SHARE);
+        }
+        if let Some(bonus) = &self.buffer_bonus
+            && features.get(names::BUFFER_DAYS) >= bonus.min_days
+        {
+            points += bonus.points;
+        }
+        points
+    }
+}
+
+/// Maps a 0-100 score to a risk label. Bands are checked from the highest
+/// `min_score` down, the first one the score reaches wins.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct RiskBand {
+    pub min_score: u8,
+    pub label: String,
+}
+
+pub fn default_risk_bands() -> Vec<RiskBand> {
+    vec![
+        RiskBand {
+            min_score: 70,
+            label: "Low".into(),
+        },
+        RiskBand {
+            min_score: 40,
+            label: "Medium".into(),
+        },
+        RiskBand {
+            min_score: 0,
+            label: "High".into(),
+        },
+    ]
+}
+
+/// Below every band, the lowest band's label applies.
+pub fn classify_risk(score: u8, bands: &[RiskBand]) -> String {
+    bands
+        .iter()
+        .filter(|b| score >= b.min_score)
+        .max_by_key(|b| b.min_score)
+        .or_else(|| bands.iter().min_by_key(|b| b.min_score))
+        .map(|b| b.label.clone())
+        .unwrap_or_default()
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct CreditScore {
+    pub total_score: u8,      // 0-100
+    pub risk_level: String,   // label from `ScoringConfig::risk_bands`
+    pub factors: Vec<String>, // ["Consistent Income", "High Gambling Activity"]
+    pub components: Vec<ScoreComponent>,
+    /// Scored despite too little history.
+    #[serde(default)]
+    pub thin_file: bool,
+    /// 0-1, below 1 for thin files.
+    #[serde(default = "full_confidence")]
+    pub confidence: f64,
+    /// Why the history was judged insufficient.
+    #[serde(default)]
+    pub data_warnings: Vec<String>,
+    /// Scorecard points, for models that produce them.
+    #[serde(default)]
+    pub points: Option<f64>,
+    /// Calibrated probability of default, for models that produce one.
+    #[serde(default)]
+    pub probability_of_default: Option<f64>,
+    /// The score on the 0-100, 300-850 and letter-grade scales.
+    #[serde(default)]
+    pub scales: Option<ScoreScales>,
+    /// Adverse-action reasons, costliest first.
+    #[serde(default)]
+    pub reasons: Vec<Reason>,
+}
+
+fn full_confidence() -> f64 {
+    1.0
+}
+
+/// One line of the score breakdown. `score` is the 0-100 sub-score before
+/// weighting; penalties carry a negative score with weight 1.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct ScoreComponent {
+    pub name: String,
+    pub score: f64,
+    pub weight: f64,
+}
+
+impl ScoreComponent {
+    pub fn new(name: &str, score: f64, weight: f64) -> Self {
+        Self {
+            name: name.to_string(),
+            score,
+            weight,
+        }
+    }
+
+    pub fn contribution(&self) -> f64 {
+        self.score * self.weight
+    }
+}
+
+impl CreditScore {
+    pub fn from_components(
+        components: Vec<ScoreComponent>,
+        factors: Vec<String>,
+        bands: &[RiskBand],
+    ) -> Self {
+        let raw: f64 = components.iter().map(ScoreComponent::contribution).sum();
+        // Ensure score stays within 0-100
+        let total_score = raw.clamp(0.0, 100.0).round() as u8;
+        Self {
+            total_score,
+            risk_level: classify_risk(total_score, bands),
+            factors,
+            components,
+            thin_file: false,
+            confidence: full_confidence(),
+            data_warnings: Vec::new(),
+            points: None,
+            probability_of_default: None,
+            scales: None,
+            reasons: Vec::new(),
+        }
+    }
+
+    pub fn component(&self, name: &str) -> Option<&ScoreComponent> {
+        self.components.iter().find(|c| c.name == name)
+    }
+}
+
+pub mod component {
+    pub const INCOME: &str = "income";
+    pub const LIQUIDITY: &str = "liquidity";
+    pub const CASHFLOW: &str = "cashflow";
+    pub const RELIABILITY: &str = "reliability";
+    pub const PENALTIES: &str = "penalties";
+    pub const GAMBLING: &str = "gambling";
+    pub const LIQUIDITY_RULES: &str = "liquidity_rules";
+}
+
+/// Human readable reasons behind a score, derived from the sub-scores.
+pub(crate) fn component_factors(components: &[ScoreComponent]) -> Vec<String> {
+    let score = |name: &str| components.iter().find(|c| c.name == name).map(|c| c.score);
+    let mut factors = Vec::new();
+
+    match score(component::INCOME) {
+        Some(s) if s >= 80.0 => factors.push("Strong Income".to_string()),
+        Some(s) if s < 30.0 => factors.push("Low Income".to_string()),
+        _ => {}
+    }
+    match score(component::LIQUIDITY) {
+        Some(s) if s >= 80.0 => factors.push("Healthy Balance".to_string()),
+        Some(s) if s < 30.0 => factors.push("Low Balance".to_string()),
+        _ => {}
+    }
+    match score(component::CASHFLOW) {
+        Some(s) if s >= 50.0 => factors.push("Positive Cash Flow".to_string()),
+        Some(_) => factors.push("Negative Cash Flow".to_string()),
+        None => {}
+    }
+
+    factors
+}
+
+/// 0-100 cash-flow sub-score: 70 points for the share of months with
+/// positive net flow, 30 for steady monthly income. A single good or bad
+/// month only moves it by its share of the history.
+pub(crate) fn cashflow_stability_score(features: &FeatureVector) -> f64 {
+    let stability = 1.0 - features.get(names::INCOME_VOLATILITY).min(1.0);
+    features.get(names::POSITIVE_MONTH_SHARE) * 70.0 + stability * 30.0
+}
+
+/// Reasons from how steady the account is over time.
+pub(crate) fn stability_factors(features: &FeatureVector) -> Vec<String> {
+    let mut factors = Vec::new();
+    if features.get(names::INCOME_VOLATILITY) > 0.5 {
+        factors.push("Volatile Income".to_string());
+    }
+    if features.get(names::MAX_DRAWDOWN_PCT) >= 0.8 {
+        factors.push("Deep Balance Drawdown".to_string());
+    }
+    factors
+}
+
+/// The configured liquidity penalties and bonus, if any.
+pub(crate) fn liquidity_rules_component(
+    features: &FeatureVector,
+    config: &ScoringConfig,
+) -> ScoreComponent {
+    ScoreComponent::new(
+        component::LIQUIDITY_RULES,
+        config.liquidity.adjustment(features),
+        1.0,
+    )
+}
+
+/// Reasons drawn from the daily balance.
+pub(crate) fn liquidity_factors(features: &FeatureVector) -> Vec<String> {
+    let mut factors = Vec::new();
+    if features.get(names::DAYS_OVERDRAWN) > 0.0 {
+        factors.push("Overdrawn Account".to_string());
+    }
+    if features.get(names::LOW_BALANCE_SHARE) >= 0.5 {
+        factors.push("Frequently Low Balance".to_string());
+    } else if features.get(names::BUFFER_DAYS) >= 30.0 {
+        factors.push("Healthy Savings Buffer".to_string());
+    }
+    factors
+}
+
+/// Reasons drawn from the borrower's existing loans.
+pub(crate) fn lending_factors(features: &FeatureVector) -> Vec<String> {
+    let mut factors = Vec::new();
+    if features.get(names::MAX_CONCURRENT_LENDERS) >= 2.0 {
+        factors.push("Stacked Loans".to_string());
+    }
+    let on_time = features.get(names::ON_TIME_REPAYMENTS);
+    let missed = features.get(names::LATE_REPAYMENTS) + features.get(names::OVERDUE_LOANS);
+    if on_time > 0.0 && missed == 0.0 {
+        factors.push("On-time Repayments".to_string());
+    } else if missed > 0.0 {
+        factors.push("Late Loan Repayments".to_string());
+    }
+    factors
+}
+
+/// The configured gambling penalty for the graded risk in `features`.
+pub(crate) fn gambling_component(
+    features: &FeatureVector,
+    config: &ScoringConfig,
+) -> ScoreComponent {
+    let risk = GamblingRisk::from_level(features.get(names::GAMBLING_RISK));
+    ScoreComponent::new(
+        component::GAMBLING,
+        -config.gambling_penalty.points(risk),
+        1.0,
+    )
+}
+
+pub(crate) fn gambling_factor(features: &FeatureVector) -> Option<String> {
+    let label = match GamblingRisk::from_level(features.get(names::GAMBLING_RISK)) {
+        GamblingRisk::None => return None,
+        GamblingRisk::Low => "Occasional Gambling",
+        GamblingRisk::Moderate => "Moderate Gambling Activity",
+        GamblingRisk::High => "High Gambling Activity",
+        GamblingRisk::Severe => "Severe Gambling Activity",
+    };
+    Some(label.to_string())
+}
+
+pub(crate) fn no_history_score(bands: &[RiskBand]) -> CreditScore {
+    CreditScore::from_components(Vec::new(), vec!["No Transaction History".into()], bands)
+}
+
+/// Scores `transactions` with whichever built-in model the config selects,
+/// behind the config's data-sufficiency gate.
+pub fn calculate_score(
+    config: &ScoringConfig,
+    transactions: &[Transaction],
+) -> Result<CreditScore> {
+    let engine = ModelRegistry::default().resolve(&config.model)?;
+    engine.assess(transactions, config)
+}
+
+#[cfg(test)]
+mod test_credit_scoring {
+    use super::*;
+    use crate::test_fixtures;
+
+    #[test]
+    fn testing_calculate_heuristic_score() {
+        // Example Usage
+        let history = vec![
+            Transaction {
+                id: "1".into(),
+                amount: 50000000.0,
+                channel: "ATM".into(),
+                authorization_token: "x".into(),
+                transaction_type: "TRF".into(),
+                debit_credit: "CREDIT".into(),
+                narration: "Salary".into(),
+                reference: "ref1".into(),
+                transaction_time: "2023-01-01".into(),
+                value_date: "2023-01-01".into(),
+                balance_after: 5000.0,
+                status: "SUCCESSFUL".into(),
+            },
+            Transaction {
+                id: "8".into(),
+                amount: 50000000.0,
+                channel: "ATM".into(),
+                authorization_token: "x".into(),
+                transaction_type: "TRF".into(),
+                debit_credit: "CREDIT".into(),
+                narration: "Salary".into(),
+                reference: "ref1".into(),
+                transaction_time: "2023-01-01".into(),
+                value_date: "2023-01-01".into(),
+                balance_after: 5000.0,
+                status: "SUCCESSFUL".into(),
+            },
+            Transaction {
+                id: "8".into(),
+                amount: 50000000.0,
+                channel: "ATM".into(),
+                authorization_token: "x".into(),
+                transaction_type: "TRF".into(),
+                debit_credit: "CREDIT".into(),
+                narration: "Salary".into(),
+                reference: "ref1".into(),
+                transaction_time: "2023-01-01".into(),
+                value_date: "2023-01-01".into(),
+                balance_after: 5000.0,
+                status: "SUCCESSFUL".into(),
+            },
+            Transaction {
+                id: "8".into(),
+                amount: 50000000.0,
+                channel: "ATM".into(),
+                authorization_token: "x".into(),
+                transaction_type: "TRF".into(),
+                debit_credit: "CREDIT".into(),
+                narration: "Salary".into(),
+                reference: "ref1".into(),
+                transaction_time: "2023-01-01".into(),
+                value_date: "2023-01-01".into(),
+                balance_after: 5000.0,
+                status: "SUCCESSFUL".into(),
+            },
+            Transaction {
+                id: "8".into(),
+                amount: 50000000.0,
+                channel: "ATM".into(),
+                authorization_token: "x".into(),
+                transaction_type: "TRF".into(),
+                debit_credit: "CREDIT".into(),
+                narration: "Salary".into(),
+                reference: "ref1".into(),
+                transaction_time: "2023-01-01".into(),
+                value_date: "2023-01-01".into(),
+                balance_after: 5000.0,
+                status: "SUCCESSFUL".into(),
+            },
+            Transaction {
+                id: "8".into(),
+                amount: 50000000.0,
+                channel: "ATM".into(),
+                authorization_token: "x".into(),
+                transaction_type: "TRF".into(),
+                debit_credit: "CREDIT".into(),
+                narration: "Salary".into(),
+                reference: "ref1".into(),
+                transaction_time: "2023-01-01".into(),
+                value_date: "2023-01-01".into(),
+                balance_after: 5000.0,
+                status: "SUCCESSFUL".into(),
+            },
+            Transaction {
+                id: "8".into(),
+                amount: 50000000.0,
+                channel: "ATM".into(),
+                authorization_token: "x".into(),
+                transaction_type: "TRF".into(),
+                debit_credit: "CREDIT".into(),
+                narration: "Salary".into(),
+                reference: "ref1".into(),
+                transaction_time: "2023-01-01".into(),
+                value_date: "2023-01-01".into(),
+                balance_after: 5000.0,
+                status: "SUCCESSFUL".into(),
+            },
+            Transaction {
+                id: "8".into(),
+                amount: 50000000.0,
+                channel: "ATM".into(),
+                authorization_token: "x".into(),
+                transaction_type: "TRF".into(),
+                debit_credit: "CREDIT".into(),
+                narration: "Salary".into(),
+                reference: "ref1".into(),
+                transaction_time: "2023-01-01".into(),
+                value_date: "2023-01-01".into(),
+                balance_after: 5000.0,
+                status: "SUCCESSFUL".into(),
+            },
+            Transaction {
+                id: "8".into(),
+                amount: 50000000.0,
+                channel: "ATM".into(),
+                authorization_token: "x".into(),
+                transaction_type: "TRF".into(),
+                debit_credit: "CREDIT".into(),
+                narration: "Salary".into(),
+                reference: "ref1".into(),
+                transaction_time: "2023-01-01".into(),
+                value_date: "2023-01-01".into(),
+                balance_after: 5000.0,
+                status: "SUCCESSFUL".into(),
+            },
+            Transaction {
+                id: "1".into(),
+                amount: 500000.0,
+                channel: "ATM".into(),
+                authorization_token: "x".into(),
+                transaction_type: "TRF".into(),
+                debit_credit: "CREDIT".into(),
+                narration: "Salary".into(),
+                reference: "ref1".into(),
+                transaction_time: "2023-01-01".into(),
+                value_date: "2023-01-01".into(),
+                balance_after: 5000.0,
+                status: "SUCCESSFUL".into(),
+            },
+            Transaction {
+                id: "1".into(),
+                amount: 500000.0,
+                channel: "ATM".into(),
+                authorization_token: "x".into(),
+                transaction_type: "TRF".into(),
+                debit_credit: "CREDIT".into(),
+                narration: "Salary".into(),
+                reference: "ref1".into(),
+                transaction_time: "2023-01-01".into(),
+                value_date: "2023-01-01".into(),
+                balance_after: 5000.0,
+                status: "SUCCESSFUL".into(),
+            },
+            Transaction {
+                id: "1".into(),
+                amount: 500000.0,
+                channel: "ATM".into(),
+                authorization_token: "x".into(),
+                transaction_type: "TRF".into(),
+                debit_credit: "CREDIT".into(),
+                narration: "Salary".into(),
+                reference: "ref1".into(),
+                transaction_time: "2023-01-01".into(),
+                value_date: "2023-01-01".into(),
+                balance_after: 5000.0,
+                status: "SUCCESSFUL".into(),
+            },
+            Transaction {
+                id: "1".into(),
+                amount: 500000.0,
+                channel: "ATM".into(),
+                authorization_token: "x".into(),
+                transaction_type: "TRF".into(),
+                debit_credit: "CREDIT".into(),
+                narration: "Salary".into(),
+                reference: "ref1".into(),
+                transaction_time: "2023-01-01".into(),
+                value_date: "2023-01-01".into(),
+                balance_after: 5000.0,
+                status: "SUCCESSFUL".into(),
+            },
+            Transaction {
+                id: "1".into(),
+                amount: 500000.0,
+                channel: "ATM".into(),
+                authorization_token: "x".into(),
+                transaction_type: "TRF".into(),
+                debit_credit: "CREDIT".into(),
+                narration: "Salary".into(),
+                reference: "ref1".into(),
+                transaction_time: "2023-01-01".into(),
+                value_date: "2023-01-01".into(),
+                balance_after: 5000.0,
+                status: "SUCCESSFUL".into(),
+            },
+            Transaction {
+                id: "1".into(),
+                amount: 500000.0,
+                channel: "ATM".into(),
+                authorization_token: "x".into(),
+                transaction_type: "TRF".into(),
+                debit_credit: "CREDIT".into(),
+                narration: "Salary".into(),
+                reference: "ref1".into(),
+                transaction_time: "2023-01-01".into(),
+                value_date: "2023-01-01".into(),
+                balance_after: 5000.0,
+                status: "SUCCESSFUL".into(),
+            },
+            Transaction {
+                id: "1".into(),
+                amount: 500000.0,
+                channel: "ATM".into(),
+                authorization_token: "x".into(),
+                transaction_type: "TRF".into(),
+                debit_credit: "CREDIT".into(),
+                narration: "Salary".into(),
+                reference: "ref1".into(),
+                transaction_time: "2023-01-01".into(),
+                value_date: "2023-01-01".into(),
+                balance_after: 5000.0,
+                status: "SUCCESSFUL".into(),
+            },
+            Transaction {
+                id: "1".into(),
+                amount: 500000.0,
+                channel: "ATM".into(),
+                authorization_token: "x".into(),
+                transaction_type: "TRF".into(),
+                debit_credit: "CREDIT".into(),
+                narration: "Salary".into(),
+                reference: "ref1".into(),
+                transaction_time: "2023-01-01".into(),
+                value_date: "2023-01-01".into(),
+                balance_after: 5000.0,
+                status: "SUCCESSFUL".into(),
+            },
+            Transaction {
+                id: "1".into(),
+                amount: 500000.0,
+                channel: "ATM".into(),
+                authorization_token: "x".into(),
+                transaction_type: "TRF".into(),
+                debit_credit: "CREDIT".into(),
+                narration: "Salary".into(),
+                reference: "ref1".into(),
+                transaction_time: "2023-01-01".into(),
+                value_date: "2023-01-01".into(),
+                balance_after: 5000.0,
+                status: "SUCCESSFUL".into(),
+            },
+            Transaction {
+                id: "1".into(),
+                amount: 500000.0,
+                channel: "ATM".into(),
+                authorization_token: "x".into(),
+                transaction_type: "TRF".into(),
+                debit_credit: "CREDIT".into(),
+                narration: "Salary".into(),
+                reference: "ref1".into(),
+                transaction_time: "2023-01-01".into(),
+                value_date: "2023-01-01".into(),
+                balance_after: 5000.0,
+                status: "SUCCESSFUL".into(),
+            },
+            Transaction {
+                id: "2".into(),
+                amount: 2000.0,
+                channel: "ATM".into(),
+                authorization_token: "x".into(),
+                transaction_type: "WDL".into(),
+                debit_credit: "DEBIT".into(),
+                narration: "Rent".into(),
+                reference: "ref2".into(),
+                transaction_time: "2023-01-02".into(),
+                value_date: "2023-01-02".into(),
+                balance_after: 3000.0,
+                status: "SUCCESSFUL".into(),
+            },
+            Transaction {
+                id: "3".into(),
+                amount: 2000.0,
+                channel: "ATM".into(),
+                authorization_token: "x".into(),
+                transaction_type: "WDL".into(),
+                debit_credit: "DEBIT".into(),
+                narration: "Shopping".into(),
+                reference: "ref2".into(),
+                transaction_time: "2023-01-02".into(),
+                value_date: "2023-01-02".into(),
+                balance_after: 3000.0,
+                status: "SUCCESSFUL".into(),
+            },
+            Transaction {
+                id: "4".into(),
+                amount: 100.0,
+                channel: "ATM".into(),
+                authorization_token: "x".into(),
+                transaction_type: "WDL".into(),
+                debit_credit: "DEBIT".into(),
+                narration: "Fail Test".into(),
+                reference: "ref3".into(),
+                transaction_time: "2023-01-03".into(),
+                value_date: "2023-01-03".into(),
+                balance_after: 3000.0,
+                status: "FAILED".into(),
+            },
+            Transaction {
+                id: "5".into(),
+                amount: 5000000.0,
+                channel: "ATM".into(),
+                authorization_token: "x".into(),
+                transaction_type: "TRF".into(),
+                debit_credit: "CREDIT".into(),
+                narration: "Salary".into(),
+                reference: "ref1".into(),
+                transaction_time: "2023-01-01".into(),
+                value_date: "2023-01-01".into(),
+                balance_after: 5000.0,
+                status: "SUCCESSFUL".into(),
+            },
+            Transaction {
+                id: "6".into(),
+                amount: 5000000.0,
+                channel: "ATM".into(),
+                authorization_token: "x".into(),
+                transaction_type: "TRF".into(),
+                debit_credit: "CREDIT".into(),
+                narration: "Salary".into(),
+                reference: "ref1".into(),
+                transaction_time: "2023-01-01".into(),
+                value_date: "2023-01-01".into(),
+                balance_after: 5000.0,
+                status: "SUCCESSFUL".into(),
+            },
+        ];
+
+        let config = ScoringConfig::default();
+        let score = calculate_heuristic_score(&history, &config);
+        assert_eq!(score.total_score, 78);
+        assert_eq!(score.risk_level, "Low");
+
+        let as_of = NaiveDate::from_ymd_opt(2023, 1, 31).unwrap();
+        let score = calculate_recency_score(&history, &RecencyDecay::default(), as_of, &config);
+        assert_eq!(score.total_score, 80);
+        assert_eq!(score.risk_level, "Low");
+    }
+
+    fn tx(time: &str, debit_credit: &str, amount: f64, status: &str) -> Transaction {
+        Transaction {
+            channel: "ATM".into(),
+            balance_after: 3000.0,
+            status: status.into(),
+            ..test_fixtures::tx("1", time, debit_credit, amount, "Test")
+        }
+    }
+
+    #[test]
+    fn testing_recency_decay_weights() {
+        let step = RecencyDecay::default();
+        assert_eq!(step.weight(0), 1.0);
+        assert_eq!(step.weight(30), 1.0);
+        assert_eq!(step.weight(31), 0.5);
+        assert_eq!(step.weight(365), 0.2);
+
+        let half_life = RecencyDecay::HalfLife {
+            half_life_days: 30.0,
+        };
+        assert_eq!(half_life.weight(0), 1.0);
+        assert!((half_life.weight(60) - 0.25).abs() < 1e-9);
+    }
+
+    #[test]
+    fn testing_recent_failures_cost_more() {
+        let as_of = NaiveDate::from_ymd_opt(2023, 6, 30).unwrap();
+        let decay = RecencyDecay::default();
+        let income = tx(
+            "2023-06-20T10:00:00.0000000",
+            "CREDIT",
+            10_000.0,
+            "SUCCESSFUL",
+        );
+
+        let recent_fail = vec![income.clone(), tx("2023-06-25", "DEBIT", 100.0, "FAILED")];
+        let old_fail = vec![income, tx("2022-06-25", "DEBIT", 100.0, "FAILED")];
+
+        let config = ScoringConfig::default();
+        assert!(
+            calculate_recency_score(&recent_fail, &decay, as_of, &config).total_score
+                < calculate_recency_score(&old_fail, &decay, as_of, &config).total_score
+        );
+    }
+
+    #[test]
+    fn testing_recency_ignores_later_activity() {
+        let as_of = NaiveDate::from_ymd_opt(2023, 6, 30).unwrap();
+        let decay = RecencyDecay::default();
+        let config = ScoringConfig::default();
+        let history = vec![tx("2023-06-20", "CREDIT", 10_000.0, "SUCCESSFUL")];
+        let mut backdated = history.clone();
+        backdated.push(tx("2023-07-02", "DEBIT", 100.0, "FAILED"));
+        backdated.push(tx("2023-08-01", "CREDIT", 90_000.0, "SUCCESSFUL"));
+
+        let engine = RecencyEngine {
+            decay: decay.clone(),
+            as_of: Some(as_of),
+        };
+        assert_eq!(
+            engine.extract(&backdated, &config),
+            engine.extract(&history, &config)
+        );
+        assert_eq!(
+            calculate_recency_score(&backdated, &decay, as_of, &config).total_score,
+            calculate_recency_score(&history, &decay, as_of, &config).total_score
+        );
+    }
+
+    #[test]
+    fn testing_heuristic_breakdown_and_factors() {
+        let history = vec![
+            tx("2023-01-05", "CREDIT", 8_000.0, "SUCCESSFUL"),
+            tx("2023-02-05", "CREDIT", 8_000.0, "SUCCESSFUL"),
+            tx("2023-03-05", "CREDIT", 8_000.0, "SUCCESSFUL"),
+            tx("2023-03-06", "DEBIT", 500.0, "FAILED"),
+        ];
+        let score = calculate_heuristic_score(&history, &ScoringConfig::default());
+
+        let income = score.component(component::INCOME).unwrap();
+        assert_eq!((income.score, income.weight), (100.0, 0.4));
+        assert_eq!(score.component(component::PENALTIES).unwrap().score, -20.0);
+        // 40 + 18 + 20 + 7.5 - 20
+        assert_eq!(score.total_score, 66);
+        assert_eq!(score.risk_level, "Medium");
+        assert!(score.factors.contains(&"Consistent Income".to_string()));
+        assert!(score.factors.contains(&"Failed Transactions".to_string()));
+
+        // A 3,000 average balance meets a 3,000 target in full.
+        let config = ScoringConfig {
+            model: ScoringModel::HeuristicWeighted {
+                target_balance: 3_000.0,
+            },
+            ..ScoringConfig::default()
+        };
+        let score = calculate_heuristic_score(&history, &config);
+        assert_eq!(score.component(component::LIQUIDITY).unwrap().score, 100.0);
+        assert_eq!(score.total_score, 78);
+    }
+
+    #[test]
+    fn testing_lending_factors() {
+        let loan = |time: &str, dc: &str, amount: f64, narration: &str| Transaction {
+            narration: narration.into(),
+            ..tx(time, dc, amount, "SUCCESSFUL")
+        };
+        let config = ScoringConfig::default();
+
+        let repaid = vec![
+            loan("2023-01-02", "CREDIT", 10_000.0, "CARBON LOAN DISB"),
+            loan("2023-01-25", "DEBIT", 11_500.0, "CARBON REPAYMENT"),
+        ];
+        let score = calculate_heuristic_score(&repaid, &config);
+        assert!(score.factors.contains(&"On-time Repayments".to_string()));
+        assert!(!score.factors.contains(&"Stacked Loans".to_string()));
+
+        let stacked = vec![
+            loan("2023-01-02", "CREDIT", 10_000.0, "CARBON LOAN DISB"),
+            loan("2023-01-04", "CREDIT", 10_000.0, "FAIRMONEY LOAN DISB"),
+            loan("2023-01-20", "DEBIT", 11_500.0, "CARBON REPAYMENT"),
+        ];
+        let score = calculate_heuristic_score(&stacked, &config);
+        assert!(score.factors.contains(&"Stacked Loans".to_string()));
+    }
+
+    #[test]
+    fn testing_lending_rules_come_from_config() {
+        let loan = |time: &str, dc: &str, amount: f64, narration: &str| Transaction {
+            narration: narration.into(),
+            ..tx(time, dc, amount, "SUCCESSFUL")
+        };
+        // Repaid 40 days after disbursement: late on a 30-day term.
+        let history = vec![
+            loan("2023-01-02", "CREDIT", 10_000.0, "CARBON LOAN DISB"),
+            loan("2023-02-11", "DEBIT", 11_500.0, "CARBON REPAYMENT"),
+        ];
+        let mut config = ScoringConfig::default();
+        let fv = FeaturePipeline::for_config(&config).extract(&history);
+        assert_eq!(fv.get(names::LATE_REPAYMENTS), 1.0);
+
+        config.lending.loan_term_days = 45;
+        let fv = FeaturePipeline::for_config(&config).extract(&history);
+        assert_eq!(fv.get(names::LATE_REPAYMENTS), 0.0);
+        assert_eq!(fv.get(names::ON_TIME_REPAYMENTS), 1.0);
+    }
+
+    #[test]
+    fn testing_one_lucky_month_does_not_max_cash_flow() {
+        let history = vec![
+            tx("2023-01-05", "CREDIT", 5_000.0, "SUCCESSFUL"),
+            tx("2023-01-20", "DEBIT", 6_000.0, "SUCCESSFUL"),
+            tx("2023-02-05", "CREDIT", 5_000.0, "SUCCESSFUL"),
+            tx("2023-02-20", "DEBIT", 6_000.0, "SUCCESSFUL"),
+            tx("2023-03-05", "CREDIT", 5_000.0, "SUCCESSFUL"),
+            tx("2023-03-20", "DEBIT", 6_000.0, "SUCCESSFUL"),
+            // Credits now beat debits overall.
+            tx("2023-04-05", "CREDIT", 50_000.0, "SUCCESSFUL"),
+        ];
+        let score = calculate_heuristic_score(&history, &ScoringConfig::default());
+        let cashflow = score.component(component::CASHFLOW).unwrap().score;
+        assert!(cashflow > 0.0 && cashflow < 50.0, "{cashflow}");
+        assert!(score.factors.contains(&"Volatile Income".to_string()));
+    }
+
+    #[test]
+    fn testing_liquidity_rules_are_optional() {
+        let overdrawn = |time: &str, dc: &str, amount: f64, balance: f64| Transaction {
+            balance_after: balance,
+            ..tx(time, dc, amount, "SUCCESSFUL")
+        };
+        let history = vec![
+            overdrawn("2023-01-01", "CREDIT", 2_000.0, 2_000.0),
+            overdrawn("2023-01-02", "DEBIT", 3_000.0, -1_000.0),
+            overdrawn("2023-01-10", "CREDIT", 3_000.0, 2_000.0),
+        ];
+        let mut config = ScoringConfig::default();
+        let plain = calculate_heuristic_score(&history, &config);
+        assert_eq!(
+            plain.component(component::LIQUIDITY_RULES).unwrap().score,
+            0.0
+        );
+        assert!(plain.factors.contains(&"Overdrawn Account".to_string()));
+
+        // Overdrawn 8 of 10 days.
+        config.liquidity.overdraft_penalty = Some(20.0);
+        let penalised = calculate_heuristic_score(&history, &config);
+        assert_eq!(
+            penalised
+                .component(component::LIQUIDITY_RULES)
+                .unwrap()
+                .score,
+            -16.0
+        );
+    }
+
+    #[test]
+    fn testing_sufficiency_gate() {
+        let history = vec![
+            tx("2023-01-05", "CREDIT", 8_000.0, "SUCCESSFUL"),
+            tx("2023-04-05", "CREDIT", 8_000.0, "SUCCESSFUL"),
+        ];
+        let mut config = ScoringConfig::default();
+
+        let score = calculate_score(&config, &history).unwrap();
+        assert!(score.thin_file);
+        assert!(score.confidence < 0.1);
+        assert!(score.factors.contains(&"Thin File".to_string()));
+        // Active days, transactions and the Feb-Mar gap; months are enough.
+        assert_eq!(score.data_warnings.len(), 3);
+
+        config.sufficiency.on_insufficient = InsufficientHistory::Refuse;
+        assert!(calculate_score(&config, &history).is_err());
+
+        config.sufficiency = SufficiencyRules {
+            min_months: 1,
+            min_active_days: 1,
+            min_transactions: 1,
+            max_gap_months: 2,
+            on_insufficient: InsufficientHistory::Refuse,
+        };
+        let score = calculate_score(&config, &history).unwrap();
+        assert!(!score.thin_file);
+        assert_eq!(score.confidence, 1.0);
+    }
+
+    #[test]
+    fn testing_unreadable_narration_model_is_a_warning() {
+        let history = vec![tx("2023-01-05", "CREDIT", 8_000.0, "SUCCESSFUL")];
+        let mut config = ScoringConfig::default();
+        config.sufficiency.min_months = 0;
+        config.sufficiency.min_active_days = 0;
+        config.sufficiency.min_transactions = 0;
+        let clean = calculate_score(&config, &history).unwrap();
+        assert!(clean.data_warnings.is_empty());
+
+        config.narration_model_path = Some("/nonexistent/narration_model.json".into());
+        let score = calculate_score(&config, &history).unwrap();
+        assert_eq!(score.total_score, clean.total_score);
+        assert_eq!(score.data_warnings.len(), 1);
+        assert!(score.data_warnings[0].contains("narration_model.json"));
+    }
+
+    #[test]
+    fn testing_gambling_penalty_is_configurable() {
+        let bet = |time: &str, amount: f64| Transaction {
+            narration: "NIP TRF TO BET9JA CASINO".into(),
+            ..tx(time, "DEBIT", amount, "SUCCESSFUL")
+        };
+        let history = vec![
+            tx("2023-01-25", "CREDIT", 50_000.0, "SUCCESSFUL"),
+            tx("2023-02-25", "CREDIT", 50_000.0, "SUCCESSFUL"),
+            bet("2023-01-10", 10_000.0),
+            bet("2023-02-10", 10_000.0),
+        ];
+        let mut config = ScoringConfig::default();
+        let score = calculate_heuristic_score(&history, &config);
+        assert!(
+            score
+                .factors
+                .contains(&"High Gambling Activity".to_string())
+        );
+        assert_eq!(score.component(component::GAMBLING).unwrap().score, -15.0);
+
+        config.gambling_penalty.high = 40.0;
+        let harsher = calculate_heuristic_score(&history, &config);
+        assert_eq!(score.total_score - harsher.total_score, 25);
+    }
+
+    #[test]
+    fn testing_classify_risk_with_custom_bands() {
+        let bands = vec![
+            RiskBand {
+                min_score: 0,
+                label: "Decline".into(),
+            },
+            RiskBand {
+                min_score: 85,
+                label: "Prime".into(),
+            },
+        ];
+        assert_eq!(classify_risk(90, &bands), "Prime");
+        assert_eq!(classify_risk(84, &bands), "Decline");
+    }
+
+    struct FlatEngine;
+
+    impl ScoringEngine for FlatEngine {
+        fn id(&self) -> &str {
+            "flat"
+        }
+
+        fn name(&self) -> &str {
+            "Flat"
+        }
+
+        fn version(&self) -> &str {
+            "0.1.0"
+        }
+
+        fn score(&self, _: &FeatureVector, config: &ScoringConfig) -> CreditScore {
+            let components = vec![ScoreComponent::new("flat", 50.0, 1.0)];
+            CreditScore::from_components(components, Vec::new(), &config.risk_bands)
+        }
+    }
+
+    #[test]
+    fn testing_registry_resolves_registered_engines() {
+        let mut registry = ModelRegistry::default();
+        registry.register(FlatEngine);
+
+        let ids: Vec<String> = registry.list().into_iter().map(|e| e.id).collect();
+        assert_eq!(ids, ["flat", "heuristic_weighted", "recency_weighted"]);
+
+        let model = ScoringModel::Registered { id: "flat".into() };
+        let engine = registry.resolve(&model).unwrap();
+        let score = engine.evaluate(&[], &ScoringConfig::default());
+        assert_eq!(score.total_score, 50);
+
+        let missing = ScoringModel::Registered { id: "nope".into() };
+        assert!(registry.resolve(&missing).is_err());
+    }
+}
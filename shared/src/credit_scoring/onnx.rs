@@ -0,0 +1,380 @@
+use serde::{Deserialize, Serialize};
+
+#[cfg(feature = "onnx")]
+pub use runtime::OnnxEngine;
+
+/// What the chosen output holds.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum OnnxOutput {
+    /// Probability of default, 0-1.
+    #[default]
+    Probability,
+    /// Log-odds of default; a sigmoid is applied.
+    Logit,
+}
+
+/// Where an ONNX model lives and how the `FeatureVector` maps onto it. The
+/// model takes one float input of shape `[1, features.len()]`.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct OnnxSpec {
+    pub path: String,
+    /// Feature names in input column order. When empty, the model's
+    /// `feature_names` metadata (comma separated) is used.
+    #[serde(default)]
+    pub features: Vec<String>,
+    /// Output tensor name; the first float output when unset.
+    #[serde(default)]
+    pub output: Option<String>,
+    #[serde(default)]
+    pub output_kind: OnnxOutput,
+    /// Column read when the output holds one value per class.
+    #[serde(default = "default_class")]
+    pub default_class: usize,
+    /// Fed in for features the vector lacks.
+    #[serde(default)]
+    pub missing_value: f64,
+}
+
+fn default_class() -> usize {
+    1
+}
+
+impl OnnxSpec {
+    pub fn new(path: &str) -> Self {
+        Self {
+            path: path.to_string(),
+            features: Vec::new(),
+            output: None,
+            output_kind: OnnxOutput::default(),
+            default_class: default_class(),
+            missing_value: 0.0,
+        }
+    }
+}
+
+#[cfg(feature = "onnx")]
+mod runtime {
+    use super::{OnnxOutput, OnnxSpec};
+    use crate::credit_scoring::engine::{ConfigField, FieldKind, ScoringEngine};
+    use crate::credit_scoring::features::{FeatureVector, names};
+    use crate::credit_scoring::{CreditScore, ScoringConfig, classify_risk, no_history_score};
+    use crate::result::{Error, Result};
+    use std::sync::Arc;
+    use tract_onnx::prelude::*;
+
+    type Plan = SimplePlan<TypedFact, Box<dyn TypedOp>, TypedModel>;
+
+    fn load_error(e: impl std::fmt::Display) -> Error {
+        Error::user("The ONNX model could not be loaded.").add_meta_x("error", &format!("{e:#}"))
+    }
+
+    /// Runs an ONNX model on the CPU with tract. The input and output
+    /// shapes are checked when loading, so scoring itself can't fail on
+    /// a schema mismatch.
+    #[derive(Clone)]
+    pub struct OnnxEngine {
+        pub spec: OnnxSpec,
+        /// Resolved input columns.
+        pub features: Vec<String>,
+        name: String,
+        version: String,
+        plan: Arc<Plan>,
+        output_index: usize,
+        /// Values in the chosen output.
+        output_width: usize,
+    }
+
+    impl OnnxEngine {
+        pub fn load(spec: &OnnxSpec) -> Result<Self> {
+            let bytes = std::fs::read(&spec.path)?;
+            Self::from_bytes(spec, &bytes)
+        }
+
+        pub fn from_bytes(spec: &OnnxSpec, mut bytes: &[u8]) -> Result<Self> {
+            let onnx = tract_onnx::onnx();
+            let proto = onnx.proto_model_for_read(&mut bytes).map_err(load_error)?;
+            let features = if spec.features.is_empty() {
+                proto
+                    .metadata_props
+                    .iter()
+                    .find(|p| p.key == "feature_names")
+                    .map(|p| p.value.split(',').map(|f| f.trim().to_string()).collect())
+                    .unwrap_or_default()
+            } else {
+                spec.features.clone()
+            };
+            if features.is_empty() {
+                return Err(Error::user(
+                    "The ONNX model doesn't say which features it takes.",
+                ));
+            }
+
+            let mut model = onnx.model_for_proto_model(&proto).map_err(load_error)?;
+            let inputs = model.input_outlets().map_err(load_error)?.len();
+            if inputs != 1 {
+                return Err(Error::user("The ONNX model must take exactly one input.")
+                    .add_meta_x("inputs", &inputs.to_string()));
+            }
+            if let Some(output) = &spec.output {
+                model.set_output_names([output]).map_err(load_error)?;
+            }
+            // Clashes with the declared input shape or type surface here.
+            model
+                .set_input_fact(0, f32::fact([1, features.len()]).into())
+                .map_err(load_error)?;
+            let model = model.into_optimized().map_err(load_error)?;
+
+            let output_index = (0..model.output_outlets().map_err(load_error)?.len())
+                .find(|&i| model.output_fact(i).is_ok_and(|f| f.datum_type.is_float()))
+                .ok_or_else(|| Error::user("The ONNX model has no float output."))?;
+            let output_width = model
+                .output_fact(output_index)
+                .map_err(load_error)?
+                .shape
+                .as_concrete()
+                .map(|s| s.iter().product::<usize>())
+                .ok_or_else(|| Error::user("The ONNX model's output shape isn't fixed."))?;
+            if output_width == 0 || (output_width > 1 && spec.default_class >= output_width) {
+                return Err(
+                    Error::user("The ONNX model's output doesn't hold the default class.")
+                        .add_meta_x("width", &output_width.to_string()),
+                );
+            }
+
+            let plan = model.into_runnable().map_err(load_error)?;
+            Ok(Self {
+                spec: spec.clone(),
+                features,
+                name: proto
+                    .graph
+                    .as_ref()
+                    .map(|g| g.name.clone())
+                    .filter(|n| !n.is_empty())
+                    .unwrap_or_else(|| "ONNX model".into()),
+                version: proto.model_version.to_string(),
+                plan: Arc::new(plan),
+                output_index,
+                output_width,
+            })
+        }
+
+        pub fn probability_of_default(&self, features: &FeatureVector) -> Result<f64> {
+            let row: Vec<f32> = self
+                .features
+                .iter()
+                .map(|f| features.try_get(f).unwrap_or(self.spec.missing_value) as f32)
+                .collect();
+            let input = Tensor::from_shape(&[1, row.len()], &row).map_err(load_error)?;
+            let outputs = self.plan.run(tvec!(input.into())).map_err(|e| {
+                Error::tech("The ONNX model failed to run.").add_meta_x("error", &format!("{e:#}"))
+            })?;
+            let output = outputs[self.output_index]
+                .cast_to::<f64>()
+                .map_err(load_error)?;
+            let values = output.as_slice::<f64>().map_err(load_error)?;
+            let raw = if self.output_width == 1 {
+                values[0]
+            } else {
+                values[self.spec.default_class]
+            };
+            Ok(match self.spec.output_kind {
+                OnnxOutput::Probability => raw.clamp(0.0, 1.0),
+                OnnxOutput::Logit => 1.0 / (1.0 + (-raw).exp()),
+            })
+        }
+    }
+
+    impl ScoringEngine for OnnxEngine {
+        fn id(&self) -> &str {
+            "onnx"
+        }
+
+        fn name(&self) -> &str {
+            &self.name
+        }
+
+        fn version(&self) -> &str {
+            &self.version
+        }
+
+        fn description(&self) -> &str {
+            "Any ONNX model taking the feature vector, run on the CPU with tract."
+        }
+
+        fn config_schema(&self) -> Vec<ConfigField> {
+            vec![
+                ConfigField::new("spec", "ONNX model", FieldKind::Json)
+                    .default_x(serde_json::to_value(&self.spec).unwrap_or_default()),
+            ]
+        }
+
+        fn score(&self, features: &FeatureVector, config: &ScoringConfig) -> CreditScore {
+            if features.get(names::TRANSACTION_COUNT) == 0.0 {
+                return no_history_score(&config.risk_bands);
+            }
+            let mut score =
+                CreditScore::from_components(Vec::new(), Vec::new(), &config.risk_bands);
+            match self.probability_of_default(features) {
+                Ok(pd) => {
+                    score.total_score = ((1.0 - pd) * 100.0).clamp(0.0, 100.0).round() as u8;
+                    score.probability_of_default = Some(pd);
+                }
+                Err(e) => score.data_warnings.push(e.to_string()),
+            }
+            score.risk_level = classify_risk(score.total_score, &config.risk_bands);
+            score
+        }
+    }
+}
+
+#[cfg(all(test, feature = "onnx"))]
+mod test_onnx {
+    use super::*;
+    use crate::credit_scoring::features::{FeatureVector, names};
+    use crate::credit_scoring::{ModelRegistry, ScoringConfig, ScoringEngine, ScoringModel};
+    use prost::Message;
+    use std::sync::Arc;
+    use tract_onnx::pb::{
+        GraphProto, ModelProto, NodeProto, OperatorSetIdProto, StringStringEntryProto, TensorProto,
+        TensorShapeProto, TypeProto, ValueInfoProto, tensor_shape_proto, type_proto,
+    };
+
+    const FLOAT: i32 = 1;
+
+    fn value_info(name: &str, dims: &[i64]) -> ValueInfoProto {
+        let dim = dims
+            .iter()
+            .map(|d| tensor_shape_proto::Dimension {
+                value: Some(tensor_shape_proto::dimension::Value::DimValue(*d)),
+                ..Default::default()
+            })
+            .collect();
+        ValueInfoProto {
+            name: name.into(),
+            r#type: Some(TypeProto {
+                value: Some(type_proto::Value::TensorType(type_proto::Tensor {
+                    elem_type: FLOAT,
+                    shape: Some(TensorShapeProto { dim }),
+                })),
+                ..Default::default()
+            }),
+            ..Default::default()
+        }
+    }
+
+    fn node(op: &str, inputs: &[&str], output: &str) -> NodeProto {
+        NodeProto {
+            op_type: op.into(),
+            input: inputs.iter().map(|i| i.to_string()).collect(),
+            output: vec![output.into()],
+            name: output.into(),
+            ..Default::default()
+        }
+    }
+
+    /// `sigmoid(x . weights + bias)` over `[1, 2]`, columns named in metadata.
+    fn logistic_model(weights: [f32; 2], bias: f32) -> Vec<u8> {
+        let graph = GraphProto {
+            name: "pd-model".into(),
+            node: vec![
+                node("MatMul", &["x", "w"], "xw"),
+                node("Add", &["xw", "b"], "z"),
+                node("Sigmoid", &["z"], "pd"),
+            ],
+            initializer: vec![
+                TensorProto {
+                    name: "w".into(),
+                    dims: vec![2, 1],
+                    data_type: FLOAT,
+                    float_data: weights.to_vec(),
+                    ..Default::default()
+                },
+                TensorProto {
+                    name: "b".into(),
+                    dims: vec![1],
+                    data_type: FLOAT,
+                    float_data: vec![bias],
+                    ..Default::default()
+                },
+            ],
+            input: vec![value_info("x", &[1, 2])],
+            output: vec![value_info("pd", &[1, 1])],
+            ..Default::default()
+        };
+        ModelProto {
+            ir_version: 7,
+            model_version: 3,
+            opset_import: vec![OperatorSetIdProto {
+                domain: String::new(),
+                version: 13,
+            }],
+            graph: Some(graph),
+            metadata_props: vec![StringStringEntryProto {
+                key: "feature_names".into(),
+                value: "success_rate, gambling_income_share".into(),
+            }],
+            ..Default::default()
+        }
+        .encode_to_vec()
+    }
+
+    #[test]
+    fn testing_runs_onnx_model() {
+        let bytes = logistic_model([-4.0, 6.0], 1.0);
+        let engine = OnnxEngine::from_bytes(&OnnxSpec::new("memory"), &bytes).unwrap();
+        assert_eq!(
+            engine.features,
+            vec!["success_rate", "gambling_income_share"]
+        );
+        assert_eq!(engine.name(), "pd-model");
+
+        let mut fv = FeatureVector::new();
+        fv.set(names::SUCCESS_RATE, 0.9);
+        fv.set(names::TRANSACTION_COUNT, 50.0);
+        // gambling_income_share missing, fed as 0.
+        let pd = engine.probability_of_default(&fv).unwrap();
+        let expected = 1.0 / (1.0 + (-(1.0 - 4.0 * 0.9f64)).exp());
+        assert!((pd - expected).abs() < 1e-6);
+
+        let score = engine.score(&fv, &ScoringConfig::default());
+        assert_eq!(score.total_score, ((1.0 - pd) * 100.0).round() as u8);
+        assert_eq!(score.probability_of_default, Some(pd));
+    }
+
+    #[test]
+    fn testing_rejects_schema_mismatch() {
+        let bytes = logistic_model([1.0, 1.0], 0.0);
+        // Three columns into a model declared as [1, 2].
+        let mut spec = OnnxSpec::new("memory");
+        spec.features = vec!["a".into(), "b".into(), "c".into()];
+        assert!(OnnxEngine::from_bytes(&spec, &bytes).is_err());
+
+        let mut spec = OnnxSpec::new("memory");
+        spec.output = Some("nope".into());
+        assert!(OnnxEngine::from_bytes(&spec, &bytes).is_err());
+
+        assert!(OnnxEngine::from_bytes(&OnnxSpec::new("memory"), b"not onnx").is_err());
+    }
+
+    #[test]
+    fn testing_registry_loads_each_model_once() {
+        let path = std::env::temp_dir().join(format!("onnx-cache-{}.onnx", std::process::id()));
+        std::fs::write(&path, logistic_model([-4.0, 6.0], 1.0)).unwrap();
+        let model = ScoringModel::Onnx {
+            spec: OnnxSpec::new(&path.to_string_lossy()),
+        };
+        let registry = ModelRegistry::default();
+        let first = registry.resolve(&model).unwrap();
+        let again = registry.clone().resolve(&model).unwrap();
+        assert!(Arc::ptr_eq(&first, &again));
+
+        let other = ScoringModel::Onnx {
+            spec: OnnxSpec {
+                missing_value: 0.5,
+                ..OnnxSpec::new(&path.to_string_lossy())
+            },
+        };
+        assert!(!Arc::ptr_eq(&first, &registry.resolve(&other).unwrap()));
+        std::fs::remove_file(&path).unwrap();
+    }
+}
@@ -0,0 +1,619 @@
+use super::engine::ScoringEngine;
+use super::features::names;
+use super::reasons::{Locale, ReasonCode, precise};
+use super::simulator::{Scenario, apply_scenarios};
+use super::{CreditScore, ScoringConfig};
+use crate::analysis::cashflow::analyse_cash_flow;
+use crate::analysis::categorise::Categoriser;
+use crate::analysis::gambling::detect_gambling;
+use crate::analysis::lending::{LoanStatus, detect_lending};
+use crate::openbank::Transaction;
+use crate::result::{Error, Result};
+use chrono::{Datelike, NaiveDate};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeSet;
+use std::path::Path;
+
+/// Something concrete the customer can do, checked against the
+/// transactions that come in after the plan is made.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum Target {
+    KeepMinBalance {
+        amount: f64,
+        days: u32,
+    },
+    SaveMonthly {
+        amount: f64,
+        months: u32,
+    },
+    StopGambling {
+        days: u32,
+    },
+    NoFailedTransactions {
+        days: u32,
+    },
+    RepayOnTime {
+        days: u32,
+    },
+    SingleLender {
+        days: u32,
+    },
+    /// Debt service ratio at most `max_ratio`.
+    CapDebtService {
+        max_ratio: f64,
+        days: u32,
+    },
+    GrowIncome {
+        monthly: f64,
+        months: u32,
+    },
+    RegularIncome {
+        months: u32,
+    },
+    BuildHistory {
+        months: u32,
+    },
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ActionStatus {
+    InProgress,
+    Achieved,
+    /// The target was missed; the action has to start over.
+    Broken,
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct Action {
+    pub code: ReasonCode,
+    pub text: String,
+    pub target: Target,
+    /// Score points expected once the target is met.
+    pub estimated_impact: f64,
+    /// Days before the statements can show the change.
+    pub days_to_effect: u32,
+}
+
+/// Actions for the reasons a score fell short, largest impact first.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct ImprovementPlan {
+    /// Last day of the history the plan was made from; progress counts
+    /// from the day after.
+    pub created: NaiveDate,
+    pub base_score: u8,
+    pub actions: Vec<Action>,
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct ActionProgress {
+    pub code: ReasonCode,
+    pub text: String,
+    /// 0 to 1.
+    pub progress: f64,
+    pub status: ActionStatus,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct PlanProgress {
+    pub as_of: NaiveDate,
+    pub days_elapsed: u32,
+    pub score: CreditScore,
+    pub score_change: i32,
+    pub actions: Vec<ActionProgress>,
+}
+
+fn round_up(amount: f64, step: f64) -> f64 {
+    ((amount / step).ceil() * step).max(step)
+}
+
+/// `NGN 20,000`.
+fn naira(amount: f64) -> String {
+    let digits = amount.round().abs().to_string();
+    let mut grouped = String::new();
+    for (i, c) in digits.chars().enumerate() {
+        if i > 0 && (digits.len() - i).is_multiple_of(3) {
+            grouped.push(',');
+        }
+        grouped.push(c);
+    }
+    format!("NGN {grouped}")
+}
+
+impl Target {
+    /// What to aim for given why the score fell short, sized from the
+    /// reference profile and the customer's own history.
+    pub fn for_reason(
+        code: ReasonCode,
+        transactions: &[Transaction],
+        config: &ScoringConfig,
+    ) -> Self {
+        let reference = |name: &str| config.reasons.reference.get(name).copied();
+        match code {
+            ReasonCode::FailedTransactions => Self::NoFailedTransactions { days: 90 },
+            ReasonCode::LowIncome => Self::GrowIncome {
+                monthly: round_up(reference(names::INCOME_MEAN).unwrap_or(50_000.0), 1_000.0),
+                months: 3,
+            },
+            ReasonCode::IrregularIncome => Self::RegularIncome { months: 6 },
+            ReasonCode::LowBalance | ReasonCode::Overdraft => Self::KeepMinBalance {
+                amount: round_up(
+                    reference(names::BALANCE_MIN)
+                        .unwrap_or_default()
+                        .max(config.liquidity.low_balance_threshold),
+                    1_000.0,
+                ),
+                days: 60,
+            },
+            ReasonCode::NegativeCashFlow => {
+                let deficits: Vec<f64> = analyse_cash_flow(transactions)
+                    .months
+                    .iter()
+                    .filter(|m| m.net < 0.0)
+                    .map(|m| -m.net)
+                    .collect();
+                let mean = deficits.iter().sum::<f64>() / deficits.len().max(1) as f64;
+                Self::SaveMonthly {
+                    amount: round_up(mean, 1_000.0),
+                    months: 3,
+                }
+            }
+            ReasonCode::Gambling => Self::StopGambling { days: 90 },
+            ReasonCode::LoanStacking => Self::SingleLender { days: 90 },
+            ReasonCode::LateLoanRepayment => Self::RepayOnTime { days: 180 },
+            ReasonCode::HighDebtService => Self::CapDebtService {
+                max_ratio: reference(names::DEBT_SERVICE_RATIO).unwrap_or(0.3),
+                days: 90,
+            },
+            ReasonCode::ShortHistory => {
+                let dates = transactions.iter().filter_map(|t| t.date());
+                let span = match (dates.clone().min(), dates.max()) {
+                    (Some(first), Some(last)) => (last - first).num_days() as f64,
+                    _ => 0.0,
+                };
+                let wanted = reference(names::HISTORY_DAYS).unwrap_or(365.0);
+                Self::BuildHistory {
+                    months: ((wanted - span) / 30.0).ceil().max(1.0) as u32,
+                }
+            }
+        }
+    }
+
+    /// How long the target takes to meet.
+    pub fn days(&self) -> u32 {
+        match self {
+            Self::KeepMinBalance { days, .. }
+            | Self::StopGambling { days }
+            | Self::NoFailedTransactions { days }
+            | Self::RepayOnTime { days }
+            | Self::SingleLender { days }
+            | Self::CapDebtService { days, .. } => *days,
+            Self::SaveMonthly { months, .. }
+            | Self::GrowIncome { months, .. }
+            | Self::RegularIncome { months }
+            | Self::BuildHistory { months } => months * 30,
+        }
+    }
+
+    /// The same change replayed on the history, where the simulator
+    /// supports it.
+    pub fn scenario(&self) -> Option<Scenario> {
+        match self {
+            Self::KeepMinBalance { amount, days } => Some(Scenario::KeepMinBalance {
+                balance: *amount,
+                months: days.div_ceil(30),
+            }),
+            Self::SaveMonthly { amount, .. } => Some(Scenario::MonthlySavings { amount: *amount }),
+            Self::StopGambling { .. } => Some(Scenario::RemoveGambling),
+            Self::NoFailedTransactions { .. } => Some(Scenario::AvoidFailedTransactions),
+            _ => None,
+        }
+    }
+
+    pub fn text(&self, locale: Locale) -> String {
+        match (locale, self) {
+            (Locale::En, Self::KeepMinBalance { amount, days }) => format!(
+                "Keep at least {} in the account for {days} days",
+                naira(*amount)
+            ),
+            (Locale::En, Self::SaveMonthly { amount, months }) => {
+                format!("Put aside {} a month for {months} months", naira(*amount))
+            }
+            (Locale::En, Self::StopGambling { days }) => format!("Stop betting for {days} days"),
+            (Locale::En, Self::NoFailedTransactions { days }) => {
+                format!("Check the balance before paying so no payment fails for {days} days")
+            }
+            (Locale::En, Self::RepayOnTime { days }) => {
+                format!("Repay every loan on time for {days} days")
+            }
+            (Locale::En, Self::SingleLender { days }) => {
+                format!("Borrow from one lender at most for {days} days")
+            }
+            (Locale::En, Self::CapDebtService { max_ratio, days }) => format!(
+                "Keep loan repayments and bills under {:.0}% of income for {days} days",
+                max_ratio * 100.0
+            ),
+            (Locale::En, Self::GrowIncome { monthly, months }) => format!(
+                "Receive at least {} a month into this account for {months} months",
+                naira(*monthly)
+            ),
+            (Locale::En, Self::RegularIncome { months }) => {
+                format!("Get paid into this account every month for {months} months")
+            }
+            (Locale::En, Self::BuildHistory { months }) => {
+                format!("Keep using this account for {months} more months")
+            }
+            (Locale::Fr, Self::KeepMinBalance { amount, days }) => format!(
+                "Garder au moins {} sur le compte pendant {days} jours",
+                naira(*amount)
+            ),
+            (Locale::Fr, Self::SaveMonthly { amount, months }) => format!(
+                "Mettre de côté {} par mois pendant {months} mois",
+                naira(*amount)
+            ),
+            (Locale::Fr, Self::StopGambling { days }) => {
+                format!("Arrêter les paris pendant {days} jours")
+            }
+            (Locale::Fr, Self::NoFailedTransactions { days }) => format!(
+                "Vérifier le solde avant de payer pour qu'aucun paiement n'échoue pendant {days} jours"
+            ),
+            (Locale::Fr, Self::RepayOnTime { days }) => {
+                format!("Rembourser chaque prêt à temps pendant {days} jours")
+            }
+            (Locale::Fr, Self::SingleLender { days }) => {
+                format!("N'emprunter qu'à un seul prêteur pendant {days} jours")
+            }
+            (Locale::Fr, Self::CapDebtService { max_ratio, days }) => format!(
+                "Garder remboursements et factures sous {:.0} % des revenus pendant {days} jours",
+                max_ratio * 100.0
+            ),
+            (Locale::Fr, Self::GrowIncome { monthly, months }) => format!(
+                "Recevoir au moins {} par mois sur ce compte pendant {months} mois",
+                naira(*monthly)
+            ),
+            (Locale::Fr, Self::RegularIncome { months }) => {
+                format!("Recevoir des revenus sur ce compte chaque mois pendant {months} mois")
+            }
+            (Locale::Fr, Self::BuildHistory { months }) => {
+                format!("Continuer à utiliser ce compte pendant encore {months} mois")
+            }
+        }
+    }
+
+    /// Progress from the transactions after `created`.
+    fn progress(
+        &self,
+        engine: &dyn ScoringEngine,
+        transactions: &[Transaction],
+        created: NaiveDate,
+        elapsed: u32,
+        config: &ScoringConfig,
+        categoriser: &Categoriser,
+    ) -> (f64, ActionStatus) {
+        let since: Vec<Transaction> = transactions
+            .iter()
+            .filter(|t| t.date().is_some_and(|d| d > created))
+            .cloned()
+            .collect();
+        let successful = || since.iter().filter(|t| !t.is_failed());
+
+        // Conditions held for a number of days.
+        let held = |days: u32, broken: bool| {
+            let progress = (elapsed as f64 / days.max(1) as f64).min(1.0);
+            let status = match (broken, progress >= 1.0) {
+                (true, _) => ActionStatus::Broken,
+                (false, true) => ActionStatus::Achieved,
+                (false, false) => ActionStatus::InProgress,
+            };
+            (progress, status)
+        };
+        // Amounts built up over time.
+        let reached = |value: f64, goal: f64| {
+            let progress = (value / goal).clamp(0.0, 1.0);
+            let status = if progress >= 1.0 {
+                ActionStatus::Achieved
+            } else {
+                ActionStatus::InProgress
+            };
+            (progress, status)
+        };
+
+        match self {
+            Self::KeepMinBalance { amount, days } => {
+                held(*days, successful().any(|t| t.balance_after < *amount))
+            }
+            Self::StopGambling { days } => held(
+                *days,
+                !detect_gambling(&since, categoriser)
+                    .transaction_ids
+                    .is_empty(),
+            ),
+            Self::NoFailedTransactions { days } => held(*days, since.iter().any(|t| t.is_failed())),
+            Self::RepayOnTime { days } => {
+                let lending = detect_lending(transactions, categoriser, &config.lending);
+                let late = lending.loans.iter().any(|l| {
+                    l.due_date > created
+                        && matches!(l.status, LoanStatus::RepaidLate | LoanStatus::Overdue)
+                });
+                held(*days, late)
+            }
+            Self::SingleLender { days } => {
+                let lending = detect_lending(&since, categoriser, &config.lending);
+                let lenders: BTreeSet<&String> = lending.loans.iter().map(|l| &l.lender).collect();
+                held(*days, lenders.len() > 1)
+            }
+            Self::CapDebtService { max_ratio, days } => {
+                let ratio = engine
+                    .extract(&since, config)
+                    .try_get(names::DEBT_SERVICE_RATIO)
+                    .unwrap_or_default();
+                held(*days, ratio > *max_ratio)
+            }
+            Self::SaveMonthly { amount, months } => {
+                let net: f64 = successful()
+                    .map(|t| if t.is_credit() { t.amount } else { -t.amount })
+                    .sum();
+                reached(net, amount * *months as f64)
+            }
+            Self::GrowIncome { monthly, months } => {
+                let income: f64 = successful()
+                    .filter(|t| t.is_credit())
+                    .map(|t| t.amount)
+                    .sum();
+                reached(income, monthly * *months as f64)
+            }
+            Self::RegularIncome { months } => {
+                let paid: BTreeSet<(i32, u32)> = successful()
+                    .filter(|t| t.is_credit())
+                    .filter_map(|t| t.date())
+                    .map(|d| (d.year(), d.month()))
+                    .collect();
+                reached(paid.len() as f64, *months as f64)
+            }
+            Self::BuildHistory { months } => held(months * 30, false),
+        }
+    }
+}
+
+impl ImprovementPlan {
+    /// One action per reason behind the score. The impact is the simulated
+    /// change where the simulator can replay the target on the history,
+    /// otherwise the reason's cost.
+    pub fn generate(
+        engine: &dyn ScoringEngine,
+        transactions: &[Transaction],
+        config: &ScoringConfig,
+    ) -> Result<Self> {
+        let created = transactions
+            .iter()
+            .filter_map(|t| t.date())
+            .max()
+            .ok_or_else(|| Error::user("Can't make a plan without any dated transactions."))?;
+        let base = engine.assess(transactions, config)?;
+
+        let mut actions: Vec<Action> = Vec::new();
+        for reason in &base.reasons {
+            let target = Target::for_reason(reason.code, transactions, config);
+            if actions.iter().any(|a| a.target == target) {
+                continue;
+            }
+            let estimated_impact = match target.scenario() {
+                Some(scenario) => {
+                    let changed = apply_scenarios(transactions, &[scenario], config);
+                    precise(&engine.assess(&changed, config)?) - precise(&base)
+                }
+                None => reason.cost,
+            };
+            if estimated_impact <= 0.0 {
+                continue;
+            }
+            actions.push(Action {
+                code: reason.code,
+                text: target.text(config.reasons.locale),
+                days_to_effect: target.days(),
+                target,
+                estimated_impact,
+            });
+        }
+        actions.sort_by(|a, b| b.estimated_impact.total_cmp(&a.estimated_impact));
+
+        Ok(Self {
+            created,
+            base_score: base.total_score,
+            actions,
+        })
+    }
+
+    /// Where each action stands given the history so far, which should
+    /// include the transactions since the plan was made.
+    pub fn progress(
+        &self,
+        engine: &dyn ScoringEngine,
+        transactions: &[Transaction],
+        config: &ScoringConfig,
+    ) -> Result<PlanProgress> {
+        let as_of = transactions
+            .iter()
+            .filter_map(|t| t.date())
+            .max()
+            .unwrap_or(self.created)
+            .max(self.created);
+        let days_elapsed = (as_of - self.created).num_days() as u32;
+        let score = engine.assess(transactions, config)?;
+        let categoriser = Categoriser::for_config(config);
+
+        let actions = self
+            .actions
+            .iter()
+            .map(|action| {
+                let (progress, status) = action.target.progress(
+                    engine,
+                    transactions,
+                    self.created,
+                    days_elapsed,
+                    config,
+                    &categoriser,
+                );
+                ActionProgress {
+                    code: action.code,
+                    text: action.text.clone(),
+                    progress,
+                    status,
+                }
+            })
+            .collect();
+
+        Ok(PlanProgress {
+            as_of,
+            days_elapsed,
+            score_change: score.total_score as i32 - self.base_score as i32,
+            score,
+            actions,
+        })
+    }
+
+    pub fn save(&self, path: impl AsRef<Path>) -> Result<()> {
+        Ok(std::fs::write(path, serde_json::to_string(self)?)?)
+    }
+
+    /// The saved plan, or `None` when no plan has been saved there yet.
+    pub fn load(path: impl AsRef<Path>) -> Result<Option<Self>> {
+        match std::fs::read_to_string(path) {
+            Ok(text) => Ok(Some(serde_json::from_str(&text)?)),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(None),
+            Err(e) => Err(e.into()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod test_plan {
+    use super::*;
+    use crate::credit_scoring::HeuristicEngine;
+    use crate::test_fixtures;
+
+    fn tx(id: &str, date: &str, dc: &str, amount: f64, balance: f64, status: &str) -> Transaction {
+        Transaction {
+            balance_after: balance,
+            status: status.into(),
+            ..test_fixtures::tx(id, date, dc, amount, "Transfer")
+        }
+    }
+
+    fn history() -> Vec<Transaction> {
+        vec![
+            tx("t1", "2023-01-02", "CREDIT", 8_000.0, 8_000.0, "SUCCESSFUL"),
+            tx("t2", "2023-01-10", "DEBIT", 7_500.0, 500.0, "SUCCESSFUL"),
+            tx("t3", "2023-01-12", "DEBIT", 2_000.0, 500.0, "FAILED"),
+            tx("t4", "2023-02-03", "CREDIT", 6_000.0, 6_500.0, "SUCCESSFUL"),
+            tx("t5", "2023-02-20", "DEBIT", 6_400.0, 100.0, "SUCCESSFUL"),
+            tx("t6", "2023-02-21", "DEBIT", 900.0, 100.0, "FAILED"),
+        ]
+    }
+
+    #[test]
+    fn testing_plan_is_prioritised() {
+        let config = ScoringConfig::default();
+        let plan =
+            ImprovementPlan::generate(&HeuristicEngine::default(), &history(), &config).unwrap();
+
+        assert_eq!(plan.created, NaiveDate::from_ymd_opt(2023, 2, 21).unwrap());
+        assert!(!plan.actions.is_empty());
+        assert!(
+            plan.actions
+                .windows(2)
+                .all(|w| w[0].estimated_impact >= w[1].estimated_impact)
+        );
+        let failed = plan
+            .actions
+            .iter()
+            .find(|a| a.code == ReasonCode::FailedTransactions)
+            .unwrap();
+        assert_eq!(failed.target, Target::NoFailedTransactions { days: 90 });
+        assert_eq!(failed.days_to_effect, 90);
+        let balance = plan
+            .actions
+            .iter()
+            .find(|a| a.code == ReasonCode::LowBalance)
+            .unwrap();
+        assert_eq!(
+            balance.text,
+            "Keep at least NGN 5,000 in the account for 60 days"
+        );
+        assert_eq!(naira(1_234_567.4), "NGN 1,234,567");
+    }
+
+    #[test]
+    fn testing_progress_over_time() {
+        let config = ScoringConfig::default();
+        let engine = HeuristicEngine::default();
+        let plan = ImprovementPlan {
+            created: NaiveDate::from_ymd_opt(2023, 2, 21).unwrap(),
+            base_score: 40,
+            actions: [
+                Target::KeepMinBalance {
+                    amount: 5_000.0,
+                    days: 60,
+                },
+                Target::NoFailedTransactions { days: 30 },
+                Target::SaveMonthly {
+                    amount: 2_000.0,
+                    months: 2,
+                },
+            ]
+            .into_iter()
+            .map(|target| Action {
+                code: ReasonCode::LowBalance,
+                text: target.text(Locale::En),
+                days_to_effect: target.days(),
+                target,
+                estimated_impact: 5.0,
+            })
+            .collect(),
+        };
+
+        let mut txs = history();
+        txs.extend([
+            tx("t7", "2023-03-01", "CREDIT", 9_000.0, 9_100.0, "SUCCESSFUL"),
+            tx("t8", "2023-03-20", "DEBIT", 3_000.0, 6_100.0, "SUCCESSFUL"),
+            tx("t9", "2023-03-28", "DEBIT", 7_000.0, 6_100.0, "FAILED"),
+        ]);
+        let progress = plan.progress(&engine, &txs, &config).unwrap();
+
+        assert_eq!(progress.days_elapsed, 35);
+        let [balance, failed, saved] = &progress.actions[..] else {
+            panic!("expected three actions");
+        };
+        assert_eq!(balance.status, ActionStatus::InProgress);
+        assert!((balance.progress - 35.0 / 60.0).abs() < 1e-9);
+        assert_eq!(failed.status, ActionStatus::Broken);
+        // 6,000 saved against 4,000.
+        assert_eq!(saved.status, ActionStatus::Achieved);
+        assert_eq!(
+            progress.score_change,
+            progress.score.total_score as i32 - 40
+        );
+    }
+
+    #[test]
+    fn testing_plan_is_saved() {
+        let path = std::env::temp_dir().join(format!("plan-{}.json", std::process::id()));
+        assert!(ImprovementPlan::load(&path).unwrap().is_none());
+
+        let config = ScoringConfig::default();
+        let plan =
+            ImprovementPlan::generate(&HeuristicEngine::default(), &history(), &config).unwrap();
+        plan.save(&path).unwrap();
+        let saved = ImprovementPlan::load(&path).unwrap().unwrap();
+        assert_eq!(saved.created, plan.created);
+        let targets = |p: &ImprovementPlan| -> Vec<Target> {
+            p.actions.iter().map(|a| a.target.clone()).collect()
+        };
+        assert_eq!(targets(&saved), targets(&plan));
+
+        std::fs::write(&path, "{").unwrap();
+        assert!(ImprovementPlan::load(&path).is_err());
+        std::fs::remove_file(&path).unwrap();
+    }
+}
@@ -0,0 +1,430 @@
+use super::features::{FeatureVector, names};
+use super::{CreditScore, ScoringConfig};
+use crate::analysis::cashflow::analyse_cash_flow;
+use crate::analysis::categorise::Categoriser;
+use crate::analysis::gambling::detect_gambling;
+use crate::analysis::income::detect_income;
+use crate::analysis::lending::{LoanStatus, detect_lending};
+use crate::analysis::obligations::detect_obligations;
+use crate::openbank::Transaction;
+use chrono::Datelike;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+/// Stable identifiers for why a score fell short. Serialised as
+/// `FAILED_TRANSACTIONS` etc.; never renumber or rename a variant.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum ReasonCode {
+    FailedTransactions,
+    LowIncome,
+    IrregularIncome,
+    LowBalance,
+    Overdraft,
+    NegativeCashFlow,
+    Gambling,
+    LoanStacking,
+    LateLoanRepayment,
+    HighDebtService,
+    ShortHistory,
+}
+
+impl ReasonCode {
+    pub const ALL: [ReasonCode; 11] = [
+        ReasonCode::FailedTransactions,
+        ReasonCode::LowIncome,
+        ReasonCode::IrregularIncome,
+        ReasonCode::LowBalance,
+        ReasonCode::Overdraft,
+        ReasonCode::NegativeCashFlow,
+        ReasonCode::Gambling,
+        ReasonCode::LoanStacking,
+        ReasonCode::LateLoanRepayment,
+        ReasonCode::HighDebtService,
+        ReasonCode::ShortHistory,
+    ];
+
+    /// Features the reason is about, moved together to the reference.
+    pub fn features(self) -> &'static [&'static str] {
+        match self {
+            Self::FailedTransactions => &[
+                names::FAILED_COUNT,
+                names::FAILED_WEIGHT,
+                names::SUCCESS_RATE,
+            ],
+            Self::LowIncome => &[
+                names::TOTAL_CREDIT,
+                names::INCOME_MEAN,
+                names::INCOME_MEDIAN,
+                names::PRIMARY_INCOME_AMOUNT,
+                names::SALARY_DETECTED,
+            ],
+            Self::IrregularIncome => &[
+                names::INCOME_VOLATILITY,
+                names::MONTHS_EMPLOYED,
+                names::MISSED_PAY_CYCLES,
+                names::LATE_PAY_CYCLES,
+                names::RECURRING_INCOME_SHARE,
+            ],
+            Self::LowBalance => &[
+                names::BALANCE_AVG,
+                names::BALANCE_MIN,
+                names::DAYS_BELOW_THRESHOLD,
+                names::LOW_BALANCE_SHARE,
+                names::MONTHLY_MIN_BALANCE,
+                names::BUFFER_DAYS,
+            ],
+            Self::Overdraft => &[names::DAYS_OVERDRAWN, names::OVERDRAFT_SHARE],
+            Self::NegativeCashFlow => &[
+                names::POSITIVE_MONTH_SHARE,
+                names::WORST_MONTH_NET,
+                names::EXPENSE_VOLATILITY,
+                names::MAX_DRAWDOWN,
+                names::MAX_DRAWDOWN_PCT,
+            ],
+            Self::Gambling => &[
+                names::GAMBLING_DEPOSITS,
+                names::GAMBLING_WITHDRAWALS,
+                names::GAMBLING_NET_LOSS,
+                names::GAMBLING_FREQUENCY,
+                names::GAMBLING_INCOME_SHARE,
+                names::GAMBLING_TREND,
+                names::GAMBLING_RISK,
+            ],
+            Self::LoanStacking => &[
+                names::LENDER_COUNT,
+                names::ACTIVE_LOANS,
+                names::MAX_CONCURRENT_LENDERS,
+                names::LOAN_OUTSTANDING,
+            ],
+            Self::LateLoanRepayment => &[names::LATE_REPAYMENTS, names::OVERDUE_LOANS],
+            Self::HighDebtService => &[names::DEBT_SERVICE_RATIO, names::OBLIGATION_RATIO],
+            Self::ShortHistory => &[
+                names::HISTORY_DAYS,
+                names::ACTIVE_DAYS,
+                names::MONTHS_SPANNED,
+                names::LONGEST_GAP_MONTHS,
+            ],
+        }
+    }
+
+    pub fn text(self, locale: Locale) -> &'static str {
+        match locale {
+            Locale::En => match self {
+                Self::FailedTransactions => "Too many failed or reversed transactions",
+                Self::LowIncome => "Income is too low",
+                Self::IrregularIncome => "Income is irregular or has gaps",
+                Self::LowBalance => "Account balance is often low",
+                Self::Overdraft => "Account was overdrawn",
+                Self::NegativeCashFlow => "Spending often exceeds income",
+                Self::Gambling => "Money spent on gambling",
+                Self::LoanStacking => "Loans from several lenders at once",
+                Self::LateLoanRepayment => "Loans repaid late or still overdue",
+                Self::HighDebtService => "Too much income goes to debt and bills",
+                Self::ShortHistory => "Transaction history is too short",
+            },
+            Locale::Fr => match self {
+                Self::FailedTransactions => "Trop de transactions échouées ou annulées",
+                Self::LowIncome => "Revenus trop faibles",
+                Self::IrregularIncome => "Revenus irréguliers ou interrompus",
+                Self::LowBalance => "Solde du compte souvent bas",
+                Self::Overdraft => "Compte à découvert",
+                Self::NegativeCashFlow => "Dépenses souvent supérieures aux revenus",
+                Self::Gambling => "Dépenses de jeux d'argent",
+                Self::LoanStacking => "Prêts auprès de plusieurs prêteurs en même temps",
+                Self::LateLoanRepayment => "Prêts remboursés en retard ou impayés",
+                Self::HighDebtService => "Trop de revenus consacrés aux dettes et factures",
+                Self::ShortHistory => "Historique de transactions trop court",
+            },
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Locale {
+    #[default]
+    En,
+    Fr,
+}
+
+/// One adverse-action reason.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct Reason {
+    pub code: ReasonCode,
+    pub text: String,
+    /// Score points lost against the reference profile.
+    pub cost: f64,
+    /// Ids of the transactions behind it.
+    pub transaction_ids: Vec<String>,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct ReasonRules {
+    #[serde(default)]
+    pub locale: Locale,
+    /// Most reasons reported, costliest first.
+    pub max_reasons: usize,
+    /// Feature values of a customer with nothing holding them back. Only
+    /// the features a reason covers are swapped in when costing it.
+    pub reference: BTreeMap<String, f64>,
+}
+
+impl Default for ReasonRules {
+    fn default() -> Self {
+        let reference = [
+            (names::FAILED_COUNT, 0.0),
+            (names::FAILED_WEIGHT, 0.0),
+            (names::SUCCESS_RATE, 1.0),
+            (names::TOTAL_CREDIT, 100_000.0),
+            (names::INCOME_MEAN, 50_000.0),
+            (names::INCOME_MEDIAN, 50_000.0),
+            (names::PRIMARY_INCOME_AMOUNT, 100_000.0),
+            (names::SALARY_DETECTED, 1.0),
+            (names::INCOME_VOLATILITY, 0.1),
+            (names::MONTHS_EMPLOYED, 12.0),
+            (names::MISSED_PAY_CYCLES, 0.0),
+            (names::LATE_PAY_CYCLES, 0.0),
+            (names::RECURRING_INCOME_SHARE, 0.8),
+            (names::BALANCE_AVG, 20_000.0),
+            (names::BALANCE_MIN, 5_000.0),
+            (names::DAYS_BELOW_THRESHOLD, 0.0),
+            (names::LOW_BALANCE_SHARE, 0.0),
+            (names::MONTHLY_MIN_BALANCE, 5_000.0),
+            (names::BUFFER_DAYS, 60.0),
+            (names::DAYS_OVERDRAWN, 0.0),
+            (names::OVERDRAFT_SHARE, 0.0),
+            (names::POSITIVE_MONTH_SHARE, 1.0),
+            (names::WORST_MONTH_NET, 0.0),
+            (names::EXPENSE_VOLATILITY, 0.2),
+            (names::MAX_DRAWDOWN, 0.0),
+            (names::MAX_DRAWDOWN_PCT, 0.0),
+            (names::GAMBLING_DEPOSITS, 0.0),
+            (names::GAMBLING_WITHDRAWALS, 0.0),
+            (names::GAMBLING_NET_LOSS, 0.0),
+            (names::GAMBLING_FREQUENCY, 0.0),
+            (names::GAMBLING_INCOME_SHARE, 0.0),
+            (names::GAMBLING_TREND, 0.0),
+            (names::GAMBLING_RISK, 0.0),
+            (names::LENDER_COUNT, 1.0),
+            (names::ACTIVE_LOANS, 1.0),
+            (names::MAX_CONCURRENT_LENDERS, 1.0),
+            (names::LOAN_OUTSTANDING, 0.0),
+            (names::LATE_REPAYMENTS, 0.0),
+            (names::OVERDUE_LOANS, 0.0),
+            (names::DEBT_SERVICE_RATIO, 0.2),
+            (names::OBLIGATION_RATIO, 0.4),
+            (names::HISTORY_DAYS, 365.0),
+            (names::ACTIVE_DAYS, 200.0),
+            (names::MONTHS_SPANNED, 12.0),
+            (names::LONGEST_GAP_MONTHS, 0.0),
+        ];
+        Self {
+            locale: Locale::default(),
+            max_reasons: 4,
+            reference: reference
+                .into_iter()
+                .map(|(name, value)| (name.to_string(), value))
+                .collect(),
+        }
+    }
+}
+
+/// Score before rounding: 100 * (1 - PD) when there is a PD, otherwise
+/// the summed components.
+pub(super) fn precise(score: &CreditScore) -> f64 {
+    match score.probability_of_default {
+        Some(pd) => (1.0 - pd) * 100.0,
+        None => score
+            .components
+            .iter()
+            .map(|c| c.contribution())
+            .sum::<f64>()
+            .clamp(0.0, 100.0),
+    }
+}
+
+impl ReasonRules {
+    /// Costs every reason by re-scoring with its features moved to the
+    /// reference profile. Works with any engine, since it only needs
+    /// `score`.
+    pub fn explain(
+        &self,
+        features: &FeatureVector,
+        transactions: &[Transaction],
+        config: &ScoringConfig,
+        categoriser: &Categoriser,
+        score: impl Fn(&FeatureVector) -> CreditScore,
+    ) -> Vec<Reason> {
+        let actual = precise(&score(features));
+        let mut costed: Vec<(ReasonCode, f64)> = ReasonCode::ALL
+            .into_iter()
+            .filter_map(|code| {
+                let mut reference = features.clone();
+                let mut changed = false;
+                for name in code.features() {
+                    if let Some(value) = self.reference.get(*name)
+                        && features.try_get(name) != Some(*value)
+                    {
+                        reference.set(name, *value);
+                        changed = true;
+                    }
+                }
+                let cost = precise(&score(&reference)) - actual;
+                (changed && cost > 0.0).then_some((code, cost))
+            })
+            .collect();
+        costed.sort_by(|a, b| b.1.total_cmp(&a.1));
+        costed.truncate(self.max_reasons);
+
+        costed
+            .into_iter()
+            .map(|(code, cost)| Reason {
+                code,
+                text: code.text(self.locale).to_string(),
+                cost,
+                transaction_ids: evidence(code, transactions, config, categoriser),
+            })
+            .collect()
+    }
+}
+
+/// Transactions that drove a reason.
+fn evidence(
+    code: ReasonCode,
+    transactions: &[Transaction],
+    config: &ScoringConfig,
+    categoriser: &Categoriser,
+) -> Vec<String> {
+    let successful = || transactions.iter().filter(|t| !t.is_failed());
+    let ids = |txs: Vec<&Transaction>| txs.into_iter().map(|t| t.id.clone()).collect();
+    match code {
+        ReasonCode::FailedTransactions => {
+            ids(transactions.iter().filter(|t| t.is_failed()).collect())
+        }
+        ReasonCode::LowIncome | ReasonCode::IrregularIncome => {
+            let income = detect_income(transactions, categoriser);
+            income
+                .primary()
+                .map(|p| p.transaction_ids.clone())
+                .unwrap_or_else(|| ids(successful().filter(|t| t.is_credit()).collect()))
+        }
+        ReasonCode::LowBalance => {
+            let threshold = config.liquidity.low_balance_threshold;
+            ids(successful()
+                .filter(|t| t.balance_after < threshold)
+                .collect())
+        }
+        ReasonCode::Overdraft => ids(successful().filter(|t| t.balance_after < 0.0).collect()),
+        ReasonCode::NegativeCashFlow => {
+            let months: Vec<(i32, u32)> = analyse_cash_flow(transactions)
+                .months
+                .iter()
+                .filter(|m| m.net < 0.0)
+                .map(|m| (m.year, m.month))
+                .collect();
+            ids(successful()
+                .filter(|t| t.is_debit())
+                .filter(|t| {
+                    t.date()
+                        .is_some_and(|d| months.contains(&(d.year(), d.month())))
+                })
+                .collect())
+        }
+        ReasonCode::Gambling => detect_gambling(transactions, categoriser).transaction_ids,
+        ReasonCode::LoanStacking | ReasonCode::LateLoanRepayment => {
+            let lending = detect_lending(transactions, categoriser, &config.lending);
+            lending
+                .loans
+                .into_iter()
+                .filter(|l| {
+                    code == ReasonCode::LoanStacking
+                        || matches!(l.status, LoanStatus::RepaidLate | LoanStatus::Overdue)
+                })
+                .flat_map(|l| l.transaction_ids)
+                .collect()
+        }
+        ReasonCode::HighDebtService => detect_obligations(transactions, categoriser, 0.0)
+            .obligations
+            .into_iter()
+            .flat_map(|o| o.series.transaction_ids)
+            .collect(),
+        ReasonCode::ShortHistory => Vec::new(),
+    }
+}
+
+#[cfg(test)]
+mod test_reasons {
+    use super::*;
+    use crate::credit_scoring::{ScoringEngine, heuristic::HeuristicEngine};
+    use crate::test_fixtures;
+
+    fn tx(id: &str, date: &str, dc: &str, amount: f64, balance: f64, status: &str) -> Transaction {
+        Transaction {
+            balance_after: balance,
+            status: status.into(),
+            ..test_fixtures::tx(id, date, dc, amount, "Transfer")
+        }
+    }
+
+    #[test]
+    fn testing_ranked_reasons_with_evidence() {
+        let txs = vec![
+            tx("t1", "2023-01-02", "CREDIT", 8_000.0, 8_000.0, "SUCCESSFUL"),
+            tx("t2", "2023-01-10", "DEBIT", 7_500.0, 500.0, "SUCCESSFUL"),
+            tx("t3", "2023-01-12", "DEBIT", 2_000.0, 500.0, "FAILED"),
+            tx("t4", "2023-02-03", "CREDIT", 6_000.0, 6_500.0, "SUCCESSFUL"),
+            tx("t5", "2023-02-20", "DEBIT", 6_400.0, 100.0, "SUCCESSFUL"),
+            tx("t6", "2023-02-21", "DEBIT", 900.0, 100.0, "FAILED"),
+        ];
+        let config = ScoringConfig::default();
+        let engine = HeuristicEngine::default();
+        let score = engine.assess(&txs, &config).unwrap();
+
+        // Two failed transactions at 20 points each cost the most.
+        let first = &score.reasons[0];
+        assert_eq!(first.code, ReasonCode::FailedTransactions);
+        assert_eq!(first.transaction_ids, vec!["t3", "t6"]);
+        assert!(score.reasons.windows(2).all(|w| w[0].cost >= w[1].cost));
+        assert!(score.reasons.len() <= 4);
+
+        let low_balance = score
+            .reasons
+            .iter()
+            .find(|r| r.code == ReasonCode::LowBalance)
+            .unwrap();
+        assert_eq!(low_balance.text, "Account balance is often low");
+        assert_eq!(low_balance.transaction_ids, vec!["t2", "t5"]);
+
+        assert_eq!(
+            serde_json::to_string(&ReasonCode::LateLoanRepayment).unwrap(),
+            "\"LATE_LOAN_REPAYMENT\""
+        );
+    }
+
+    #[test]
+    fn testing_no_reasons_at_reference() {
+        let rules = ReasonRules {
+            locale: Locale::Fr,
+            ..ReasonRules::default()
+        };
+        let mut fv = FeatureVector::new();
+        fv.set(names::TRANSACTION_COUNT, 50.0);
+        for (name, value) in &rules.reference {
+            fv.set(name, *value);
+        }
+        let engine = HeuristicEngine::default();
+        let config = ScoringConfig::default();
+        assert!(
+            rules
+                .explain(&fv, &[], &config, &Categoriser::default(), |fv| {
+                    engine.score(fv, &config)
+                })
+                .is_empty()
+        );
+        assert_eq!(
+            ReasonCode::Gambling.text(Locale::Fr),
+            "Dépenses de jeux d'argent"
+        );
+    }
+}
@@ -0,0 +1,192 @@
+use super::engine::{ConfigField, FieldKind, ScoringEngine};
+use super::features::{FeaturePipeline, FeatureVector, names};
+use super::streaming::FeatureStream;
+use super::{CreditScore, ScoreComponent, ScoringConfig};
+use super::{
+    cashflow_stability_score, component, component_factors, gambling_component, gambling_factor,
+    lending_factors, liquidity_factors, liquidity_rules_component, no_history_score,
+    stability_factors,
+};
+use crate::openbank::Transaction;
+use chrono::{NaiveDate, Utc};
+use serde::{Deserialize, Serialize};
+
+/// How much a transaction counts given its age in days.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub enum RecencyDecay {
+    /// Step decay: the first bucket whose `max_days` covers the age wins,
+    /// anything older than every bucket gets `older_weight`.
+    Step {
+        buckets: Vec<DecayBucket>,
+        older_weight: f64,
+    },
+    /// Exponential decay, weight halves every `half_life_days`.
+    HalfLife { half_life_days: f64 },
+}
+
+#[derive(Clone, Copy, Debug, Serialize, Deserialize)]
+pub struct DecayBucket {
+    pub max_days: u32,
+    pub weight: f64,
+}
+
+impl Default for RecencyDecay {
+    fn default() -> Self {
+        Self::Step {
+            buckets: vec![
+                DecayBucket {
+                    max_days: 30,
+                    weight: 1.0, // Fresh data (100% impact)
+                },
+                DecayBucket {
+                    max_days: 90,
+                    weight: 0.5, // Recent history (50% impact)
+                },
+            ],
+            older_weight: 0.2, // Old history (20% impact)
+        }
+    }
+}
+
+impl RecencyDecay {
+    pub fn weight(&self, days_old: u32) -> f64 {
+        match self {
+            Self::Step {
+                buckets,
+                older_weight,
+            } => buckets
+                .iter()
+                .filter(|b| days_old <= b.max_days)
+                .min_by_key(|b| b.max_days)
+                .map(|b| b.weight)
+                .unwrap_or(*older_weight),
+            Self::HalfLife { half_life_days } => {
+                if *half_life_days <= 0.0 {
+                    return 1.0;
+                }
+                0.5_f64.powf(days_old as f64 / half_life_days)
+            }
+        }
+    }
+}
+
+/// Same buckets as the heuristic model, but every transaction is weighted by
+/// how old it is relative to `as_of` (today when unset).
+#[derive(Clone, Debug, Default)]
+pub struct RecencyEngine {
+    pub decay: RecencyDecay,
+    pub as_of: Option<NaiveDate>,
+}
+
+impl RecencyEngine {
+    pub fn as_of(&self) -> NaiveDate {
+        self.as_of.unwrap_or_else(|| Utc::now().date_naive())
+    }
+
+    /// Weights each transaction by its age at `as_of`; anything later has
+    /// not happened yet and is left out.
+    fn pipeline(&self, config: &ScoringConfig) -> FeaturePipeline<'_> {
+        let as_of = self.as_of();
+        FeaturePipeline::for_config(config)
+            .until(as_of)
+            .weighted(move |tx| {
+                // Skip invalid dates or parse errors
+                let days_old = (as_of - tx.date()?).num_days();
+                if days_old < 0 {
+                    return None;
+                }
+                Some(self.decay.weight(days_old as u32))
+            })
+    }
+}
+
+impl ScoringEngine for RecencyEngine {
+    fn id(&self) -> &str {
+        "recency_weighted"
+    }
+
+    fn name(&self) -> &str {
+        "Recency weighted"
+    }
+
+    fn version(&self) -> &str {
+        "1.0.0"
+    }
+
+    fn description(&self) -> &str {
+        "Heuristic buckets where recent income, balances and failures count more than old ones."
+    }
+
+    fn config_schema(&self) -> Vec<ConfigField> {
+        vec![
+            ConfigField::new("decay", "Decay", FieldKind::Json)
+                .default_x(serde_json::to_value(&self.decay).unwrap_or_default()),
+            ConfigField::new("as_of", "As of", FieldKind::Date).optional_x(),
+        ]
+    }
+
+    fn extract(&self, transactions: &[Transaction], config: &ScoringConfig) -> FeatureVector {
+        self.pipeline(config).extract(transactions)
+    }
+
+    fn stream<'a>(&'a self, config: &ScoringConfig) -> FeatureStream<'a> {
+        self.pipeline(config).stream()
+    }
+
+    fn score(&self, features: &FeatureVector, config: &ScoringConfig) -> CreditScore {
+        if features.get(names::TRANSACTION_COUNT) == 0.0 {
+            return no_history_score(&config.risk_bands);
+        }
+
+        // Logic: Adjusted Income Score (Target: 5000 weighted income units)
+        // Recent income fills this bucket faster than old income.
+        let total_credit = features.get(names::TOTAL_CREDIT);
+        let income_score = (total_credit / 5_000.0 * 100.0).min(100.0);
+
+        // Logic: Liquidity Score based on weighted average
+        let liquidity_score = (features.get(names::BALANCE_AVG) / 3_000.0 * 100.0).min(100.0);
+
+        // Logic: Cashflow (Months in the black, steady recent income is better)
+        let cashflow_score = cashflow_stability_score(features);
+
+        // Recent failures hurt A LOT (20 * 1.0 = 20 pts),
+        // Old failures hurt a little (20 * 0.2 = 4 pts)
+        let failed_weight = features.get(names::FAILED_WEIGHT);
+        let penalty = failed_weight * 20.0;
+
+        let components = vec![
+            ScoreComponent::new(component::INCOME, income_score, 0.4),
+            ScoreComponent::new(component::LIQUIDITY, liquidity_score, 0.4),
+            ScoreComponent::new(component::CASHFLOW, cashflow_score, 0.2),
+            ScoreComponent::new(component::PENALTIES, -penalty, 1.0),
+            gambling_component(features, config),
+            liquidity_rules_component(features, config),
+        ];
+
+        let mut factors = component_factors(&components);
+        factors.extend(stability_factors(features));
+        factors.extend(liquidity_factors(features));
+        factors.extend(lending_factors(features));
+        factors.extend(gambling_factor(features));
+        if failed_weight >= 1.0 {
+            factors.push("Recent Failed Transactions".to_string());
+        } else if features.get(names::FAILED_COUNT) > 0.0 {
+            factors.push("Failed Transactions".to_string());
+        }
+
+        CreditScore::from_components(components, factors, &config.risk_bands)
+    }
+}
+
+pub fn calculate_recency_score(
+    transactions: &[Transaction],
+    decay: &RecencyDecay,
+    as_of: NaiveDate,
+    config: &ScoringConfig,
+) -> CreditScore {
+    RecencyEngine {
+        decay: decay.clone(),
+        as_of: Some(as_of),
+    }
+    .evaluate(transactions, config)
+}
@@ -0,0 +1,302 @@
+use super::engine::{ConfigField, FieldKind, ScoringEngine};
+use super::features::{FeatureVector, names};
+use super::{CreditScore, ScoreComponent, ScoringConfig, classify_risk, no_history_score};
+use crate::result::{Error, Result};
+use serde::{Deserialize, Serialize};
+use std::path::Path;
+
+/// Turns log-odds into points: `base_score` points at `base_odds` (good to
+/// bad), and every `pdo` points doubles the odds.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct PointsScaling {
+    pub base_score: f64,
+    pub base_odds: f64,
+    pub pdo: f64,
+}
+
+impl Default for PointsScaling {
+    fn default() -> Self {
+        Self {
+            base_score: 600.0,
+            base_odds: 50.0,
+            pdo: 20.0,
+        }
+    }
+}
+
+impl PointsScaling {
+    pub fn factor(&self) -> f64 {
+        self.pdo / std::f64::consts::LN_2
+    }
+
+    pub fn offset(&self) -> f64 {
+        self.base_score - self.factor() * self.base_odds.ln()
+    }
+}
+
+/// One attribute of a characteristic. Covers `min <= value < max`, either
+/// end open when unset; a `missing` bin catches features the vector lacks.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct Bin {
+    #[serde(default)]
+    pub label: String,
+    #[serde(default)]
+    pub min: Option<f64>,
+    #[serde(default)]
+    pub max: Option<f64>,
+    #[serde(default)]
+    pub missing: bool,
+    /// Weight of evidence, `ln(%good / %bad)`.
+    pub woe: f64,
+}
+
+impl Bin {
+    fn contains(&self, value: f64) -> bool {
+        !self.missing
+            && self.min.is_none_or(|min| value >= min)
+            && self.max.is_none_or(|max| value < max)
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct Characteristic {
+    /// Feature name, see `features::names`.
+    pub feature: String,
+    pub coefficient: f64,
+    pub bins: Vec<Bin>,
+}
+
+impl Characteristic {
+    pub(crate) fn bin(&self, features: &FeatureVector) -> Option<&Bin> {
+        match features.try_get(&self.feature) {
+            Some(value) => self.bins.iter().find(|b| b.contains(value)),
+            None => self.bins.iter().find(|b| b.missing),
+        }
+    }
+}
+
+/// Logistic regression on WoE-binned features, the classic scorecard:
+/// `ln(odds of good) = intercept + sum(coefficient * woe)`.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct Scorecard {
+    pub name: String,
+    #[serde(default)]
+    pub version: String,
+    pub intercept: f64,
+    pub characteristics: Vec<Characteristic>,
+    #[serde(default)]
+    pub scaling: PointsScaling,
+}
+
+/// A scorecard's verdict before it becomes a `CreditScore`.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct ScorecardResult {
+    pub points: f64,
+    pub probability_of_default: f64,
+    /// Points per characteristic, intercept spread evenly across them.
+    pub attributes: Vec<(String, String, f64)>,
+}
+
+impl Scorecard {
+    pub fn from_json(json: &str) -> Result<Self> {
+        serde_json::from_str(json).map_err(|e| {
+            Error::user("The scorecard could not be read.").add_meta_x("error", &e.to_string())
+        })
+    }
+
+    pub fn from_toml(text: &str) -> Result<Self> {
+        toml::from_str(text).map_err(|e| {
+            Error::user("The scorecard could not be read.").add_meta_x("error", &e.to_string())
+        })
+    }
+
+    /// Reads a `.toml` or `.json` scorecard, going by the extension.
+    pub fn load(path: impl AsRef<Path>) -> Result<Self> {
+        let path = path.as_ref();
+        let text = std::fs::read_to_string(path)?;
+        match path.extension().and_then(|e| e.to_str()) {
+            Some("toml") => Self::from_toml(&text),
+            _ => Self::from_json(&text),
+        }
+    }
+
+    pub fn to_toml(&self) -> Result<String> {
+        toml::to_string_pretty(self).map_err(|e| {
+            Error::tech("The scorecard could not be written.").add_meta_x("error", &e.to_string())
+        })
+    }
+
+    /// Writes the card as TOML or JSON, going by the extension.
+    pub fn save(&self, path: impl AsRef<Path>) -> Result<()> {
+        let path = path.as_ref();
+        let text = match path.extension().and_then(|e| e.to_str()) {
+            Some("toml") => self.to_toml()?,
+            _ => serde_json::to_string_pretty(self)?,
+        };
+        Ok(std::fs::write(path, text)?)
+    }
+
+    pub fn evaluate(&self, features: &FeatureVector) -> ScorecardResult {
+        let factor = self.scaling.factor();
+        let n = self.characteristics.len().max(1) as f64;
+        let share = (self.intercept * factor + self.scaling.offset()) / n;
+
+        let mut log_odds = self.intercept;
+        let mut attributes = Vec::with_capacity(self.characteristics.len());
+        for c in &self.characteristics {
+            let bin = c.bin(features);
+            let woe = bin.map(|b| b.woe).unwrap_or(0.0);
+            log_odds += c.coefficient * woe;
+            attributes.push((
+                c.feature.clone(),
+                bin.map(|b| b.label.clone()).unwrap_or_default(),
+                c.coefficient * woe * factor + share,
+            ));
+        }
+
+        ScorecardResult {
+            points: self.scaling.offset() + factor * log_odds,
+            probability_of_default: 1.0 / (1.0 + log_odds.exp()),
+            attributes,
+        }
+    }
+}
+
+/// Runs a `Scorecard`. `total_score` is `100 * (1 - PD)`; the points and
+/// PD themselves are reported alongside, and each characteristic becomes a
+/// component worth its points.
+#[derive(Clone, Debug)]
+pub struct ScorecardEngine {
+    pub card: Scorecard,
+}
+
+impl ScoringEngine for ScorecardEngine {
+    fn id(&self) -> &str {
+        "scorecard"
+    }
+
+    fn name(&self) -> &str {
+        &self.card.name
+    }
+
+    fn version(&self) -> &str {
+        &self.card.version
+    }
+
+    fn description(&self) -> &str {
+        "Logistic-regression scorecard over weight-of-evidence bins, scaled to points."
+    }
+
+    fn config_schema(&self) -> Vec<ConfigField> {
+        vec![
+            ConfigField::new("card", "Scorecard", FieldKind::Json)
+                .default_x(serde_json::to_value(&self.card).unwrap_or_default()),
+        ]
+    }
+
+    fn score(&self, features: &FeatureVector, config: &ScoringConfig) -> CreditScore {
+        if features.get(names::TRANSACTION_COUNT) == 0.0 {
+            return no_history_score(&config.risk_bands);
+        }
+        let result = self.card.evaluate(features);
+        let components = result
+            .attributes
+            .iter()
+            .map(|(feature, _, points)| ScoreComponent::new(feature, *points, 1.0))
+            .collect();
+        let factors = result
+            .attributes
+            .iter()
+            .filter(|(_, label, _)| !label.is_empty())
+            .map(|(feature, label, _)| format!("{feature}: {label}"))
+            .collect();
+
+        let total_score = ((1.0 - result.probability_of_default) * 100.0)
+            .clamp(0.0, 100.0)
+            .round() as u8;
+        let mut score = CreditScore::from_components(components, factors, &config.risk_bands);
+        score.total_score = total_score;
+        score.risk_level = classify_risk(total_score, &config.risk_bands);
+        score.points = Some(result.points);
+        score.probability_of_default = Some(result.probability_of_default);
+        score
+    }
+}
+
+#[cfg(test)]
+mod test_scorecard {
+    use super::*;
+    use crate::credit_scoring::ScoringModel;
+
+    const CARD: &str = r#"
+name = "Thin-file starter"
+version = "1"
+intercept = 2.0
+
+[scaling]
+base_score = 600.0
+base_odds = 50.0
+pdo = 20.0
+
+[[characteristics]]
+feature = "success_rate"
+coefficient = 1.0
+bins = [
+    { label = "under 90%", max = 0.9, woe = -0.8 },
+    { label = "90% and up", min = 0.9, woe = 0.4 },
+]
+
+[[characteristics]]
+feature = "months_employed"
+coefficient = 0.5
+bins = [
+    { label = "missing", missing = true, woe = -0.2 },
+    { label = "under 3", max = 3.0, woe = -0.6 },
+    { label = "3 and up", min = 3.0, woe = 0.9 },
+]
+"#;
+
+    #[test]
+    fn testing_points_and_probability() {
+        let card = Scorecard::from_toml(CARD).unwrap();
+        let mut fv = FeatureVector::new();
+        fv.set(names::SUCCESS_RATE, 0.95);
+        fv.set(names::MONTHS_EMPLOYED, 6.0);
+
+        let result = card.evaluate(&fv);
+        let log_odds: f64 = 2.0 + 0.4 + 0.5 * 0.9;
+        assert!((result.probability_of_default - 1.0 / (1.0 + log_odds.exp())).abs() < 1e-12);
+        // 600 at 50:1, 20 points per doubling.
+        let expected = 600.0 + 20.0 / std::f64::consts::LN_2 * (log_odds - 50f64.ln());
+        assert!((result.points - expected).abs() < 1e-9);
+        // Attribute points add up to the total.
+        let sum: f64 = result.attributes.iter().map(|a| a.2).sum();
+        assert!((sum - result.points).abs() < 1e-9);
+
+        let config = ScoringConfig {
+            model: ScoringModel::Scorecard { card: card.clone() },
+            ..ScoringConfig::default()
+        };
+        fv.set(names::TRANSACTION_COUNT, 40.0);
+        let engine = ScorecardEngine { card };
+        let score = engine.score(&fv, &config);
+        assert_eq!(score.points, Some(result.points));
+        assert_eq!(
+            score.total_score,
+            ((1.0 - result.probability_of_default) * 100.0).round() as u8
+        );
+    }
+
+    #[test]
+    fn testing_missing_bin_and_json_roundtrip() {
+        let card = Scorecard::from_toml(CARD).unwrap();
+        let json = serde_json::to_string(&card).unwrap();
+        assert_eq!(Scorecard::from_json(&json).unwrap(), card);
+
+        let mut fv = FeatureVector::new();
+        fv.set(names::SUCCESS_RATE, 0.5);
+        let result = card.evaluate(&fv);
+        assert_eq!(result.attributes[1].1, "missing");
+        assert!((result.probability_of_default - 1.0 / (1.0 + 1.1f64.exp())).abs() < 1e-12);
+    }
+}
@@ -0,0 +1,277 @@
+use super::engine::ScoringEngine;
+use super::{CreditScore, ScoringConfig};
+use crate::analysis::categorise::Categoriser;
+use crate::analysis::gambling::detect_gambling;
+use crate::openbank::Transaction;
+use crate::result::Result;
+use chrono::{Datelike, Months};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, BTreeSet};
+
+/// A hypothetical change to the history.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum Scenario {
+    /// Spend `amount` less every month, debits trimmed pro rata; the
+    /// savings pile up in the balance.
+    MonthlySavings { amount: f64 },
+    /// Drop every betting deposit and withdrawal.
+    RemoveGambling,
+    /// Drop failed transactions.
+    AvoidFailedTransactions,
+    /// Top the balance up so it never falls below `balance` during the
+    /// last `months` months.
+    KeepMinBalance { balance: f64, months: u32 },
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct ComponentChange {
+    pub name: String,
+    pub base: f64,
+    pub projected: f64,
+    /// Change in weighted contribution.
+    pub change: f64,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct Simulation {
+    pub base: CreditScore,
+    pub projected: CreditScore,
+    pub score_change: i32,
+    pub factors_gained: Vec<String>,
+    pub factors_lost: Vec<String>,
+    pub component_changes: Vec<ComponentChange>,
+}
+
+/// Applies the scenarios in order and scores the history before and after
+/// with the same engine and config.
+pub fn simulate(
+    engine: &dyn ScoringEngine,
+    transactions: &[Transaction],
+    scenarios: &[Scenario],
+    config: &ScoringConfig,
+) -> Result<Simulation> {
+    let base = engine.assess(transactions, config)?;
+    let changed = apply_scenarios(transactions, scenarios, config);
+    let projected = engine.assess(&changed, config)?;
+
+    let before: BTreeSet<&String> = base.factors.iter().collect();
+    let after: BTreeSet<&String> = projected.factors.iter().collect();
+    let component_changes = projected
+        .components
+        .iter()
+        .map(|p| {
+            let b = base.component(&p.name);
+            ComponentChange {
+                name: p.name.clone(),
+                base: b.map(|c| c.score).unwrap_or_default(),
+                projected: p.score,
+                change: p.contribution() - b.map(|c| c.contribution()).unwrap_or_default(),
+            }
+        })
+        .filter(|c| c.change != 0.0)
+        .collect();
+
+    Ok(Simulation {
+        score_change: projected.total_score as i32 - base.total_score as i32,
+        factors_gained: after.difference(&before).map(|f| f.to_string()).collect(),
+        factors_lost: before.difference(&after).map(|f| f.to_string()).collect(),
+        component_changes,
+        base,
+        projected,
+    })
+}
+
+/// The history with the scenarios applied, in time order.
+pub fn apply_scenarios(
+    transactions: &[Transaction],
+    scenarios: &[Scenario],
+    config: &ScoringConfig,
+) -> Vec<Transaction> {
+    let mut txs = transactions.to_vec();
+    txs.sort_by_key(|t| t.timestamp());
+    for scenario in scenarios {
+        txs = match scenario {
+            Scenario::MonthlySavings { amount } => {
+                let mut debits: BTreeMap<(i32, u32), f64> = BTreeMap::new();
+                for tx in txs.iter().filter(|t| t.is_debit() && !t.is_failed()) {
+                    if let Some(d) = tx.date() {
+                        *debits.entry((d.year(), d.month())).or_default() += tx.amount;
+                    }
+                }
+                edit(txs, |tx| {
+                    let month = tx.date().map(|d| (d.year(), d.month()));
+                    match month.and_then(|m| debits.get(&m)) {
+                        Some(total) if tx.is_debit() && *total > 0.0 => {
+                            Some(tx.amount * ((total - amount) / total).max(0.0))
+                        }
+                        _ => Some(tx.amount),
+                    }
+                })
+            }
+            Scenario::RemoveGambling => {
+                let ids: BTreeSet<String> = detect_gambling(&txs, &Categoriser::for_config(config))
+                    .transaction_ids
+                    .into_iter()
+                    .collect();
+                edit(txs, |tx| (!ids.contains(&tx.id)).then_some(tx.amount))
+            }
+            Scenario::AvoidFailedTransactions => {
+                txs.retain(|t| !t.is_failed());
+                txs
+            }
+            Scenario::KeepMinBalance { balance, months } => {
+                let Some(last) = txs.iter().filter_map(|t| t.date()).max() else {
+                    continue;
+                };
+                let from = last
+                    .checked_sub_months(Months::new(*months))
+                    .unwrap_or(last);
+                let in_window = |t: &Transaction| t.date().is_some_and(|d| d > from);
+                let lowest = txs
+                    .iter()
+                    .filter(|t| !t.is_failed() && in_window(t))
+                    .map(|t| t.balance_after)
+                    .fold(f64::INFINITY, f64::min);
+                let top_up = (balance - lowest).max(0.0);
+                for tx in txs.iter_mut().filter(|t| in_window(t)) {
+                    tx.balance_after += top_up;
+                }
+                txs
+            }
+        };
+    }
+    txs
+}
+
+/// Replays the history with `amount` giving each transaction's new amount,
+/// or `None` to drop it; the balance effect carries over to everything
+/// after it.
+fn edit(
+    txs: Vec<Transaction>,
+    mut amount: impl FnMut(&Transaction) -> Option<f64>,
+) -> Vec<Transaction> {
+    let mut shift = 0.0;
+    txs.into_iter()
+        .filter_map(|mut tx| {
+            let new = amount(&tx);
+            if !tx.is_failed() {
+                let delta = new.unwrap_or(0.0) - tx.amount;
+                if tx.is_debit() {
+                    shift -= delta;
+                } else if tx.is_credit() {
+                    shift += delta;
+                }
+            }
+            tx.amount = new?;
+            tx.balance_after += shift;
+            Some(tx)
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod test_simulator {
+    use super::*;
+    use crate::credit_scoring::HeuristicEngine;
+    use crate::test_fixtures;
+
+    fn tx(
+        id: &str,
+        date: &str,
+        dc: &str,
+        amount: f64,
+        balance: f64,
+        narration: &str,
+    ) -> Transaction {
+        Transaction {
+            balance_after: balance,
+            ..test_fixtures::tx(id, date, dc, amount, narration)
+        }
+    }
+
+    fn history() -> Vec<Transaction> {
+        let mut failed = tx("f1", "2023-02-15", "DEBIT", 500.0, 1_000.0, "POS");
+        failed.status = "FAILED".into();
+        vec![
+            tx(
+                "1",
+                "2023-01-02",
+                "CREDIT",
+                5_000.0,
+                5_000.0,
+                "Transfer from ACME",
+            ),
+            tx(
+                "2",
+                "2023-01-05",
+                "DEBIT",
+                2_000.0,
+                3_000.0,
+                "BET9JA CASINO TOPUP",
+            ),
+            tx("3", "2023-01-20", "DEBIT", 2_000.0, 1_000.0, "POS"),
+            failed,
+            tx("4", "2023-02-28", "DEBIT", 1_000.0, 0.0, "POS"),
+        ]
+    }
+
+    #[test]
+    fn testing_scenarios_rewrite_history() {
+        let config = ScoringConfig::default();
+        let without = apply_scenarios(&history(), &[Scenario::RemoveGambling], &config);
+        assert!(without.iter().all(|t| t.id != "2"));
+        // The 2,000 bet stays in the account from then on.
+        assert_eq!(without.last().unwrap().balance_after, 2_000.0);
+
+        let saved = apply_scenarios(
+            &history(),
+            &[Scenario::MonthlySavings { amount: 1_000.0 }],
+            &config,
+        );
+        // January's 4,000 of debits trimmed to 3,000, February's to 0.
+        assert_eq!(saved[1].amount, 1_500.0);
+        assert_eq!(saved.last().unwrap().amount, 0.0);
+        assert_eq!(saved.last().unwrap().balance_after, 2_000.0);
+
+        let kept = apply_scenarios(
+            &history(),
+            &[Scenario::KeepMinBalance {
+                balance: 1_500.0,
+                months: 1,
+            }],
+            &config,
+        );
+        // Only February is in the window, lifted by 1,500.
+        assert_eq!(kept[2].balance_after, 1_000.0);
+        assert_eq!(kept.last().unwrap().balance_after, 1_500.0);
+    }
+
+    #[test]
+    fn testing_simulation_reports_deltas() {
+        let simulation = simulate(
+            &HeuristicEngine::default(),
+            &history(),
+            &[Scenario::AvoidFailedTransactions, Scenario::RemoveGambling],
+            &ScoringConfig::default(),
+        )
+        .unwrap();
+
+        assert!(simulation.score_change > 0);
+        assert_eq!(
+            simulation.score_change,
+            simulation.projected.total_score as i32 - simulation.base.total_score as i32
+        );
+        assert!(
+            simulation
+                .factors_lost
+                .contains(&"Failed Transactions".to_string())
+        );
+        assert!(
+            simulation
+                .component_changes
+                .iter()
+                .any(|c| c.name == "penalties" && c.change > 0.0)
+        );
+    }
+}
@@ -0,0 +1,314 @@
+use super::features::{
+    CoreStats, FeatureVector, Incomes, Weighting, set_cash_flow_features, set_gambling_features,
+    set_liquidity_features, set_pattern_features,
+};
+use crate::analysis::cashflow::CashFlowAccumulator;
+use crate::analysis::categorise::{Categoriser, Category};
+use crate::analysis::gambling::GamblingAccumulator;
+use crate::analysis::lending::{LendingRules, categorised_lender_name};
+use crate::analysis::liquidity::LiquidityAccumulator;
+use crate::openbank::Transaction;
+use chrono::NaiveDate;
+use std::collections::{BTreeMap, VecDeque};
+
+/// Most transactions `FeatureStream` keeps for income, obligation and
+/// lending detection unless told otherwise.
+pub const DEFAULT_PATTERN_CAPACITY: usize = 50_000;
+
+/// Relative width of a `QuantileSketch` bucket.
+const SKETCH_GAMMA: f64 = 1.001;
+
+/// Log-bucketed histogram of amounts. Quantiles come back within 0.05% of
+/// the exact value, and memory grows with the range of amounts rather
+/// than their count.
+#[derive(Clone, Debug, Default)]
+pub(crate) struct QuantileSketch {
+    buckets: BTreeMap<i32, u64>,
+    /// Zero and negative amounts, which have no log bucket.
+    non_positive: u64,
+    count: u64,
+}
+
+impl QuantileSketch {
+    pub(crate) fn insert(&mut self, value: f64) {
+        self.count += 1;
+        if value <= 0.0 {
+            self.non_positive += 1;
+            return;
+        }
+        let index = (value.ln() / SKETCH_GAMMA.ln()).ceil() as i32;
+        *self.buckets.entry(index).or_default() += 1;
+    }
+
+    pub(crate) fn count(&self) -> u64 {
+        self.count
+    }
+
+    /// Averages the two middle values for an even count, as `median` does.
+    pub(crate) fn median(&self) -> f64 {
+        if self.count == 0 {
+            return 0.0;
+        }
+        (self.at_rank((self.count - 1) / 2) + self.at_rank(self.count / 2)) / 2.0
+    }
+
+    /// Value of the `rank`th smallest amount, counting from 0.
+    fn at_rank(&self, rank: u64) -> f64 {
+        if rank < self.non_positive {
+            return 0.0;
+        }
+        let mut seen = self.non_positive;
+        for (index, n) in &self.buckets {
+            seen += n;
+            if rank < seen {
+                return 2.0 * SKETCH_GAMMA.powi(*index) / (SKETCH_GAMMA + 1.0);
+            }
+        }
+        0.0
+    }
+}
+
+/// `FeaturePipeline::extract` in a single pass: push transactions one at
+/// a time or in chunks, oldest first, then `finalize`.
+///
+/// Totals, balances, cash flow, liquidity and gambling are accumulated as
+/// they arrive. The income median comes from a `QuantileSketch`. Income,
+/// obligation and lending detection look for patterns across the whole
+/// history, so the transactions they could use (no failures, and no card
+/// spend, betting or bank charges unless owed to a lender) are buffered up
+/// to `pattern_capacity`, the oldest dropped first. Below that the features
+/// match the slice version.
+pub struct FeatureStream<'a> {
+    weighting: Option<Weighting<'a>>,
+    categoriser: Categoriser,
+    core: CoreStats,
+    cash_flow: CashFlowAccumulator,
+    liquidity: LiquidityAccumulator,
+    gambling: GamblingAccumulator,
+    lending: LendingRules,
+    patterns: VecDeque<Transaction>,
+    pattern_capacity: usize,
+    /// Date of the latest transaction, where the history ends.
+    latest: Option<NaiveDate>,
+    until: Option<NaiveDate>,
+}
+
+impl<'a> FeatureStream<'a> {
+    pub(crate) fn new(
+        weighting: Option<Weighting<'a>>,
+        categoriser: Categoriser,
+        low_balance_threshold: f64,
+        lending: LendingRules,
+        until: Option<NaiveDate>,
+    ) -> Self {
+        Self {
+            weighting,
+            categoriser,
+            core: CoreStats::new(Incomes::Sketch(QuantileSketch::default())),
+            cash_flow: CashFlowAccumulator::new(),
+            liquidity: LiquidityAccumulator::new(low_balance_threshold),
+            gambling: GamblingAccumulator::default(),
+            lending,
+            patterns: VecDeque::new(),
+            pattern_capacity: DEFAULT_PATTERN_CAPACITY,
+            latest: None,
+            until,
+        }
+    }
+
+    pub fn pattern_capacity(mut self, capacity: usize) -> Self {
+        self.pattern_capacity = capacity;
+        self
+    }
+
+    pub fn push(&mut self, tx: &Transaction) {
+        if let (Some(until), Some(d)) = (self.until, tx.date())
+            && d > until
+        {
+            return;
+        }
+        let detail = self.categoriser.categorise_detail(tx);
+        let weight = match &self.weighting {
+            Some(f) => f(tx),
+            None => Some(1.0),
+        };
+        if let Some(w) = weight {
+            self.core.push(tx, w, detail.category);
+        }
+
+        // Like the slice version, everything below sees every transaction
+        // whatever its weight.
+        self.cash_flow.push(tx);
+        self.liquidity.push(tx);
+        self.gambling
+            .push_categorised(tx, &detail, &self.categoriser);
+
+        if let Some(d) = tx.date() {
+            self.latest = self.latest.max(Some(d));
+        }
+        if is_pattern_candidate(tx, detail.category) && self.pattern_capacity > 0 {
+            if self.patterns.len() >= self.pattern_capacity {
+                self.patterns.pop_front();
+            }
+            self.patterns.push_back(tx.clone());
+        }
+    }
+
+    pub fn push_chunk(&mut self, transactions: &[Transaction]) {
+        for tx in transactions {
+            self.push(tx);
+        }
+    }
+
+    pub fn finalize(self) -> FeatureVector {
+        let mut fv = FeatureVector::new();
+        let Some(totals) = self.core.finish(&mut fv) else {
+            return fv;
+        };
+
+        let patterns: Vec<Transaction> = self.patterns.into();
+        set_pattern_features(
+            &mut fv,
+            &patterns,
+            self.latest,
+            &self.categoriser,
+            &self.lending,
+            &totals,
+        );
+        set_cash_flow_features(&mut fv, &self.cash_flow.finish());
+        set_liquidity_features(&mut fv, &self.liquidity.finish());
+        set_gambling_features(&mut fv, &self.gambling.finish());
+        fv
+    }
+}
+
+/// Whether the pattern detectors could use `tx`, given its category.
+fn is_pattern_candidate(tx: &Transaction, category: Category) -> bool {
+    if tx.is_failed() {
+        return false;
+    }
+    if tx.is_credit() {
+        return true;
+    }
+    tx.is_debit()
+        && (!matches!(
+            category,
+            Category::PosSpend | Category::Gambling | Category::BankCharges
+        ) || categorised_lender_name(tx, category).is_some())
+}
+
+#[cfg(test)]
+mod test_streaming {
+    use super::*;
+    use crate::credit_scoring::features::{FeaturePipeline, names};
+    use crate::test_fixtures;
+    use chrono::Days;
+
+    fn tx(id: usize, date: NaiveDate, dc: &str, amount: f64, narration: &str) -> Transaction {
+        let date = date.format("%Y-%m-%d").to_string();
+        Transaction {
+            balance_after: (id % 97) as f64 * 1_000.0 - 5_000.0,
+            status: if id.is_multiple_of(37) {
+                "FAILED"
+            } else {
+                "SUCCESSFUL"
+            }
+            .into(),
+            ..test_fixtures::tx(&id.to_string(), &date, dc, amount, narration)
+        }
+    }
+
+    /// A year of salary, rent, card spend, betting and a loan.
+    fn history() -> Vec<Transaction> {
+        let start = NaiveDate::from_ymd_opt(2022, 1, 3).unwrap();
+        let mut txs = Vec::new();
+        for day in 0..365u64 {
+            let date = start + Days::new(day);
+            let id = txs.len();
+            if day % 30 == 0 {
+                txs.push(tx(id, date, "CREDIT", 250_000.0, "SALARY ACME LTD"));
+                txs.push(tx(id + 1, date, "DEBIT", 80_000.0, "RENT LANDLORD OKON"));
+            } else if day % 7 == 0 {
+                txs.push(tx(
+                    id,
+                    date,
+                    "DEBIT",
+                    5_000.0 + day as f64,
+                    "BET9JA deposit",
+                ));
+            } else if day == 200 {
+                txs.push(tx(id, date, "CREDIT", 50_000.0, "CARBON loan disbursement"));
+            } else if day == 230 {
+                txs.push(tx(id, date, "DEBIT", 55_000.0, "CARBON loan repayment"));
+            } else {
+                txs.push(tx(id, date, "DEBIT", 1_500.0, "POS purchase SHOPRITE"));
+            }
+        }
+        txs
+    }
+
+    #[test]
+    fn testing_stream_matches_slice() {
+        let txs = history();
+        let slice = FeaturePipeline::new().extract(&txs);
+        let mut stream = FeaturePipeline::new().stream();
+        for chunk in txs.chunks(100) {
+            stream.push_chunk(chunk);
+        }
+        let streamed = stream.finalize();
+
+        for name in [
+            names::SALARY_DETECTED,
+            names::OBLIGATION_COUNT,
+            names::LENDER_COUNT,
+            names::GAMBLING_DEPOSITS,
+            names::MAX_DRAWDOWN,
+            names::DAYS_OVERDRAWN,
+        ] {
+            assert!(slice.get(name) > 0.0, "{name}");
+        }
+        assert_eq!(slice.values.len(), streamed.values.len());
+        for (name, expected) in &slice.values {
+            let got = streamed.get(name);
+            let tolerance = if name == names::INCOME_MEDIAN {
+                expected.abs() * 1e-3
+            } else {
+                1e-6 * expected.abs().max(1.0)
+            };
+            assert!(
+                (got - expected).abs() <= tolerance,
+                "{name}: {got} vs {expected}"
+            );
+        }
+    }
+
+    #[test]
+    fn testing_pattern_capacity_bounds_memory() {
+        let txs = history();
+        let mut stream = FeaturePipeline::new().stream().pattern_capacity(10);
+        stream.push_chunk(&txs);
+        assert_eq!(stream.patterns.len(), 10);
+        let streamed = stream.finalize();
+        // Buffer-free features are unaffected.
+        let slice = FeaturePipeline::new().extract(&txs);
+        assert_eq!(
+            streamed.get(names::TRANSACTION_COUNT),
+            slice.get(names::TRANSACTION_COUNT)
+        );
+        assert_eq!(
+            streamed.get(names::TOTAL_DEBIT),
+            slice.get(names::TOTAL_DEBIT)
+        );
+    }
+
+    #[test]
+    fn testing_sketch_median() {
+        let mut sketch = QuantileSketch::default();
+        for v in [0.0, 10.0, 20.0, 30.0, 1_000.0] {
+            sketch.insert(v);
+        }
+        assert!((sketch.median() - 20.0).abs() < 0.02);
+        sketch.insert(40.0);
+        assert!((sketch.median() - 25.0).abs() < 0.03);
+    }
+}
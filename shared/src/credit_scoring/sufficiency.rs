@@ -0,0 +1,114 @@
+use super::CreditScore;
+use super::features::{FeatureVector, names};
+use crate::result::{Error, Result};
+use serde::{Deserialize, Serialize};
+
+/// What to do with a history below the minimums.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum InsufficientHistory {
+    /// Return an error instead of a score.
+    Refuse,
+    /// Score anyway, flagged as a thin file with reduced confidence.
+    #[default]
+    ThinFile,
+}
+
+/// Minimum history needed for a score to be trusted.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct SufficiencyRules {
+    pub min_months: u32,
+    pub min_active_days: u32,
+    pub min_transactions: u32,
+    /// Longest run of months without a transaction we tolerate.
+    pub max_gap_months: u32,
+    #[serde(default)]
+    pub on_insufficient: InsufficientHistory,
+}
+
+impl Default for SufficiencyRules {
+    fn default() -> Self {
+        Self {
+            min_months: 3,
+            min_active_days: 20,
+            min_transactions: 30,
+            max_gap_months: 1,
+            on_insufficient: InsufficientHistory::default(),
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct SufficiencyCheck {
+    pub reasons: Vec<String>,
+    /// 1 when every minimum is met, otherwise the product of how far each
+    /// failed check got towards its minimum.
+    pub confidence: f64,
+}
+
+impl SufficiencyCheck {
+    pub fn is_sufficient(&self) -> bool {
+        self.reasons.is_empty()
+    }
+}
+
+impl SufficiencyRules {
+    pub fn check(&self, features: &FeatureVector) -> SufficiencyCheck {
+        let mut reasons = Vec::new();
+        let mut confidence = 1.0;
+        let mut minimum = |actual: f64, required: u32, what: &str| {
+            if actual < required as f64 {
+                reasons.push(format!("Only {actual} {what}, {required} needed"));
+                confidence *= actual / required as f64;
+            }
+        };
+        minimum(
+            features.get(names::MONTHS_SPANNED),
+            self.min_months,
+            "months of history",
+        );
+        minimum(
+            features.get(names::ACTIVE_DAYS),
+            self.min_active_days,
+            "active days",
+        );
+        minimum(
+            features.get(names::TRANSACTION_COUNT),
+            self.min_transactions,
+            "transactions",
+        );
+
+        let gap = features.get(names::LONGEST_GAP_MONTHS);
+        if gap > self.max_gap_months as f64 {
+            reasons.push(format!(
+                "{gap} months without transactions, at most {} allowed",
+                self.max_gap_months
+            ));
+            confidence *= (self.max_gap_months as f64 + 1.0) / (gap + 1.0);
+        }
+
+        SufficiencyCheck {
+            reasons,
+            confidence,
+        }
+    }
+
+    /// Applies the gate to a computed score: an error when refusing,
+    /// otherwise the score with the thin-file flag and reasons filled in.
+    pub fn apply(&self, features: &FeatureVector, mut score: CreditScore) -> Result<CreditScore> {
+        let check = self.check(features);
+        if check.is_sufficient() {
+            return Ok(score);
+        }
+        if self.on_insufficient == InsufficientHistory::Refuse {
+            return Err(
+                Error::user("There isn't enough transaction history to score.")
+                    .add_meta_x("reasons", &check.reasons.join("; ")),
+            );
+        }
+        score.thin_file = true;
+        score.confidence = check.confidence;
+        score.data_warnings = check.reasons;
+        score.factors.push("Thin File".to_string());
+        Ok(score)
+    }
+}
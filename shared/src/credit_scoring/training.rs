@@ -0,0 +1,521 @@
+use super::features::{FeaturePipeline, FeatureVector};
+use super::scorecard::{Bin, Characteristic, PointsScaling, Scorecard};
+use crate::openbank::Transaction;
+use crate::result::{Error, Result};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeSet;
+
+/// Added to every good and bad count so empty bins keep a finite WoE.
+const SMOOTHING: f64 = 0.5;
+
+/// One customer's history with its outcome.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct LabelledHistory {
+    #[serde(default)]
+    pub customer_id: String,
+    pub defaulted: bool,
+    pub transactions: Vec<Transaction>,
+}
+
+impl LabelledHistory {
+    /// Reads a JSON array or JSON lines, one history per line.
+    pub fn parse_many(text: &str) -> Result<Vec<Self>> {
+        if text.trim_start().starts_with('[') {
+            return Ok(serde_json::from_str(text)?);
+        }
+        text.lines()
+            .enumerate()
+            .filter(|(_, line)| !line.trim().is_empty())
+            .map(|(i, line)| {
+                serde_json::from_str(line).map_err(|e| {
+                    Error::user("A labelled history could not be read.")
+                        .add_meta_x("line", &(i + 1).to_string())
+                        .add_meta_x("error", &e.to_string())
+                })
+            })
+            .collect()
+    }
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct TrainingOptions {
+    pub name: String,
+    /// Features to consider; every feature seen when empty.
+    pub candidates: Vec<String>,
+    /// Quantile bins to start from before merging.
+    pub initial_bins: usize,
+    /// Smallest share of samples a bin may hold.
+    pub min_bin_share: f64,
+    /// Features below this information value are dropped.
+    pub min_iv: f64,
+    pub max_features: usize,
+    /// L2 penalty on the coefficients (not the intercept).
+    pub l2: f64,
+    pub scaling: PointsScaling,
+}
+
+impl Default for TrainingOptions {
+    fn default() -> Self {
+        Self {
+            name: "Trained scorecard".into(),
+            candidates: Vec::new(),
+            initial_bins: 10,
+            min_bin_share: 0.05,
+            min_iv: 0.02,
+            max_features: 12,
+            l2: 1e-3,
+            scaling: PointsScaling::default(),
+        }
+    }
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct BinReport {
+    pub label: String,
+    pub count: usize,
+    pub defaults: usize,
+    pub woe: f64,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct FeatureReport {
+    pub feature: String,
+    /// Information value over the final bins.
+    pub iv: f64,
+    pub selected: bool,
+    pub coefficient: Option<f64>,
+    pub bins: Vec<BinReport>,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct TrainingReport {
+    pub samples: usize,
+    pub defaults: usize,
+    pub intercept: f64,
+    /// Area under the ROC curve on the training data.
+    pub auc: f64,
+    /// Every candidate, highest IV first.
+    pub features: Vec<FeatureReport>,
+}
+
+/// Runs the feature pipeline over each history, then `train_from_features`.
+pub fn train(
+    histories: &[LabelledHistory],
+    pipeline: &FeaturePipeline,
+    options: &TrainingOptions,
+) -> Result<(Scorecard, TrainingReport)> {
+    let samples: Vec<(FeatureVector, bool)> = histories
+        .iter()
+        .map(|h| (pipeline.extract(&h.transactions), h.defaulted))
+        .collect();
+    train_from_features(&samples, options)
+}
+
+/// Monotonic WoE binning per feature, IV-based selection, then a
+/// logistic regression of "good" on the selected WoE values.
+pub fn train_from_features(
+    samples: &[(FeatureVector, bool)],
+    options: &TrainingOptions,
+) -> Result<(Scorecard, TrainingReport)> {
+    let bads = samples.iter().filter(|(_, d)| *d).count();
+    if bads == 0 || bads == samples.len() {
+        return Err(Error::user(
+            "Training needs both defaulted and repaid customers.",
+        ));
+    }
+    let candidates: Vec<String> = if options.candidates.is_empty() {
+        samples
+            .iter()
+            .flat_map(|(fv, _)| fv.values.keys().cloned())
+            .collect::<BTreeSet<_>>()
+            .into_iter()
+            .collect()
+    } else {
+        options.candidates.clone()
+    };
+
+    let mut binned: Vec<(Characteristic, Vec<BinReport>, f64)> = candidates
+        .iter()
+        .filter_map(|feature| bin_feature(feature, samples, options))
+        .collect();
+    binned.sort_by(|a, b| b.2.total_cmp(&a.2));
+    let selected: Vec<usize> = (0..binned.len())
+        .filter(|&i| binned[i].2 >= options.min_iv)
+        .take(options.max_features)
+        .collect();
+    if selected.is_empty() {
+        return Err(Error::user("No feature separates defaulters well enough."));
+    }
+
+    // Design matrix of WoE values, intercept first.
+    let rows: Vec<Vec<f64>> = samples
+        .iter()
+        .map(|(fv, _)| {
+            std::iter::once(1.0)
+                .chain(selected.iter().map(|&i| woe_of(&binned[i].0, fv)))
+                .collect()
+        })
+        .collect();
+    let good: Vec<f64> = samples
+        .iter()
+        .map(|(_, d)| if *d { 0.0 } else { 1.0 })
+        .collect();
+    let beta = fit_logistic(&rows, &good, options.l2);
+
+    let scores: Vec<f64> = rows.iter().map(|x| dot(x, &beta)).collect();
+    let auc = auc(&scores, &good);
+
+    let mut characteristics = Vec::new();
+    let mut features = Vec::new();
+    for (i, (mut characteristic, bins, iv)) in binned.into_iter().enumerate() {
+        let coefficient = selected.iter().position(|&s| s == i).map(|p| beta[p + 1]);
+        if let Some(c) = coefficient {
+            characteristic.coefficient = c;
+            characteristics.push(characteristic.clone());
+        }
+        features.push(FeatureReport {
+            feature: characteristic.feature,
+            iv,
+            selected: coefficient.is_some(),
+            coefficient,
+            bins,
+        });
+    }
+
+    let card = Scorecard {
+        name: options.name.clone(),
+        version: "1".into(),
+        intercept: beta[0],
+        characteristics,
+        scaling: options.scaling.clone(),
+    };
+    let report = TrainingReport {
+        samples: samples.len(),
+        defaults: bads,
+        intercept: beta[0],
+        auc,
+        features,
+    };
+    Ok((card, report))
+}
+
+fn woe_of(characteristic: &Characteristic, fv: &FeatureVector) -> f64 {
+    characteristic.bin(fv).map(|b| b.woe).unwrap_or(0.0)
+}
+
+/// Half-open bin `[lower, upper)` being merged.
+struct Cell {
+    lower: Option<f64>,
+    upper: Option<f64>,
+    count: usize,
+    bads: usize,
+}
+
+impl Cell {
+    fn bad_rate(&self) -> f64 {
+        self.bads as f64 / self.count.max(1) as f64
+    }
+}
+
+/// Quantile bins merged until each is big enough and bad rates move in one
+/// direction. `None` when the feature doesn't vary.
+fn bin_feature(
+    feature: &str,
+    samples: &[(FeatureVector, bool)],
+    options: &TrainingOptions,
+) -> Option<(Characteristic, Vec<BinReport>, f64)> {
+    let mut present: Vec<(f64, bool)> = samples
+        .iter()
+        .filter_map(|(fv, d)| Some((fv.try_get(feature)?, *d)))
+        .filter(|(v, _)| v.is_finite())
+        .collect();
+    present.sort_by(|a, b| a.0.total_cmp(&b.0));
+    let (first, last) = (present.first()?.0, present.last()?.0);
+    if first == last {
+        return None;
+    }
+
+    let n = present.len();
+    let mut cuts: Vec<f64> = (1..options.initial_bins.max(2))
+        .map(|k| present[k * n / options.initial_bins.max(2)].0)
+        .filter(|c| *c > first)
+        .collect();
+    cuts.dedup();
+    let mut cells: Vec<Cell> = Vec::with_capacity(cuts.len() + 1);
+    let mut lower = None;
+    for upper in cuts.iter().copied().map(Some).chain(std::iter::once(None)) {
+        let inside = present
+            .iter()
+            .filter(|(v, _)| lower.is_none_or(|l| *v >= l) && upper.is_none_or(|u| *v < u));
+        let (count, bads) = inside.fold((0, 0), |(c, b), (_, d)| (c + 1, b + *d as usize));
+        cells.push(Cell {
+            lower,
+            upper,
+            count,
+            bads,
+        });
+        lower = upper;
+    }
+
+    let min_count = ((samples.len() as f64 * options.min_bin_share).ceil() as usize).max(1);
+    while cells.len() > 1 {
+        if let Some(i) = cells.iter().position(|c| c.count < min_count) {
+            let at = if i + 1 < cells.len() { i } else { i - 1 };
+            merge(&mut cells, at);
+            continue;
+        }
+        let rising = cells.last()?.bad_rate() >= cells.first()?.bad_rate();
+        let violation = cells.windows(2).position(|w| {
+            if rising {
+                w[1].bad_rate() < w[0].bad_rate()
+            } else {
+                w[1].bad_rate() > w[0].bad_rate()
+            }
+        });
+        match violation {
+            Some(i) => merge(&mut cells, i),
+            None => break,
+        }
+    }
+
+    let total_bads = samples.iter().filter(|(_, d)| *d).count() as f64;
+    let total_goods = samples.len() as f64 - total_bads;
+    let woe = |count: usize, bads: usize| {
+        let goods = (count - bads) as f64;
+        ((goods + SMOOTHING) / (total_goods + SMOOTHING)).ln()
+            - ((bads as f64 + SMOOTHING) / (total_bads + SMOOTHING)).ln()
+    };
+    let iv_part = |count: usize, bads: usize, woe: f64| {
+        let goods = (count - bads) as f64;
+        (goods / total_goods - bads as f64 / total_bads) * woe
+    };
+
+    let mut bins = Vec::new();
+    let mut reports = Vec::new();
+    let mut iv = 0.0;
+    for cell in &cells {
+        let w = woe(cell.count, cell.bads);
+        iv += iv_part(cell.count, cell.bads, w);
+        let label = match (cell.lower, cell.upper) {
+            (None, None) => "all".to_string(),
+            (None, Some(u)) => format!("< {u}"),
+            (Some(l), None) => format!(">= {l}"),
+            (Some(l), Some(u)) => format!("{l} to {u}"),
+        };
+        reports.push(BinReport {
+            label: label.clone(),
+            count: cell.count,
+            defaults: cell.bads,
+            woe: w,
+        });
+        bins.push(Bin {
+            label,
+            min: cell.lower,
+            max: cell.upper,
+            missing: false,
+            woe: w,
+        });
+    }
+
+    let missing = samples.len() - n;
+    if missing > 0 {
+        let bads = samples
+            .iter()
+            .filter(|(fv, d)| *d && fv.try_get(feature).is_none())
+            .count();
+        let w = woe(missing, bads);
+        iv += iv_part(missing, bads, w);
+        reports.push(BinReport {
+            label: "missing".into(),
+            count: missing,
+            defaults: bads,
+            woe: w,
+        });
+        bins.push(Bin {
+            label: "missing".into(),
+            min: None,
+            max: None,
+            missing: true,
+            woe: w,
+        });
+    }
+
+    let characteristic = Characteristic {
+        feature: feature.to_string(),
+        coefficient: 0.0,
+        bins,
+    };
+    Some((characteristic, reports, iv))
+}
+
+/// Folds cell `i + 1` into cell `i`.
+fn merge(cells: &mut Vec<Cell>, i: usize) {
+    let next = cells.remove(i + 1);
+    let cell = &mut cells[i];
+    cell.upper = next.upper;
+    cell.count += next.count;
+    cell.bads += next.bads;
+}
+
+fn dot(a: &[f64], b: &[f64]) -> f64 {
+    a.iter().zip(b).map(|(x, y)| x * y).sum()
+}
+
+fn sigmoid(z: f64) -> f64 {
+    1.0 / (1.0 + (-z).exp())
+}
+
+/// Newton-Raphson (IRLS) with a ridge penalty, intercept unpenalised.
+/// Callers check `y` holds both classes; with one the intercept diverges.
+pub(crate) fn fit_logistic(rows: &[Vec<f64>], y: &[f64], l2: f64) -> Vec<f64> {
+    let k = rows.first().map_or(0, Vec::len);
+    let mut beta = vec![0.0; k];
+    for _ in 0..50 {
+        let mut gradient = vec![0.0; k];
+        let mut hessian = vec![vec![0.0; k]; k];
+        for (x, target) in rows.iter().zip(y) {
+            let p = sigmoid(dot(x, &beta));
+            let w = p * (1.0 - p);
+            for i in 0..k {
+                gradient[i] += (target - p) * x[i];
+                for j in 0..k {
+                    hessian[i][j] += w * x[i] * x[j];
+                }
+            }
+        }
+        for i in 1..k {
+            gradient[i] -= l2 * beta[i];
+            hessian[i][i] += l2;
+        }
+        let Some(step) = solve(hessian, gradient) else {
+            break;
+        };
+        for (b, s) in beta.iter_mut().zip(&step) {
+            *b += s;
+        }
+        if step.iter().all(|s| s.abs() < 1e-9) {
+            break;
+        }
+    }
+    beta
+}
+
+/// Gaussian elimination with partial pivoting; `None` when singular.
+fn solve(mut a: Vec<Vec<f64>>, mut b: Vec<f64>) -> Option<Vec<f64>> {
+    let n = b.len();
+    for col in 0..n {
+        let pivot = (col..n).max_by(|&i, &j| a[i][col].abs().total_cmp(&a[j][col].abs()))?;
+        if a[pivot][col].abs() < 1e-12 {
+            return None;
+        }
+        a.swap(col, pivot);
+        b.swap(col, pivot);
+        let (upper, lower) = a.split_at_mut(col + 1);
+        let pivot_row = &upper[col];
+        for (offset, row) in lower.iter_mut().enumerate() {
+            let f = row[col] / pivot_row[col];
+            for (cell, p) in row[col..].iter_mut().zip(&pivot_row[col..]) {
+                *cell -= f * p;
+            }
+            b[col + 1 + offset] -= f * b[col];
+        }
+    }
+    let mut x = vec![0.0; n];
+    for row in (0..n).rev() {
+        let rest: f64 = (row + 1..n).map(|c| a[row][c] * x[c]).sum();
+        x[row] = (b[row] - rest) / a[row][row];
+    }
+    Some(x)
+}
+
+/// Probability a random good outscores a random bad, ties counting half.
+fn auc(scores: &[f64], good: &[f64]) -> f64 {
+    let mut ranked: Vec<(f64, f64)> = scores.iter().copied().zip(good.iter().copied()).collect();
+    ranked.sort_by(|a, b| a.0.total_cmp(&b.0));
+    let goods = good.iter().sum::<f64>();
+    let bads = good.len() as f64 - goods;
+    let mut rank_sum = 0.0;
+    let mut i = 0;
+    while i < ranked.len() {
+        let mut j = i;
+        while j < ranked.len() && ranked[j].0 == ranked[i].0 {
+            j += 1;
+        }
+        // Average 1-based rank of the tied run.
+        let rank = (i + j + 1) as f64 / 2.0;
+        rank_sum += ranked[i..j].iter().map(|(_, g)| g * rank).sum::<f64>();
+        i = j;
+    }
+    (rank_sum - goods * (goods + 1.0) / 2.0) / (goods * bads)
+}
+
+#[cfg(test)]
+mod test_training {
+    use super::*;
+
+    /// Defaults get likelier as `success_rate` drops; `noise` says nothing.
+    fn samples() -> Vec<(FeatureVector, bool)> {
+        let mut seed: u64 = 42;
+        let mut next = || {
+            seed = seed
+                .wrapping_mul(6364136223846793005)
+                .wrapping_add(1442695040888963407);
+            (seed >> 33) as f64 / (1u64 << 31) as f64
+        };
+        (0..600)
+            .map(|_| {
+                let rate = next();
+                let mut fv = FeatureVector::new();
+                fv.set("success_rate", rate);
+                fv.set("noise", next());
+                let defaulted = next() < 0.6 - 0.5 * rate;
+                (fv, defaulted)
+            })
+            .collect()
+    }
+
+    #[test]
+    fn testing_trains_monotonic_scorecard() {
+        let (card, report) = train_from_features(&samples(), &TrainingOptions::default()).unwrap();
+
+        let rate = report
+            .features
+            .iter()
+            .find(|f| f.feature == "success_rate")
+            .unwrap();
+        assert!(rate.selected);
+        assert!(rate.iv > 0.2);
+        // Higher success rates, safer customers.
+        assert!(rate.bins.windows(2).all(|w| w[1].woe >= w[0].woe));
+        assert!(report.features[0].feature == "success_rate");
+        // WoE inputs give coefficients near one.
+        assert!((rate.coefficient.unwrap() - 1.0).abs() < 0.3);
+        assert!(report.auc > 0.65);
+
+        let noise = report
+            .features
+            .iter()
+            .find(|f| f.feature == "noise")
+            .unwrap();
+        assert!(noise.iv < rate.iv);
+
+        let text = card.to_toml().unwrap();
+        assert_eq!(Scorecard::from_toml(&text).unwrap(), card);
+    }
+
+    #[test]
+    fn testing_needs_both_outcomes() {
+        let all_good: Vec<_> = samples().into_iter().map(|(fv, _)| (fv, false)).collect();
+        assert!(train_from_features(&all_good, &TrainingOptions::default()).is_err());
+    }
+
+    #[test]
+    fn testing_reads_json_lines() {
+        let text = r#"{"customer_id":"a","defaulted":true,"transactions":[]}
+
+{"customer_id":"b","defaulted":false,"transactions":[]}"#;
+        let histories = LabelledHistory::parse_many(text).unwrap();
+        assert_eq!(histories.len(), 2);
+        assert!(histories[0].defaulted);
+    }
+}
@@ -1,4 +1,8 @@
+pub mod analysis;
 pub mod app;
 pub mod credit_scoring;
 pub mod openbank;
 pub mod result;
+
+#[cfg(test)]
+pub(crate) mod test_fixtures;
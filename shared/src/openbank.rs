@@ -1,3 +1,5 @@
+use crate::result::{Error, Result};
+use chrono::{DateTime, NaiveDate, NaiveDateTime};
 use serde::{Deserialize, Serialize};
 
 #[derive(Default, Clone, Serialize, Deserialize)]
@@ -10,13 +12,28 @@ pub struct TransactionsResponse {
     pub data: TransactionsData,
 }
 
+impl TransactionsResponse {
+    /// Reads a statement: a full `TransactionsResponse` body or a bare JSON
+    /// array of transactions.
+    pub fn parse_transactions(text: &str) -> Result<Vec<Transaction>> {
+        let parsed = if text.trim_start().starts_with('[') {
+            serde_json::from_str(text)
+        } else {
+            serde_json::from_str::<Self>(text).map(|r| r.data.transactions)
+        };
+        parsed.map_err(|e| {
+            Error::user("The statement could not be read.").add_meta_x("error", &e.to_string())
+        })
+    }
+}
+
 #[derive(Debug, Serialize, Deserialize)]
 pub struct TransactionsData {
     pub summary: Summary,
     pub transactions: Vec<Transaction>,
 }
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Clone, Debug, Serialize, Deserialize)]
 pub struct Transaction {
     pub id: String,                  // "1234567890",
     pub amount: f64,                 // 1000.24,
@@ -32,6 +49,48 @@ pub struct Transaction {
     pub status: String,              // "SUCESSFUL | FAILED"
 }
 
+impl Transaction {
+    pub fn is_failed(&self) -> bool {
+        self.status == "FAILED"
+    }
+
+    pub fn is_credit(&self) -> bool {
+        self.debit_credit == "CREDIT"
+    }
+
+    pub fn is_debit(&self) -> bool {
+        self.debit_credit == "DEBIT"
+    }
+
+    /// Parses `transaction_time`, falling back to `value_date` when the
+    /// provider only sends a date (or sends something we can't read).
+    pub fn timestamp(&self) -> Option<NaiveDateTime> {
+        parse_timestamp(&self.transaction_time).or_else(|| parse_timestamp(&self.value_date))
+    }
+
+    pub fn date(&self) -> Option<NaiveDate> {
+        self.timestamp().map(|t| t.date())
+    }
+}
+
+/// Accepts "2019-01-02T19:58:47.1234567", RFC 3339 and plain "2019-01-02".
+fn parse_timestamp(raw: &str) -> Option<NaiveDateTime> {
+    let raw = raw.trim();
+    NaiveDateTime::parse_from_str(raw, "%Y-%m-%dT%H:%M:%S%.f")
+        .or_else(|_| NaiveDateTime::parse_from_str(raw, "%Y-%m-%d %H:%M:%S%.f"))
+        .ok()
+        .or_else(|| {
+            DateTime::parse_from_rfc3339(raw)
+                .ok()
+                .map(|d| d.naive_utc())
+        })
+        .or_else(|| {
+            NaiveDate::parse_from_str(raw, "%Y-%m-%d")
+                .ok()
+                .and_then(|d| d.and_hms_opt(0, 0, 0))
+        })
+}
+
 #[derive(Debug, Serialize, Deserialize)]
 pub struct Summary {
     account_number: String,    // "0123456789",
@@ -51,9 +110,58 @@ pub struct Summary {
 }
 
 #[derive(Debug, Serialize, Deserialize)]
-struct CustomerProperty {
+pub struct CustomerProperty {
     id: String,          // "Some random ID",
     description: String, // "Some random text",
     r#type: String,      // "Some random type",
     value: String,       // "Some value"
 }
+
+#[cfg(test)]
+mod test_openbank {
+    use super::*;
+    use crate::test_fixtures::tx;
+    use serde_json::json;
+
+    #[test]
+    fn testing_parse_transactions() {
+        let transactions = json!([
+            tx("1", "2023-01-05", "CREDIT", 1_000.0, "Salary"),
+            tx("2", "2023-01-06", "DEBIT", 200.0, "Airtime"),
+        ]);
+        let bare = TransactionsResponse::parse_transactions(&transactions.to_string()).unwrap();
+        assert_eq!(bare.len(), 2);
+        assert_eq!(bare[1].id, "2");
+
+        let response = json!({
+            "status": "00",
+            "message": "The process was completed successully",
+            "data": {
+                "summary": {
+                    "account_number": "0123456789",
+                    "currency_code": "NGN",
+                    "from": "2023-01-01",
+                    "to": "2023-01-31",
+                    "first_transaction": "2023-01-05",
+                    "last_transaction": "2023-01-06",
+                    "opening_balance": 0.0,
+                    "closing_balance": 800.0,
+                    "total_debit_count": 1,
+                    "total_credit_count": 1,
+                    "total_debit_value": 200.0,
+                    "total_credit_value": 1_000.0,
+                    "pages": 1,
+                    "records_per_page": 100
+                },
+                "transactions": transactions
+            }
+        });
+        let full = TransactionsResponse::parse_transactions(&response.to_string()).unwrap();
+        assert_eq!(full.len(), 2);
+        assert_eq!(full[0].narration, "Salary");
+
+        assert!(TransactionsResponse::parse_transactions("[{\"id\": \"1\"}]").is_err());
+        assert!(TransactionsResponse::parse_transactions("{\"status\": \"00\"}").is_err());
+        assert!(TransactionsResponse::parse_transactions("not json").is_err());
+    }
+}
@@ -51,7 +51,12 @@ impl Error {
     }
 
     pub fn has_meta(&self) -> bool {
-        self.meta.len() > 0
+        !self.meta.is_empty()
+    }
+
+    /// The message meant for the user.
+    pub fn note(&self) -> &str {
+        &self.note
     }
 }
 
@@ -88,11 +93,20 @@ impl From<std::num::ParseIntError> for Error {
     }
 }
 
-// impl From<std::io::Error> for Error {
-//     fn from(e: std::io::Error) -> Self {
-//         let mut err = Error::tech("");
-//         err.add_meta("from", "io_err");
-//         err.add_meta("error", &e.to_string());
-//         err
-//     }
-// }
+impl From<std::io::Error> for Error {
+    fn from(e: std::io::Error) -> Self {
+        let mut err = Error::tech("");
+        err.add_meta("from", "io_err");
+        err.add_meta("error", &e.to_string());
+        err
+    }
+}
+
+impl From<serde_json::Error> for Error {
+    fn from(e: serde_json::Error) -> Self {
+        let mut err = Error::tech("");
+        err.add_meta("from", "serde_json_err");
+        err.add_meta("error", &e.to_string());
+        err
+    }
+}
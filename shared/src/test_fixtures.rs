@@ -0,0 +1,23 @@
+//! Shared builders for unit tests.
+
+use crate::openbank::Transaction;
+
+/// A successful mobile transfer booked on `date`, with a zero balance.
+/// Tests that care about other fields override them with struct update
+/// syntax.
+pub(crate) fn tx(id: &str, date: &str, dc: &str, amount: f64, narration: &str) -> Transaction {
+    Transaction {
+        id: id.into(),
+        amount,
+        channel: "MOBILE".into(),
+        authorization_token: "x".into(),
+        transaction_type: "TRF".into(),
+        debit_credit: dc.into(),
+        narration: narration.into(),
+        reference: "ref1".into(),
+        transaction_time: date.into(),
+        value_date: date.into(),
+        balance_after: 0.0,
+        status: "SUCCESSFUL".into(),
+    }
+}
@@ -1,4 +1,6 @@
 use shared::app::{AppState, SharedAppState};
+use shared::openbank::{Transaction, TransactionsResponse};
+use shared::result::Result;
 use tauri::State;
 
 // Learn more about Tauri commands at https://tauri.app/develop/calling-rust/
@@ -12,3 +14,10 @@ pub fn openbank_set_config() {
 pub fn openbank_get_transactions() -> Vec<String> {
     unimplemented!()
 }
+
+/// Reads a statement file: a `TransactionsResponse` body or a JSON array of
+/// transactions.
+#[tauri::command]
+pub fn openbank_load_statement(path: String) -> Result<Vec<Transaction>> {
+    TransactionsResponse::parse_transactions(&std::fs::read_to_string(path)?)
+}
@@ -1,16 +1,296 @@
+use chrono::{Days, Local, Utc};
+use shared::analysis::{
+    BillDue, CashFlowReport, Categorisation, Categoriser, Category, GamblingReport, IncomeReport,
+    LendingReport, LiquidityReport, NarrationClassifier, ObligationsReport, analyse_cash_flow,
+    analyse_liquidity, bill_calendar, detect_gambling, detect_income, detect_lending,
+    detect_obligations,
+};
 use shared::app::{AppState, SharedAppState};
-use tauri::State;
+use shared::credit_scoring::engine::EngineInfo;
+use shared::credit_scoring::{
+    CreditScore, FeatureVector, HorizonSummary, ImprovementPlan, ModelRegistry, OnnxSpec,
+    PlanProgress, Scenario, ScoreHistory, ScoreRecord, ScoreTrend, Scorecard, ScoringConfig,
+    ScoringModel, Simulation, TreeEnsemble, score_horizons, simulate,
+};
+use shared::openbank::Transaction;
+use shared::result::{Error, Result};
+use std::path::PathBuf;
+use std::sync::Mutex;
+use tauri::{AppHandle, Manager, State};
 
 // Learn more about Tauri commands at https://tauri.app/develop/calling-rust/
 
 #[tauri::command]
-pub fn scoring_set_config(name: &str) -> String {
-    unimplemented!()
+pub fn scoring_set_config(state: State<'_, SharedAppState>, config: ScoringConfig) -> AppState {
+    let mut state = state.write().unwrap();
+    state.scoring_config = config;
+    state.clone()
 }
 
+/// Switches the config to the scorecard in a `.toml` or `.json` file.
 #[tauri::command]
-pub fn scoring_calculate_score() {
-    unimplemented!()
+pub fn scoring_load_scorecard(state: State<'_, SharedAppState>, path: String) -> Result<AppState> {
+    let card = Scorecard::load(&path)?;
+    let mut state = state.write().unwrap();
+    state.scoring_config.model = ScoringModel::Scorecard { card };
+    Ok(state.clone())
+}
+
+/// Switches the config to a tree model exported from XGBoost or LightGBM.
+#[tauri::command]
+pub fn scoring_load_gbdt(state: State<'_, SharedAppState>, path: String) -> Result<AppState> {
+    let model = TreeEnsemble::load(&path)?;
+    let mut state = state.write().unwrap();
+    state.scoring_config.model = ScoringModel::Gbdt { model };
+    Ok(state.clone())
+}
+
+/// Switches the config to an ONNX model, checking its schema first.
+#[tauri::command]
+pub fn scoring_load_onnx(
+    state: State<'_, SharedAppState>,
+    registry: State<'_, ModelRegistry>,
+    spec: OnnxSpec,
+) -> Result<AppState> {
+    let model = ScoringModel::Onnx { spec };
+    registry.resolve(&model)?;
+    let mut state = state.write().unwrap();
+    state.scoring_config.model = model;
+    Ok(state.clone())
+}
+
+#[tauri::command]
+pub fn scoring_list_models(registry: State<'_, ModelRegistry>) -> Vec<EngineInfo> {
+    registry.list()
+}
+
+/// Held while score_history.json is read, added to and saved, so
+/// concurrent scores don't drop each other's records.
+#[derive(Default)]
+pub struct ScoreHistoryLock(Mutex<()>);
+
+#[tauri::command]
+pub fn scoring_calculate_score(
+    app: AppHandle,
+    state: State<'_, SharedAppState>,
+    registry: State<'_, ModelRegistry>,
+    history_lock: State<'_, ScoreHistoryLock>,
+    transactions: Vec<Transaction>,
+) -> Result<CreditScore> {
+    let config = state.read().unwrap().scoring_config.clone();
+    let engine = registry.resolve(&config.model)?;
+    let score = engine.assess(&transactions, &config)?;
+
+    let path = app_data_file(&app, "score_history.json")?;
+    let _guard = history_lock.0.lock().unwrap();
+    let mut history = ScoreHistory::load_or_default(&path)?;
+    history.record(
+        engine.as_ref(),
+        &config,
+        &transactions,
+        score.clone(),
+        Utc::now(),
+    );
+    history.save(&path)?;
+    Ok(score)
+}
+
+/// Scores the last 30, 90, 180 and 365 days (per `horizons` in the
+/// config) and whether the score is trending up or down.
+#[tauri::command]
+pub fn scoring_calculate_horizons(
+    state: State<'_, SharedAppState>,
+    registry: State<'_, ModelRegistry>,
+    transactions: Vec<Transaction>,
+) -> Result<HorizonSummary> {
+    let config = state.read().unwrap().scoring_config.clone();
+    let engine = registry.resolve(&config.model)?;
+    score_horizons(engine.as_ref(), &transactions, &config)
+}
+
+/// Projected score of the history under hypothetical changes.
+#[tauri::command]
+pub fn scoring_simulate(
+    state: State<'_, SharedAppState>,
+    registry: State<'_, ModelRegistry>,
+    transactions: Vec<Transaction>,
+    scenarios: Vec<Scenario>,
+) -> Result<Simulation> {
+    let config = state.read().unwrap().scoring_config.clone();
+    let engine = registry.resolve(&config.model)?;
+    simulate(engine.as_ref(), &transactions, &scenarios, &config)
+}
+
+/// Makes a new improvement plan from the history and saves it for
+/// `scoring_plan_progress`.
+#[tauri::command]
+pub fn scoring_improvement_plan(
+    app: AppHandle,
+    state: State<'_, SharedAppState>,
+    registry: State<'_, ModelRegistry>,
+    transactions: Vec<Transaction>,
+) -> Result<ImprovementPlan> {
+    let config = state.read().unwrap().scoring_config.clone();
+    let engine = registry.resolve(&config.model)?;
+    let plan = ImprovementPlan::generate(engine.as_ref(), &transactions, &config)?;
+    plan.save(app_data_file(&app, "improvement_plan.json")?)?;
+    Ok(plan)
+}
+
+/// Progress against the saved plan, given the history including anything
+/// new since it was made.
+#[tauri::command]
+pub fn scoring_plan_progress(
+    app: AppHandle,
+    state: State<'_, SharedAppState>,
+    registry: State<'_, ModelRegistry>,
+    transactions: Vec<Transaction>,
+) -> Result<PlanProgress> {
+    let plan = ImprovementPlan::load(app_data_file(&app, "improvement_plan.json")?)?
+        .ok_or_else(|| Error::notfound("No improvement plan has been made yet."))?;
+    let config = state.read().unwrap().scoring_config.clone();
+    let engine = registry.resolve(&config.model)?;
+    plan.progress(engine.as_ref(), &transactions, &config)
+}
+
+/// Features the selected model sees, for audit.
+#[tauri::command]
+pub fn scoring_extract_features(
+    state: State<'_, SharedAppState>,
+    registry: State<'_, ModelRegistry>,
+    transactions: Vec<Transaction>,
+) -> Result<FeatureVector> {
+    let config = state.read().unwrap().scoring_config.clone();
+    Ok(registry
+        .resolve(&config.model)?
+        .extract(&transactions, &config))
+}
+
+#[tauri::command]
+pub fn scoring_categorise(
+    state: State<'_, SharedAppState>,
+    transactions: Vec<Transaction>,
+) -> Vec<Categorisation> {
+    let categoriser = Categoriser::for_config(&state.read().unwrap().scoring_config);
+    transactions
+        .iter()
+        .map(|tx| categoriser.categorise_detail(tx))
+        .collect()
+}
+
+#[tauri::command]
+pub fn scoring_income_report(
+    state: State<'_, SharedAppState>,
+    transactions: Vec<Transaction>,
+) -> IncomeReport {
+    let categoriser = Categoriser::for_config(&state.read().unwrap().scoring_config);
+    detect_income(&transactions, &categoriser)
+}
+
+#[tauri::command]
+pub fn scoring_obligations(
+    state: State<'_, SharedAppState>,
+    transactions: Vec<Transaction>,
+) -> ObligationsReport {
+    let categoriser = Categoriser::for_config(&state.read().unwrap().scoring_config);
+    obligations(&transactions, &categoriser)
+}
+
+/// Bills expected over the next `days` days, from today.
+#[tauri::command]
+pub fn scoring_bill_calendar(
+    state: State<'_, SharedAppState>,
+    transactions: Vec<Transaction>,
+    days: u32,
+) -> Vec<BillDue> {
+    let categoriser = Categoriser::for_config(&state.read().unwrap().scoring_config);
+    let report = obligations(&transactions, &categoriser);
+    let from = Local::now().date_naive();
+    let until = from
+        .checked_add_days(Days::new(days as u64))
+        .unwrap_or(from);
+    bill_calendar(&report.obligations, from, until)
+}
+
+/// Loans from digital lenders and microfinance banks found in `transactions`.
+#[tauri::command]
+pub fn scoring_lending_report(
+    state: State<'_, SharedAppState>,
+    transactions: Vec<Transaction>,
+) -> LendingReport {
+    let config = state.read().unwrap().scoring_config.clone();
+    let categoriser = Categoriser::for_config(&config);
+    detect_lending(&transactions, &categoriser, &config.lending)
+}
+
+/// Monthly income and expense series with stability metrics.
+#[tauri::command]
+pub fn scoring_cash_flow(transactions: Vec<Transaction>) -> CashFlowReport {
+    analyse_cash_flow(&transactions)
+}
+
+/// Daily balance series and low-balance metrics, using the configured
+/// threshold.
+#[tauri::command]
+pub fn scoring_liquidity(
+    state: State<'_, SharedAppState>,
+    transactions: Vec<Transaction>,
+) -> LiquidityReport {
+    let threshold = state
+        .read()
+        .unwrap()
+        .scoring_config
+        .liquidity
+        .low_balance_threshold;
+    analyse_liquidity(&transactions, threshold)
+}
+
+/// Betting activity in `transactions` and its graded risk.
+#[tauri::command]
+pub fn scoring_gambling_report(
+    state: State<'_, SharedAppState>,
+    transactions: Vec<Transaction>,
+) -> GamblingReport {
+    let categoriser = Categoriser::for_config(&state.read().unwrap().scoring_config);
+    detect_gambling(&transactions, &categoriser)
+}
+
+fn obligations(transactions: &[Transaction], categoriser: &Categoriser) -> ObligationsReport {
+    let monthly_income = detect_income(transactions, categoriser)
+        .primary()
+        .map(|p| p.monthly_amount())
+        .unwrap_or(0.0);
+    detect_obligations(transactions, categoriser, monthly_income)
+}
+
+/// Feeds user-confirmed categories to the on-device narration model,
+/// creating it on first use. Returns how many examples the model has seen.
+#[tauri::command]
+pub fn scoring_confirm_categories(
+    app: AppHandle,
+    state: State<'_, SharedAppState>,
+    examples: Vec<(Transaction, Category)>,
+) -> Result<u32> {
+    let configured = state
+        .read()
+        .unwrap()
+        .scoring_config
+        .narration_model_path
+        .clone();
+    let path = match configured {
+        Some(path) => PathBuf::from(path),
+        None => app_data_file(&app, "narration_model.json")?,
+    };
+
+    let mut model = NarrationClassifier::load_or_default(&path)?;
+    for (tx, category) in &examples {
+        model.learn(tx, *category);
+    }
+    model.save(&path)?;
+
+    state.write().unwrap().scoring_config.narration_model_path =
+        Some(path.to_string_lossy().into_owned());
+    Ok(model.example_count())
 }
 
 #[tauri::command]
@@ -18,9 +298,28 @@ pub fn scoring_share_score(id: &str) -> String {
     unimplemented!()
 }
 
+/// `name` in the app's data directory, which is created if need be.
+fn app_data_file(app: &AppHandle, name: &str) -> Result<PathBuf> {
+    let dir = app
+        .path()
+        .app_data_dir()
+        .map_err(|e| Error::tech("").add_meta_x("error", &e.to_string()))?;
+    std::fs::create_dir_all(&dir)?;
+    Ok(dir.join(name))
+}
+
+/// Every score computed so far, oldest first.
 #[tauri::command]
-pub fn scoring_get_scores() -> Vec<String> {
-    unimplemented!()
+pub fn scoring_get_scores(app: AppHandle) -> Result<Vec<ScoreRecord>> {
+    Ok(ScoreHistory::load_or_default(app_data_file(&app, "score_history.json")?)?.records)
+}
+
+#[tauri::command]
+pub fn scoring_score_trend(app: AppHandle) -> Result<ScoreTrend> {
+    Ok(
+        ScoreHistory::load_or_default(app_data_file(&app, "score_history.json")?)?
+            .trend(Utc::now()),
+    )
 }
 
 /*----VERIFICATION----*/
@@ -7,17 +7,39 @@ pub fn run() {
     tauri::Builder::default()
         .setup(|app| {
             app.manage(shared::app::AppState::new());
+            app.manage(shared::credit_scoring::ModelRegistry::default());
+            app.manage(commands::scoring::ScoreHistoryLock::default());
             Ok(())
         })
         .plugin(tauri_plugin_opener::init())
         .invoke_handler(tauri::generate_handler![
             commands::scoring::scoring_set_config,
+            commands::scoring::scoring_list_models,
+            commands::scoring::scoring_load_scorecard,
+            commands::scoring::scoring_load_gbdt,
+            commands::scoring::scoring_load_onnx,
             commands::scoring::scoring_calculate_score,
+            commands::scoring::scoring_calculate_horizons,
+            commands::scoring::scoring_simulate,
+            commands::scoring::scoring_improvement_plan,
+            commands::scoring::scoring_plan_progress,
+            commands::scoring::scoring_extract_features,
+            commands::scoring::scoring_categorise,
+            commands::scoring::scoring_confirm_categories,
+            commands::scoring::scoring_income_report,
+            commands::scoring::scoring_obligations,
+            commands::scoring::scoring_bill_calendar,
+            commands::scoring::scoring_lending_report,
+            commands::scoring::scoring_gambling_report,
+            commands::scoring::scoring_cash_flow,
+            commands::scoring::scoring_liquidity,
             commands::scoring::scoring_share_score,
             commands::scoring::scoring_get_scores,
+            commands::scoring::scoring_score_trend,
             commands::scoring::scoring_verify,
             commands::scoring::scoring_get_verifications,
             commands::openbank::openbank_set_config,
+            commands::openbank::openbank_load_statement,
             commands::get_state,
         ])
         .run(tauri::generate_context!())
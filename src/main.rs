@@ -3,6 +3,7 @@ mod ui;
 
 use dioxus::prelude::*;
 use dioxus_logger::tracing::Level;
+use shared::openbank::Transaction;
 use ui::Route;
 
 static CSS: Asset = asset!("/assets/styles.css");
@@ -10,6 +11,9 @@ static CSS: Asset = asset!("/assets/styles.css");
 fn main() {
     dioxus_logger::init(Level::INFO).expect("failed to init logger");
     launch(|| {
+        // Statement the user is working with, shared by every page.
+        use_context_provider(|| Signal::new(Vec::<Transaction>::new()));
+
         rsx! {
             link { rel: "stylesheet", href: CSS }
             Router::<Route> {}
@@ -1,11 +1,27 @@
 
 use serde::{Serialize, de::DeserializeOwned};
+use shared::result::Error;
 
 pub async fn invoke<T: DeserializeOwned, A: Serialize>(cmd: &str, args: &A) -> T {
     let args = serde_wasm_bindgen::to_value(&args).unwrap();
     serde_wasm_bindgen::from_value(binding::invoke(cmd, args).await).unwrap()
 }
 
+/// `invoke` for commands that return a `Result`: a rejected call comes back
+/// as the command's `Error` instead of panicking.
+pub async fn try_invoke<T: DeserializeOwned, A: Serialize>(
+    cmd: &str,
+    args: &A,
+) -> Result<T, Error> {
+    let args = serde_wasm_bindgen::to_value(&args).unwrap();
+    match binding::try_invoke(cmd, args).await {
+        Ok(value) => serde_wasm_bindgen::from_value(value).map_err(|e| {
+            Error::tech("The response could not be read.").add_meta_x("error", &e.to_string())
+        }),
+        Err(e) => Err(serde_wasm_bindgen::from_value(e).unwrap_or_else(|_| Error::tech(""))),
+    }
+}
+
 
 
 mod binding {
@@ -15,6 +31,9 @@ mod binding {
     extern "C" {
         #[wasm_bindgen(js_namespace = ["window", "__TAURI__", "core"])]
         pub async fn invoke(cmd: &str, args: JsValue) -> JsValue;
+
+        #[wasm_bindgen(js_namespace = ["window", "__TAURI__", "core"], js_name = invoke, catch)]
+        pub async fn try_invoke(cmd: &str, args: JsValue) -> Result<JsValue, JsValue>;
     }
 }
 
@@ -0,0 +1,83 @@
+#![allow(non_snake_case)]
+
+use crate::tauri;
+use crate::ui::statement::StatementLoader;
+use dioxus::prelude::*;
+use serde::Serialize;
+use shared::analysis::BillDue;
+use shared::openbank::Transaction;
+
+#[derive(Serialize)]
+struct BillCalendarArgs<'a> {
+    transactions: &'a [Transaction],
+    days: u32,
+}
+
+#[component]
+pub fn Bills() -> Element {
+    let transactions = use_context::<Signal<Vec<Transaction>>>();
+    let mut days = use_signal(|| 60u32);
+
+    let bills = use_resource(move || async move {
+        let transactions = transactions.read().clone();
+        if transactions.is_empty() {
+            return None;
+        }
+        let args = BillCalendarArgs {
+            transactions: &transactions,
+            days: days(),
+        };
+        Some(tauri::invoke::<Vec<BillDue>, _>("scoring_bill_calendar", &args).await)
+    });
+
+    rsx! {
+        div {
+            class: "container",
+            h1 { "Bill calendar" }
+            StatementLoader {}
+
+            div {
+                class: "row",
+                for d in [30u32, 60, 90] {
+                    button {
+                        key: "{d}",
+                        class: if days() == d { "active" } else { "" },
+                        onclick: move |_| days.set(d),
+                        "{d} days"
+                    }
+                }
+            }
+
+            match &*bills.read() {
+                None => rsx! { p { "Loading..." } },
+                Some(None) => rsx! { p { "Load a statement to see recurring payments." } },
+                Some(Some(list)) if list.is_empty() => rsx! {
+                    p { "No recurring payments are due in that window." }
+                },
+                Some(Some(list)) => rsx! {
+                    table {
+                        thead {
+                            tr {
+                                th { "Due" }
+                                th { "Payee" }
+                                th { "Kind" }
+                                th { "Amount" }
+                            }
+                        }
+                        tbody {
+                            for bill in list.iter() {
+                                tr {
+                                    key: "{bill.date}-{bill.counterparty}",
+                                    td { "{bill.date}" }
+                                    td { "{bill.counterparty}" }
+                                    td { "{bill.kind:?}" }
+                                    td { "NGN {bill.amount:.2}" }
+                                }
+                            }
+                        }
+                    }
+                },
+            }
+        }
+    }
+}
@@ -4,7 +4,12 @@ use dioxus::prelude::*;
 use serde::{Deserialize, Serialize};
 use crate::tauri;
 
+mod bills;
+mod simulator;
+mod statement;
 
+use bills::Bills;
+use simulator::Simulator;
 
 #[derive(Clone, Debug, PartialEq, Routable)]
 pub enum Route {
@@ -13,6 +18,12 @@ pub enum Route {
 
     #[route("/score/:id")]
     ScoreDetails{id:String},
+
+    #[route("/bills")]
+    Bills,
+
+    #[route("/simulator")]
+    Simulator,
 }
 
 
@@ -0,0 +1,161 @@
+#![allow(non_snake_case)]
+
+use crate::tauri;
+use crate::ui::statement::StatementLoader;
+use dioxus::prelude::*;
+use serde::Serialize;
+use shared::credit_scoring::{Scenario, Simulation};
+use shared::openbank::Transaction;
+
+#[derive(Serialize)]
+struct SimulateArgs<'a> {
+    transactions: &'a [Transaction],
+    scenarios: Vec<Scenario>,
+}
+
+#[component]
+pub fn Simulator() -> Element {
+    let transactions = use_context::<Signal<Vec<Transaction>>>();
+    let mut savings = use_signal(|| 0.0f64);
+    let mut min_balance = use_signal(|| 0.0f64);
+    let mut months = use_signal(|| 3u32);
+    let mut no_gambling = use_signal(|| false);
+    let mut no_failed = use_signal(|| false);
+
+    let simulation = use_resource(move || async move {
+        let transactions = transactions.read().clone();
+        if transactions.is_empty() {
+            return None;
+        }
+        let mut scenarios = Vec::new();
+        if savings() > 0.0 {
+            scenarios.push(Scenario::MonthlySavings { amount: savings() });
+        }
+        if no_gambling() {
+            scenarios.push(Scenario::RemoveGambling);
+        }
+        if no_failed() {
+            scenarios.push(Scenario::AvoidFailedTransactions);
+        }
+        if min_balance() > 0.0 && months() > 0 {
+            scenarios.push(Scenario::KeepMinBalance {
+                balance: min_balance(),
+                months: months(),
+            });
+        }
+        let args = SimulateArgs {
+            transactions: &transactions,
+            scenarios,
+        };
+        Some(tauri::try_invoke::<Simulation, _>("scoring_simulate", &args).await)
+    });
+
+    rsx! {
+        div {
+            class: "container",
+            h1 { "What if?" }
+            StatementLoader {}
+
+            div {
+                class: "row",
+                label { "Save each month (NGN)" }
+                input {
+                    r#type: "number",
+                    min: "0",
+                    value: "{savings}",
+                    oninput: move |e| savings.set(e.value().parse().unwrap_or_default())
+                }
+            }
+            div {
+                class: "row",
+                label { "Keep a balance of at least (NGN)" }
+                input {
+                    r#type: "number",
+                    min: "0",
+                    value: "{min_balance}",
+                    oninput: move |e| min_balance.set(e.value().parse().unwrap_or_default())
+                }
+                label { "for the last" }
+                input {
+                    r#type: "number",
+                    min: "1",
+                    value: "{months}",
+                    oninput: move |e| months.set(e.value().parse().unwrap_or_default())
+                }
+                label { "months" }
+            }
+            div {
+                class: "row",
+                label {
+                    input {
+                        r#type: "checkbox",
+                        checked: no_gambling(),
+                        onchange: move |e| no_gambling.set(e.checked())
+                    }
+                    "Stop gambling"
+                }
+                label {
+                    input {
+                        r#type: "checkbox",
+                        checked: no_failed(),
+                        onchange: move |e| no_failed.set(e.checked())
+                    }
+                    "Avoid failed transactions"
+                }
+            }
+
+            match &*simulation.read() {
+                None => rsx! { p { "Simulating..." } },
+                Some(None) => rsx! { p { "Load a statement to try out changes." } },
+                Some(Some(Err(e))) => {
+                    let message = e.note();
+                    rsx! { p { "{message}" } }
+                }
+                Some(Some(Ok(sim))) => rsx! {
+                    h2 {
+                        "{sim.base.total_score} → {sim.projected.total_score} "
+                        if sim.score_change >= 0 { "(+{sim.score_change})" } else { "({sim.score_change})" }
+                    }
+                    p { "Risk: {sim.base.risk_level} → {sim.projected.risk_level}" }
+                    table {
+                        thead {
+                            tr {
+                                th { "Component" }
+                                th { "Now" }
+                                th { "Projected" }
+                                th { "Effect" }
+                            }
+                        }
+                        tbody {
+                            for c in sim.component_changes.iter() {
+                                tr {
+                                    key: "{c.name}",
+                                    td { "{c.name}" }
+                                    td { "{c.base:.2}" }
+                                    td { "{c.projected:.2}" }
+                                    td { "{c.change:+.2}" }
+                                }
+                            }
+                        }
+                    }
+                    if !sim.factors_lost.is_empty() {
+                        h3 { "Factors that would no longer apply" }
+                        ul {
+                            for f in sim.factors_lost.iter() {
+                                li { key: "{f}", "{f}" }
+                            }
+                        }
+                    }
+                    if !sim.factors_gained.is_empty() {
+                        h3 { "Factors that would apply" }
+                        ul {
+                            for f in sim.factors_gained.iter() {
+                                li { key: "{f}", "{f}" }
+                            }
+                        }
+                    }
+                },
+            }
+        }
+    }
+}
@@ -0,0 +1,55 @@
+#![allow(non_snake_case)]
+
+use crate::tauri;
+use dioxus::prelude::*;
+use serde::Serialize;
+use shared::openbank::Transaction;
+
+#[derive(Serialize)]
+struct LoadStatementArgs<'a> {
+    path: &'a str,
+}
+
+/// Loads a statement file into the `Signal<Vec<Transaction>>` context the
+/// pages share.
+#[component]
+pub fn StatementLoader() -> Element {
+    let mut transactions = use_context::<Signal<Vec<Transaction>>>();
+    let mut path = use_signal(String::new);
+    let mut error = use_signal(|| None::<String>);
+
+    let load = move |e: FormEvent| async move {
+        e.prevent_default();
+        let path = path.read().clone();
+        if path.is_empty() {
+            return;
+        }
+        let args = LoadStatementArgs { path: &path };
+        match tauri::try_invoke::<Vec<Transaction>, _>("openbank_load_statement", &args).await {
+            Ok(loaded) => {
+                transactions.set(loaded);
+                error.set(None);
+            }
+            Err(e) => error.set(Some(e.note().to_string())),
+        }
+    };
+    let count = transactions.read().len();
+
+    rsx! {
+        form {
+            class: "row",
+            onsubmit: load,
+            input {
+                placeholder: "Statement file (.json)",
+                value: "{path}",
+                oninput: move |e| path.set(e.value())
+            }
+            button { r#type: "submit", "Load" }
+        }
+        match error() {
+            Some(message) => rsx! { p { "{message}" } },
+            None if count > 0 => rsx! { p { "{count} transactions loaded." } },
+            None => rsx! {},
+        }
+    }
+}